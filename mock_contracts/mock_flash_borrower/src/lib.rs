@@ -0,0 +1,96 @@
+use near_sdk::{
+    env, ext_contract, json_types::U128, near, AccountId, Gas, NearToken, PanicOnDefault, Promise,
+    PromiseOrValue,
+};
+
+const GAS_FOR_MT_TRANSFER_CALL: Gas = Gas::from_tgas(100);
+
+#[ext_contract(ext_mt)]
+pub trait MultiToken {
+    fn mt_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: String,
+        amount: U128,
+        approval: Option<(AccountId, u64)>,
+        memo: Option<String>,
+        msg: String,
+    );
+}
+
+#[ext_contract(ext_vault)]
+pub trait Vault {
+    fn flash_loan(&mut self, receiver_id: AccountId, amount: U128, msg: String);
+}
+
+/// Flash loan borrower whose behaviour is selected by the loan `msg`:
+/// - `"repay"` sends principal plus fee back to the vault
+/// - `"repay_principal"` sends only the principal back
+/// - `"repay_partial"` sends half the principal back
+/// - anything else keeps the loan
+#[near(contract_state)]
+#[derive(PanicOnDefault)]
+pub struct MockFlashBorrower {
+    /// Number of times `on_flash_loan` was called
+    loans: u64,
+    /// Initiator of the last flash loan
+    last_initiator: Option<AccountId>,
+}
+
+#[near]
+impl MockFlashBorrower {
+    #[init]
+    pub fn new() -> Self {
+        Self {
+            loans: 0,
+            last_initiator: None,
+        }
+    }
+
+    /// Takes a flash loan of `amount` from `vault_id` against this contract's bond
+    pub fn borrow(&mut self, vault_id: AccountId, amount: U128, msg: String) -> Promise {
+        ext_vault::ext(vault_id).flash_loan(env::current_account_id(), amount, msg)
+    }
+
+    pub fn on_flash_loan(
+        &mut self,
+        initiator: AccountId,
+        token: AccountId,
+        token_id: String,
+        amount: U128,
+        fee: U128,
+        msg: String,
+    ) -> PromiseOrValue<()> {
+        self.loans += 1;
+        self.last_initiator = Some(initiator);
+
+        let repayment = match msg.as_str() {
+            "repay" => amount.0 + fee.0,
+            "repay_principal" => amount.0,
+            "repay_partial" => amount.0 / 2,
+            _ => return PromiseOrValue::Value(()),
+        };
+
+        PromiseOrValue::Promise(
+            ext_mt::ext(token)
+                .with_attached_deposit(NearToken::from_yoctonear(1))
+                .with_static_gas(GAS_FOR_MT_TRANSFER_CALL)
+                .mt_transfer_call(
+                    env::predecessor_account_id(),
+                    token_id,
+                    U128(repayment),
+                    None,
+                    None,
+                    String::new(),
+                ),
+        )
+    }
+
+    pub fn loans(&self) -> u64 {
+        self.loans
+    }
+
+    pub fn last_initiator(&self) -> Option<AccountId> {
+        self.last_initiator.clone()
+    }
+}
@@ -1,23 +1,129 @@
 use std::collections::HashMap;
 
 use near_sdk::{
+    assert_one_yocto,
     borsh::{self, BorshDeserialize, BorshSerialize},
     collections::LookupMap,
-    env, near_bindgen,
-    AccountId, PanicOnDefault, Gas, Promise, PromiseResult,
+    env, ext_contract, near, near_bindgen,
+    serde_json::{self, json},
+    AccountId, Gas, NearToken, PanicOnDefault, Promise, PromiseOrValue, PromiseResult,
+};
+use near_sdk::{
+    json_types::{U128, U64},
+    BorshStorageKey,
 };
-use near_sdk::{json_types::U128, BorshStorageKey};
 
 // Type alias for consistency
 type TokenId = String;
-type Approval = Option<u64>;
+/// Approval presented on a transfer: `(owner_id, approval_id)`
+type Approval = (AccountId, u64);
+/// Approval spent by a transfer, restored if the transfer is refunded:
+/// `(approved_account_id, approval_id, amount)`
+type ClearedApproval = (AccountId, u64, U128);
 
 const GAS_FOR_MT_ON_TRANSFER: Gas = Gas::from_tgas(50);
 const GAS_FOR_RESOLVE_TRANSFER: Gas = Gas::from_tgas(50);
+const GAS_FOR_MT_ON_APPROVE: Gas = Gas::from_tgas(20);
+
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
 
 #[derive(BorshSerialize, BorshStorageKey)]
 enum StorageKey {
     TokenBalances,
+    TokenSupplies,
+    Approvals,
+    YieldStreams,
+    Approvers,
+}
+
+#[ext_contract(ext_mt_receiver)]
+pub trait MultiTokenReceiver {
+    fn mt_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        previous_owner_ids: Vec<AccountId>,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        msg: String,
+    ) -> PromiseOrValue<Vec<U128>>;
+}
+
+#[ext_contract(ext_mt_approval_receiver)]
+pub trait MultiTokenApprovalReceiver {
+    fn mt_on_approve(
+        &mut self,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        owner_id: AccountId,
+        approval_ids: Vec<u64>,
+        msg: String,
+    );
+}
+
+#[ext_contract(ext_self)]
+pub trait MultiTokenResolver {
+    fn mt_resolve_transfer(
+        &mut self,
+        previous_owner_ids: Vec<AccountId>,
+        receiver_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        approvals: Option<Vec<Option<Vec<ClearedApproval>>>>,
+    ) -> Vec<U128>;
+}
+
+/// Ways the mock can be told to misbehave, to exercise the failure paths of callers
+#[near(serializers = [borsh, json])]
+#[serde(tag = "mode", rename_all = "snake_case")]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum FailureMode {
+    #[default]
+    None,
+    /// Every `mt_transfer` fails
+    AllTransfers,
+    /// Transfers to `account_id` fail
+    TransfersTo { account_id: AccountId },
+    /// The first `calls` transfers succeed, every later one fails
+    TransfersAfter { calls: u32 },
+    /// Transfers spin until they run out of gas
+    BurnGas,
+    /// `mt_resolve_transfer` settles the transfer but returns bytes that are not JSON
+    MalformedResolve,
+}
+
+/// Token record returned by `mt_token`
+#[near(serializers = [json])]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Token {
+    pub token_id: TokenId,
+    /// Always `None`, every token of the mock is fungible
+    pub owner_id: Option<AccountId>,
+}
+
+/// Amount of one token an owner allows an account to transfer
+#[near(serializers = [borsh, json])]
+#[derive(Clone, Debug)]
+pub struct AccountApproval {
+    pub approval_id: u64,
+    /// Amount left to transfer
+    pub amount: U128,
+}
+
+/// Yield minted to a balance every second, as an interest-bearing token would
+#[near(serializers = [borsh, json])]
+#[derive(Clone, Debug)]
+pub struct YieldStream {
+    pub amount_per_second: U128,
+    /// When the stream was last settled into the balance, in nanoseconds
+    pub settled_at: U64,
+}
+
+impl YieldStream {
+    /// Whole seconds of yield accrued since the last settlement, and the amount they earned
+    fn pending(&self) -> (u64, u128) {
+        let seconds = (env::block_timestamp() - self.settled_at.0) / NANOS_PER_SECOND;
+        (seconds, self.amount_per_second.0 * u128::from(seconds))
+    }
 }
 
 #[near_bindgen]
@@ -26,7 +132,20 @@ pub struct MockMultiToken {
     /// Maps from (account_id, token_id) to balance
     balances: LookupMap<String, U128>,
     /// Token supplies by token_id
-    supplies: HashMap<String, U128>,
+    supplies: LookupMap<TokenId, U128>,
+    /// Maps from (owner_id, token_id) to the approvals by approved account
+    approvals: LookupMap<String, HashMap<AccountId, AccountApproval>>,
+    /// Maps from (approved account_id, token_id) to the owners that approved it.
+    /// Spent approvals are left in, so entries must be checked against `approvals`.
+    approvers: LookupMap<String, Vec<AccountId>>,
+    next_approval_id: u64,
+    /// Maps from (account_id, token_id) to the yield streaming into that balance
+    yield_streams: LookupMap<String, YieldStream>,
+    /// Account allowed to set the failure mode and move yield
+    owner: AccountId,
+    failure_mode: FailureMode,
+    /// Transfers attempted since the failure mode was last set
+    transfer_calls: u32,
 }
 
 #[near_bindgen]
@@ -35,18 +154,89 @@ impl MockMultiToken {
     pub fn new() -> Self {
         Self {
             balances: LookupMap::new(StorageKey::TokenBalances),
-            supplies: HashMap::new(),
+            supplies: LookupMap::new(StorageKey::TokenSupplies),
+            approvals: LookupMap::new(StorageKey::Approvals),
+            approvers: LookupMap::new(StorageKey::Approvers),
+            next_approval_id: 0,
+            yield_streams: LookupMap::new(StorageKey::YieldStreams),
+            owner: env::predecessor_account_id(),
+            failure_mode: FailureMode::None,
+            transfer_calls: 0,
         }
     }
 
+    /// Makes later calls fail as described by `mode`, `None` to behave again.
+    /// Resets the transfer count of `TransfersAfter`.
+    pub fn set_failure_mode(&mut self, mode: FailureMode) {
+        self.assert_owner();
+        self.failure_mode = mode;
+        self.transfer_calls = 0;
+    }
+
+    pub fn failure_mode(&self) -> FailureMode {
+        self.failure_mode.clone()
+    }
+
     /// Mint tokens to an account (for testing purposes)
     pub fn mint(&mut self, account_id: AccountId, token_id: String, amount: U128) {
-        let key = format!("{}:{}", account_id, token_id);
-        let current_balance = self.balances.get(&key).unwrap_or(U128(0));
-        self.balances.insert(&key, &U128(current_balance.0 + amount.0));
-        
-        let current_supply = self.supplies.get(&token_id).unwrap_or(&U128(0));
-        self.supplies.insert(token_id, U128(current_supply.0 + amount.0));
+        self.internal_mint(&account_id, &token_id, amount.0, None);
+    }
+
+    // Yield simulation, the balance of `account_id` behaves like an interest-bearing
+    // position whose value the owner moves up or down
+
+    /// Mints `amount` of yield to `account_id` straight away
+    pub fn credit_yield(&mut self, account_id: AccountId, token_id: TokenId, amount: U128) {
+        self.assert_owner();
+        self.internal_mint(&account_id, &token_id, amount.0, Some("yield"));
+    }
+
+    /// Burns `amount` from `account_id` to simulate a loss of the underlying
+    pub fn slash(&mut self, account_id: AccountId, token_id: TokenId, amount: U128) {
+        self.assert_owner();
+        self.internal_withdraw(&account_id, &token_id, amount.0);
+
+        let supply = self.supplies.get(&token_id).unwrap_or(U128(0));
+        self.supplies.insert(&token_id, &U128(supply.0 - amount.0));
+
+        emit_event(
+            "mt_burn",
+            vec![json!({
+                "owner_id": account_id,
+                "token_ids": [token_id],
+                "amounts": [amount],
+                "memo": "slash",
+            })],
+        );
+    }
+
+    /// Mints `amount_per_second` of yield to `account_id` for every second from now on,
+    /// `0` to stop. Yield already accrued at the previous rate is kept.
+    pub fn set_yield_rate(
+        &mut self,
+        account_id: AccountId,
+        token_id: TokenId,
+        amount_per_second: U128,
+    ) {
+        self.assert_owner();
+        self.internal_settle_yield(&account_id, &token_id);
+
+        let key = balance_key(&account_id, &token_id);
+        if amount_per_second.0 == 0 {
+            self.yield_streams.remove(&key);
+        } else {
+            let stream = YieldStream {
+                amount_per_second,
+                settled_at: U64(env::block_timestamp()),
+            };
+            self.yield_streams.insert(&key, &stream);
+        }
+    }
+
+    pub fn yield_rate(&self, account_id: AccountId, token_id: TokenId) -> U128 {
+        self.yield_streams
+            .get(&balance_key(&account_id, &token_id))
+            .map_or(U128(0), |stream| stream.amount_per_second)
     }
 
     // Multi-token core methods
@@ -56,15 +246,286 @@ impl MockMultiToken {
         receiver_id: AccountId,
         token_id: TokenId,
         amount: U128,
-        _approval: Approval,
-        _memo: Option<String>,
+        approval: Option<Approval>,
+        memo: Option<String>,
     ) {
-        let sender = env::predecessor_account_id();
-        let sender_key = format!("{}:{}", sender, token_id);
-        let receiver_key = format!("{}:{}", receiver_id, token_id);
+        assert_one_yocto();
+        self.internal_transfer_batch(
+            &env::predecessor_account_id(),
+            &receiver_id,
+            &[token_id],
+            &[amount],
+            Some(vec![approval]),
+            memo,
+        );
+    }
 
-        // Validate token exists
-        assert!(self.supplies.contains_key(&token_id), "Token does not exist");
+    #[payable]
+    pub fn mt_batch_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        approvals: Option<Vec<Option<Approval>>>,
+        memo: Option<String>,
+    ) {
+        assert_one_yocto();
+        self.internal_transfer_batch(
+            &env::predecessor_account_id(),
+            &receiver_id,
+            &token_ids,
+            &amounts,
+            approvals,
+            memo,
+        );
+    }
+
+    #[payable]
+    pub fn mt_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        amount: U128,
+        approval: Option<Approval>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<Vec<U128>> {
+        assert_one_yocto();
+        self.internal_transfer_call(
+            receiver_id,
+            vec![token_id],
+            vec![amount],
+            Some(vec![approval]),
+            memo,
+            msg,
+        )
+    }
+
+    #[payable]
+    pub fn mt_batch_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        approvals: Option<Vec<Option<Approval>>>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<Vec<U128>> {
+        assert_one_yocto();
+        self.internal_transfer_call(receiver_id, token_ids, amounts, approvals, memo, msg)
+    }
+
+    /// Refunds the amounts the receiver left unused and restores the approvals they
+    /// spent, then returns the amounts that were finally transferred
+    #[private]
+    pub fn mt_resolve_transfer(
+        &mut self,
+        previous_owner_ids: Vec<AccountId>,
+        receiver_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        approvals: Option<Vec<Option<Vec<ClearedApproval>>>>,
+    ) {
+        let used = self.internal_resolve_transfer(
+            previous_owner_ids,
+            receiver_id,
+            token_ids,
+            amounts,
+            approvals,
+        );
+
+        // Returns the used amounts by hand so that they can be replaced by garbage
+        if self.failure_mode == FailureMode::MalformedResolve {
+            env::value_return(b"not json");
+        } else {
+            env::value_return(&serde_json::to_vec(&used).unwrap());
+        }
+    }
+
+    // Approval management methods
+
+    /// Allows `account_id` to transfer `amounts` of `token_ids` owned by the predecessor,
+    /// replacing its earlier approvals for them. Calls `mt_on_approve` if `msg` is given.
+    #[payable]
+    pub fn mt_approve(
+        &mut self,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        account_id: AccountId,
+        msg: Option<String>,
+    ) -> Option<Promise> {
+        assert!(
+            env::attached_deposit() >= NearToken::from_yoctonear(1),
+            "Requires attached deposit of at least 1 yoctoNEAR"
+        );
+        assert_eq!(token_ids.len(), amounts.len(), "Invalid number of amounts");
+        let owner_id = env::predecessor_account_id();
+
+        let mut approval_ids = Vec::with_capacity(token_ids.len());
+        for (token_id, amount) in token_ids.iter().zip(&amounts) {
+            assert!(self.supplies.contains_key(token_id), "Token does not exist");
+            let approval_id = self.next_approval_id;
+            self.next_approval_id += 1;
+
+            let key = balance_key(&owner_id, token_id);
+            let mut approvals = self.approvals.get(&key).unwrap_or_default();
+            approvals.insert(
+                account_id.clone(),
+                AccountApproval {
+                    approval_id,
+                    amount: *amount,
+                },
+            );
+            self.approvals.insert(&key, &approvals);
+            self.internal_add_approver(&owner_id, token_id, &account_id);
+            approval_ids.push(approval_id);
+        }
+
+        msg.map(|msg| {
+            ext_mt_approval_receiver::ext(account_id)
+                .with_static_gas(GAS_FOR_MT_ON_APPROVE)
+                .mt_on_approve(token_ids, amounts, owner_id, approval_ids, msg)
+        })
+    }
+
+    #[payable]
+    pub fn mt_revoke(&mut self, token_ids: Vec<TokenId>, account_id: AccountId) {
+        assert_one_yocto();
+        let owner_id = env::predecessor_account_id();
+
+        for token_id in &token_ids {
+            let key = balance_key(&owner_id, token_id);
+            if let Some(mut approvals) = self.approvals.get(&key) {
+                approvals.remove(&account_id);
+                self.approvals.insert(&key, &approvals);
+            }
+            self.internal_remove_approver(&owner_id, token_id, &account_id);
+        }
+    }
+
+    #[payable]
+    pub fn mt_revoke_all(&mut self, token_ids: Vec<TokenId>) {
+        assert_one_yocto();
+        let owner_id = env::predecessor_account_id();
+
+        for token_id in &token_ids {
+            let approvals = self
+                .approvals
+                .remove(&balance_key(&owner_id, token_id))
+                .unwrap_or_default();
+            for account_id in approvals.keys() {
+                self.internal_remove_approver(&owner_id, token_id, account_id);
+            }
+        }
+    }
+
+    /// Checks whether `approved_account_id` may transfer `amounts` of `token_ids`.
+    /// Tokens of the mock have many owners, so a token counts as approved if any of
+    /// them approved at least the amount, with the given approval ID if any.
+    pub fn mt_is_approved(
+        &self,
+        token_ids: Vec<TokenId>,
+        approved_account_id: AccountId,
+        amounts: Vec<U128>,
+        approval_ids: Option<Vec<u64>>,
+    ) -> bool {
+        assert_eq!(token_ids.len(), amounts.len(), "Invalid number of amounts");
+
+        token_ids
+            .iter()
+            .zip(&amounts)
+            .enumerate()
+            .all(|(i, (token_id, amount))| {
+                let owners = self
+                    .approvers
+                    .get(&balance_key(&approved_account_id, token_id))
+                    .unwrap_or_default();
+                owners.iter().any(|owner_id| {
+                    self.mt_approval(
+                        owner_id.clone(),
+                        token_id.clone(),
+                        approved_account_id.clone(),
+                    )
+                    .is_some_and(|approval| {
+                        approval.amount.0 >= amount.0
+                            && approval_ids
+                                .as_ref()
+                                .is_none_or(|ids| ids.get(i) == Some(&approval.approval_id))
+                    })
+                })
+            })
+    }
+
+    /// Approval `owner_id` gave `account_id` for `token_id`, not part of NEP-245
+    pub fn mt_approval(
+        &self,
+        owner_id: AccountId,
+        token_id: TokenId,
+        account_id: AccountId,
+    ) -> Option<AccountApproval> {
+        self.approvals
+            .get(&balance_key(&owner_id, &token_id))
+            .and_then(|approvals| approvals.get(&account_id).cloned())
+    }
+
+    // Views
+
+    pub fn mt_balance_of(&self, account_id: AccountId, token_id: TokenId) -> U128 {
+        U128(self.internal_balance(&account_id, &token_id))
+    }
+
+    pub fn mt_batch_balance_of(&self, account_id: AccountId, token_ids: Vec<TokenId>) -> Vec<U128> {
+        token_ids
+            .iter()
+            .map(|token_id| U128(self.internal_balance(&account_id, token_id)))
+            .collect()
+    }
+
+    pub fn mt_supply(&self, token_id: TokenId) -> Option<U128> {
+        self.supplies.get(&token_id)
+    }
+
+    pub fn mt_batch_supply(&self, token_ids: Vec<TokenId>) -> Vec<Option<U128>> {
+        token_ids
+            .iter()
+            .map(|token_id| self.supplies.get(token_id))
+            .collect()
+    }
+
+    pub fn mt_token(&self, token_ids: Vec<TokenId>) -> Vec<Option<Token>> {
+        token_ids
+            .into_iter()
+            .map(|token_id| {
+                self.supplies.contains_key(&token_id).then_some(Token {
+                    token_id,
+                    owner_id: None,
+                })
+            })
+            .collect()
+    }
+}
+
+impl MockMultiToken {
+    /// Moves `amounts` of `token_ids` to `receiver_id` and logs an `mt_transfer` event.
+    /// Tokens come from the sender, or from the owner of the approval the sender presents.
+    /// Returns the previous owners and the approvals spent, for `mt_resolve_transfer`.
+    fn internal_transfer_batch(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        token_ids: &[TokenId],
+        amounts: &[U128],
+        approvals: Option<Vec<Option<Approval>>>,
+        memo: Option<String>,
+    ) -> (Vec<AccountId>, Vec<Option<Vec<ClearedApproval>>>) {
+        assert!(!token_ids.is_empty(), "Nothing to transfer");
+        assert_eq!(token_ids.len(), amounts.len(), "Invalid number of amounts");
+        let approvals = approvals.unwrap_or_else(|| vec![None; token_ids.len()]);
+        assert_eq!(
+            approvals.len(),
+            token_ids.len(),
+            "Invalid number of approvals"
+        );
 
         // Simulate storage deposit requirement - fail if receiver is "nonexistent.testnet"
         // This simulates realistic NEP-245 behavior where accounts need to exist
@@ -73,119 +534,375 @@ impl MockMultiToken {
             "Account does not exist or has no storage deposit"
         );
 
-        let sender_balance = self.balances.get(&sender_key).unwrap_or(U128(0));
-        assert!(sender_balance.0 >= amount.0, "Insufficient balance");
+        self.inject_transfer_failure(receiver_id);
+
+        let mut previous_owner_ids = Vec::with_capacity(token_ids.len());
+        let mut cleared_approvals = Vec::with_capacity(token_ids.len());
+        let mut logs = Vec::with_capacity(token_ids.len());
+
+        for ((token_id, amount), approval) in token_ids.iter().zip(amounts).zip(approvals) {
+            // Validate token exists
+            assert!(self.supplies.contains_key(token_id), "Token does not exist");
+            assert!(amount.0 > 0, "Amount must be positive");
 
-        // Perform the transfer
-        self.balances.insert(&sender_key, &U128(sender_balance.0 - amount.0));
-        
-        let receiver_balance = self.balances.get(&receiver_key).unwrap_or(U128(0));
-        self.balances.insert(&receiver_key, &U128(receiver_balance.0 + amount.0));
+            let (owner_id, cleared) = match approval {
+                Some((owner_id, approval_id)) => {
+                    let cleared = self.internal_spend_approval(
+                        &owner_id,
+                        token_id,
+                        sender_id,
+                        approval_id,
+                        amount.0,
+                    );
+                    (owner_id, Some(vec![cleared]))
+                }
+                None => (sender_id.clone(), None),
+            };
+            assert_ne!(&owner_id, receiver_id, "Sender and receiver must differ");
+
+            self.internal_withdraw(&owner_id, token_id, amount.0);
+            self.internal_deposit(receiver_id, token_id, amount.0);
+
+            logs.push(transfer_log(
+                &owner_id,
+                receiver_id,
+                token_id,
+                *amount,
+                cleared.as_ref().map(|_| sender_id),
+                memo.as_deref(),
+            ));
+            previous_owner_ids.push(owner_id);
+            cleared_approvals.push(cleared);
+        }
+
+        emit_event("mt_transfer", logs);
+
+        (previous_owner_ids, cleared_approvals)
     }
 
-    pub fn mt_transfer_call(
+    fn internal_transfer_call(
         &mut self,
         receiver_id: AccountId,
-        token_id: TokenId,
-        amount: U128,
-        approval: Approval,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        approvals: Option<Vec<Option<Approval>>>,
         memo: Option<String>,
         msg: String,
-    ) -> Promise {
-        let sender = env::predecessor_account_id();
-        self.mt_transfer(receiver_id.clone(), token_id.clone(), amount, approval, memo.clone());
+    ) -> PromiseOrValue<Vec<U128>> {
+        let sender_id = env::predecessor_account_id();
+        let (previous_owner_ids, cleared_approvals) = self.internal_transfer_batch(
+            &sender_id,
+            &receiver_id,
+            &token_ids,
+            &amounts,
+            approvals,
+            memo,
+        );
+        let cleared_approvals = cleared_approvals
+            .iter()
+            .any(Option::is_some)
+            .then_some(cleared_approvals);
 
         // Call the receiver contract's mt_on_transfer method
-        Promise::new(receiver_id.clone())
-            .function_call(
-                "mt_on_transfer".to_string(),
-                near_sdk::serde_json::to_vec(&near_sdk::serde_json::json!({
-                    "sender_id": sender,
-                    "previous_owner_id": sender.clone(),
-                    "token_ids": vec![token_id.clone()],
-                    "amounts": vec![amount],
-                    "msg": msg
-                })).unwrap(),
-                near_sdk::NearToken::from_yoctonear(0),
-                GAS_FOR_MT_ON_TRANSFER,
+        ext_mt_receiver::ext(receiver_id.clone())
+            .with_static_gas(GAS_FOR_MT_ON_TRANSFER)
+            .mt_on_transfer(
+                sender_id,
+                previous_owner_ids.clone(),
+                token_ids.clone(),
+                amounts.clone(),
+                msg,
             )
             .then(
-                Promise::new(env::current_account_id())
-                    .function_call(
-                        "mt_resolve_transfer".to_string(),
-                        near_sdk::serde_json::to_vec(&near_sdk::serde_json::json!({
-                            "sender_id": sender,
-                            "receiver_id": receiver_id,
-                            "token_id": token_id,
-                            "amount": amount
-                        })).unwrap(),
-                        near_sdk::NearToken::from_yoctonear(0),
-                        GAS_FOR_RESOLVE_TRANSFER,
-                    )
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_TRANSFER)
+                    .mt_resolve_transfer(
+                        previous_owner_ids,
+                        receiver_id,
+                        token_ids,
+                        amounts,
+                        cleared_approvals,
+                    ),
             )
+            .into()
     }
 
-    #[private]
-    pub fn mt_resolve_transfer(
+    fn internal_resolve_transfer(
         &mut self,
-        sender_id: AccountId,
+        previous_owner_ids: Vec<AccountId>,
         receiver_id: AccountId,
-        token_id: TokenId,
-        amount: U128,
-    ) -> U128 {
-        match env::promise_result(0) {
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        approvals: Option<Vec<Option<Vec<ClearedApproval>>>>,
+    ) -> Vec<U128> {
+        // The receiver keeps nothing if it failed or did not return one unused amount per token
+        let unused = match env::promise_result(0) {
             PromiseResult::Successful(result) => {
-                // Try to parse the result as unused amounts Vec<U128>
-                if let Ok(unused_amounts) = near_sdk::serde_json::from_slice::<Vec<U128>>(&result) {
-                    if let Some(unused) = unused_amounts.first() {
-                        if unused.0 > 0 {
-                            // Refund unused tokens
-                            let sender_key = format!("{}:{}", sender_id, token_id);
-                            let receiver_key = format!("{}:{}", receiver_id, token_id);
-                            
-                            let receiver_balance = self.balances.get(&receiver_key).unwrap_or(U128(0));
-                            let sender_balance = self.balances.get(&sender_key).unwrap_or(U128(0));
-                            
-                            self.balances.insert(&receiver_key, &U128(receiver_balance.0 - unused.0));
-                            self.balances.insert(&sender_key, &U128(sender_balance.0 + unused.0));
-                        }
-                        // Return amount used (total - unused)
-                        return U128(amount.0 - unused.0);
-                    }
+                match serde_json::from_slice::<Vec<U128>>(&result) {
+                    Ok(unused) if unused.len() == amounts.len() => unused,
+                    _ => amounts.clone(),
                 }
-                // No unused amounts vector returned, assume all was used
-                amount
-            }
-            PromiseResult::Failed => {
-                // Transfer failed, refund all tokens
-                let sender_key = format!("{}:{}", sender_id, token_id);
-                let receiver_key = format!("{}:{}", receiver_id, token_id);
-                
-                let receiver_balance = self.balances.get(&receiver_key).unwrap_or(U128(0));
-                let sender_balance = self.balances.get(&sender_key).unwrap_or(U128(0));
-                
-                self.balances.insert(&receiver_key, &U128(receiver_balance.0 - amount.0));
-                self.balances.insert(&sender_key, &U128(sender_balance.0 + amount.0));
-                
-                // Transfer failed, so nothing was used
-                U128(0)
             }
+            PromiseResult::Failed => amounts.clone(),
+        };
+        let mut approvals = approvals.unwrap_or_else(|| vec![None; amounts.len()]);
+
+        let mut logs = Vec::new();
+        let used = (0..amounts.len())
+            .map(|i| {
+                let (owner_id, token_id) = (&previous_owner_ids[i], &token_ids[i]);
+                // The receiver may have moved tokens on already, refund what is left
+                let refund = unused[i]
+                    .0
+                    .min(amounts[i].0)
+                    .min(self.internal_balance(&receiver_id, token_id));
+
+                if refund > 0 {
+                    self.internal_withdraw(&receiver_id, token_id, refund);
+                    self.internal_deposit(owner_id, token_id, refund);
+                    logs.push(transfer_log(
+                        &receiver_id,
+                        owner_id,
+                        token_id,
+                        U128(refund),
+                        None,
+                        None,
+                    ));
+
+                    for cleared in approvals
+                        .get_mut(i)
+                        .and_then(Option::take)
+                        .unwrap_or_default()
+                    {
+                        self.internal_restore_approval(owner_id, token_id, cleared, refund);
+                    }
+                }
+
+                U128(amounts[i].0 - refund)
+            })
+            .collect();
+
+        if !logs.is_empty() {
+            emit_event("mt_transfer", logs);
         }
+
+        used
     }
 
-    pub fn mt_balance_of(&self, account_id: AccountId, token_id: TokenId) -> U128 {
-        let key = format!("{}:{}", account_id, token_id);
-        self.balances.get(&key).unwrap_or(U128(0))
+    /// Spends `amount` of the approval `owner_id` gave `account_id` for `token_id`
+    fn internal_spend_approval(
+        &mut self,
+        owner_id: &AccountId,
+        token_id: &TokenId,
+        account_id: &AccountId,
+        approval_id: u64,
+        amount: u128,
+    ) -> ClearedApproval {
+        let key = balance_key(owner_id, token_id);
+        let mut approvals = self.approvals.get(&key).unwrap_or_default();
+
+        let approval = approvals.get_mut(account_id).expect("Not approved");
+        assert_eq!(approval.approval_id, approval_id, "Invalid approval ID");
+        assert!(approval.amount.0 >= amount, "Approved amount exceeded");
+        approval.amount.0 -= amount;
+        if approval.amount.0 == 0 {
+            approvals.remove(account_id);
+        }
+        self.approvals.insert(&key, &approvals);
+
+        (account_id.clone(), approval_id, U128(amount))
     }
 
-    pub fn mt_supply(&self, token_id: TokenId) -> Option<U128> {
-        self.supplies.get(&token_id).cloned()
+    /// Gives back up to `refund` of a spent approval, unless the owner replaced it since
+    fn internal_restore_approval(
+        &mut self,
+        owner_id: &AccountId,
+        token_id: &TokenId,
+        (account_id, approval_id, amount): ClearedApproval,
+        refund: u128,
+    ) {
+        let key = balance_key(owner_id, token_id);
+        let mut approvals = self.approvals.get(&key).unwrap_or_default();
+
+        let approval = approvals
+            .entry(account_id.clone())
+            .or_insert(AccountApproval {
+                approval_id,
+                amount: U128(0),
+            });
+        if approval.approval_id == approval_id {
+            approval.amount.0 += refund.min(amount.0);
+        }
+        self.approvals.insert(&key, &approvals);
+        self.internal_add_approver(owner_id, token_id, &account_id);
     }
 
-    pub fn mt_token(&self, token_ids: Vec<TokenId>) -> Vec<bool> {
-        token_ids
-            .into_iter()
-            .map(|token_id| self.supplies.contains_key(&token_id))
-            .collect()
+    fn internal_add_approver(
+        &mut self,
+        owner_id: &AccountId,
+        token_id: &TokenId,
+        account_id: &AccountId,
+    ) {
+        let key = balance_key(account_id, token_id);
+        let mut owners = self.approvers.get(&key).unwrap_or_default();
+        if !owners.contains(owner_id) {
+            owners.push(owner_id.clone());
+            self.approvers.insert(&key, &owners);
+        }
+    }
+
+    fn internal_remove_approver(
+        &mut self,
+        owner_id: &AccountId,
+        token_id: &TokenId,
+        account_id: &AccountId,
+    ) {
+        let key = balance_key(account_id, token_id);
+        if let Some(mut owners) = self.approvers.get(&key) {
+            owners.retain(|owner| owner != owner_id);
+            self.approvers.insert(&key, &owners);
+        }
+    }
+
+    fn assert_owner(&self) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only the owner can control the mock"
+        );
+    }
+
+    fn internal_mint(
+        &mut self,
+        account_id: &AccountId,
+        token_id: &TokenId,
+        amount: u128,
+        memo: Option<&str>,
+    ) {
+        self.internal_deposit(account_id, token_id, amount);
+
+        let current_supply = self.supplies.get(token_id).unwrap_or(U128(0));
+        self.supplies
+            .insert(token_id, &U128(current_supply.0 + amount));
+
+        let mut log = json!({
+            "owner_id": account_id,
+            "token_ids": [token_id],
+            "amounts": [U128(amount)],
+        });
+        if let Some(memo) = memo {
+            log["memo"] = json!(memo);
+        }
+        emit_event("mt_mint", vec![log]);
     }
-}
\ No newline at end of file
+
+    /// Balance including streamed yield that is not settled yet
+    fn internal_balance(&self, account_id: &AccountId, token_id: &TokenId) -> u128 {
+        let key = balance_key(account_id, token_id);
+        let pending = self
+            .yield_streams
+            .get(&key)
+            .map_or(0, |stream| stream.pending().1);
+
+        self.balances.get(&key).unwrap_or(U128(0)).0 + pending
+    }
+
+    /// Mints the yield streamed to a balance since it was last settled. The supply
+    /// only counts streamed yield once it is settled.
+    fn internal_settle_yield(&mut self, account_id: &AccountId, token_id: &TokenId) {
+        let key = balance_key(account_id, token_id);
+        let Some(mut stream) = self.yield_streams.get(&key) else {
+            return;
+        };
+
+        let (seconds, pending) = stream.pending();
+        // Only whole seconds are settled, the fraction keeps accruing
+        stream.settled_at.0 += seconds * NANOS_PER_SECOND;
+        self.yield_streams.insert(&key, &stream);
+
+        if pending > 0 {
+            let balance = self.balances.get(&key).unwrap_or(U128(0)).0;
+            self.balances.insert(&key, &U128(balance + pending));
+            let supply = self.supplies.get(token_id).unwrap_or(U128(0));
+            self.supplies.insert(token_id, &U128(supply.0 + pending));
+        }
+    }
+
+    fn internal_deposit(&mut self, account_id: &AccountId, token_id: &TokenId, amount: u128) {
+        self.internal_settle_yield(account_id, token_id);
+        let balance = self.internal_balance(account_id, token_id);
+        self.balances
+            .insert(&balance_key(account_id, token_id), &U128(balance + amount));
+    }
+
+    fn internal_withdraw(&mut self, account_id: &AccountId, token_id: &TokenId, amount: u128) {
+        self.internal_settle_yield(account_id, token_id);
+        let balance = self.internal_balance(account_id, token_id);
+        assert!(balance >= amount, "Insufficient balance");
+        self.balances
+            .insert(&balance_key(account_id, token_id), &U128(balance - amount));
+    }
+
+    fn inject_transfer_failure(&mut self, receiver_id: &AccountId) {
+        self.transfer_calls += 1;
+
+        match &self.failure_mode {
+            FailureMode::AllTransfers => env::panic_str("Injected failure: all transfers fail"),
+            FailureMode::TransfersTo { account_id } if account_id == receiver_id => env::panic_str(
+                &format!("Injected failure: transfers to {} fail", account_id),
+            ),
+            FailureMode::TransfersAfter { calls } if self.transfer_calls > *calls => {
+                env::panic_str(&format!(
+                    "Injected failure: transfers after {} calls fail",
+                    calls
+                ))
+            }
+            FailureMode::BurnGas => {
+                let mut hash = env::sha256(b"burn");
+                loop {
+                    hash = env::sha256(&hash);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn balance_key(account_id: &AccountId, token_id: &TokenId) -> String {
+    format!("{}:{}", account_id, token_id)
+}
+
+/// One `mt_transfer` event entry, optional fields are left out when absent
+fn transfer_log(
+    old_owner_id: &AccountId,
+    new_owner_id: &AccountId,
+    token_id: &TokenId,
+    amount: U128,
+    authorized_id: Option<&AccountId>,
+    memo: Option<&str>,
+) -> serde_json::Value {
+    let mut log = json!({
+        "old_owner_id": old_owner_id,
+        "new_owner_id": new_owner_id,
+        "token_ids": [token_id],
+        "amounts": [amount],
+    });
+    if let Some(authorized_id) = authorized_id {
+        log["authorized_id"] = json!(authorized_id);
+    }
+    if let Some(memo) = memo {
+        log["memo"] = json!(memo);
+    }
+    log
+}
+
+/// Logs a NEP-245 event with `data` as its entries
+fn emit_event(event: &str, data: Vec<serde_json::Value>) {
+    let event = json!({
+        "standard": "nep245",
+        "version": "1.0.0",
+        "event": event,
+        "data": data,
+    });
+    env::log_str(&format!("EVENT_JSON:{}", event));
+}
@@ -0,0 +1,88 @@
+use near_sdk::{assert_one_yocto, near_bindgen, AccountId};
+
+use crate::contract_standards::events::{VaultAllowlistUpdate, VaultConfigChange};
+use crate::error::{ensure, VaultError};
+use crate::{TokenizedMTVault, TokenizedMTVaultExt};
+
+#[near_bindgen]
+impl TokenizedMTVault {
+    /// Turns allowlist mode on or off. While it is on, only allowlisted accounts can
+    /// deposit, receive deposited shares or be sent shares.
+    #[payable]
+    pub fn set_allowlist_enabled(&mut self, enabled: bool) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        VaultConfigChange {
+            parameter: "allowlist_enabled",
+            old_value: self.allowlist_enabled.into(),
+            new_value: enabled.into(),
+        }
+        .emit();
+
+        self.allowlist_enabled = enabled;
+    }
+
+    /// Adds `account_ids` to the allowlist. The vault pays for the storage.
+    #[payable]
+    pub fn add_to_allowlist(&mut self, account_ids: Vec<AccountId>) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        let added: Vec<AccountId> = account_ids
+            .into_iter()
+            .filter(|account_id| self.allowlist.insert(account_id))
+            .collect();
+        emit_allowlist_updates(&added, true);
+    }
+
+    #[payable]
+    pub fn remove_from_allowlist(&mut self, account_ids: Vec<AccountId>) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        let removed: Vec<AccountId> = account_ids
+            .into_iter()
+            .filter(|account_id| self.allowlist.remove(account_id))
+            .collect();
+        emit_allowlist_updates(&removed, false);
+    }
+
+    pub fn allowlist_enabled(&self) -> bool {
+        self.allowlist_enabled
+    }
+
+    pub fn is_allowlisted(&self, account_id: AccountId) -> bool {
+        self.allowlist.contains(&account_id)
+    }
+}
+
+impl TokenizedMTVault {
+    /// Whether `account_id` may hold newly minted or transferred shares
+    pub fn is_allowed(&self, account_id: &AccountId) -> bool {
+        !self.allowlist_enabled || self.allowlist.contains(account_id)
+    }
+
+    pub fn assert_allowed(&self, account_id: &AccountId) {
+        ensure!(
+            self.is_allowed(account_id),
+            VaultError::NotAllowlisted(account_id.clone())
+        );
+    }
+}
+
+/// Logs one entry per account whose allowlist status changed, if any did
+fn emit_allowlist_updates(account_ids: &[AccountId], allowed: bool) {
+    if account_ids.is_empty() {
+        return;
+    }
+
+    let updates: Vec<VaultAllowlistUpdate> = account_ids
+        .iter()
+        .map(|account_id| VaultAllowlistUpdate {
+            account_id,
+            allowed,
+        })
+        .collect();
+    VaultAllowlistUpdate::emit_many(&updates);
+}
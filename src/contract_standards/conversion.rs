@@ -0,0 +1,39 @@
+//! Share and asset conversions for a given vault state.
+//!
+//! Every conversion in the vault, from the `VaultCore` defaults to the amounts
+//! actually minted and burned, goes through these two functions. A virtual unit
+//! of assets and a virtual share keep the rate defined after a total loss and make
+//! inflating the price with a donation to an almost empty vault unprofitable. As
+//! they are offset alike, a sole holder can still redeem every asset.
+
+use crate::mul_div::{mul_div, Rounding};
+
+/// Converts assets to shares for a given vault state
+pub fn convert_to_shares(
+    assets: u128,
+    total_assets: u128,
+    total_supply: u128,
+    rounding: Rounding,
+) -> u128 {
+    // Handle empty vault case - return 1:1 ratio for first deposit
+    if total_supply == 0 {
+        return assets;
+    }
+
+    mul_div(assets, total_supply + 1, total_assets + 1, rounding)
+}
+
+/// Converts shares to assets for a given vault state
+pub fn convert_to_assets(
+    shares: u128,
+    total_assets: u128,
+    total_supply: u128,
+    rounding: Rounding,
+) -> u128 {
+    // For empty vault, assume 1:1 ratio for consistency
+    if total_supply == 0 {
+        return shares;
+    }
+
+    mul_div(shares, total_assets + 1, total_supply + 1, rounding)
+}
@@ -2,6 +2,9 @@ use near_sdk::json_types::U128;
 use near_sdk::serde::Serialize;
 use near_sdk::{env, AccountIdRef};
 
+/// Version of the `vault` event standard, bumped whenever an event's shape changes
+pub const VAULT_STANDARD_VERSION: &str = "1.0.0";
+
 #[derive(Serialize, Debug)]
 #[serde(crate = "near_sdk::serde")]
 #[serde(tag = "standard")]
@@ -9,11 +12,11 @@ use near_sdk::{env, AccountIdRef};
 #[serde(rename_all = "snake_case")]
 #[allow(unused)]
 pub(crate) enum NearEvent<'a> {
-    Nep000(Nep000Event<'a>),
+    Vault(VaultEvent<'a>),
 }
 
 #[allow(unused)]
-impl<'a> NearEvent<'a> {
+impl NearEvent<'_> {
     fn to_json_string(&self) -> String {
         // Events cannot fail to serialize so fine to panic on error
         #[allow(clippy::redundant_closure)]
@@ -33,7 +36,8 @@ impl<'a> NearEvent<'a> {
     }
 }
 
-/// Data to log for an FT mint event. To log this event, call [`.emit()`](FtMint::emit).
+/// Data to log when assets were deposited for shares. `shares` are those the owner
+/// received, net of any referral fee. To log this event, call [`.emit()`](VaultDeposit::emit).
 #[must_use]
 #[derive(Serialize, Debug, Clone)]
 #[serde(crate = "near_sdk::serde")]
@@ -45,6 +49,8 @@ pub struct VaultDeposit<'a> {
     pub shares: U128,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub memo: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub referrer: Option<&'a AccountIdRef>,
 }
 
 #[allow(unused)]
@@ -55,15 +61,47 @@ impl VaultDeposit<'_> {
         Self::emit_many(&[self])
     }
 
-    /// Emits an FT mint event, through [`env::log_str`](near_sdk::env::log_str),
-    /// where each [`FtMint`] represents the data of each mint.
+    /// Emits a vault deposit event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`VaultDeposit`] represents one deposit.
     pub fn emit_many(data: &[VaultDeposit<'_>]) {
-        new_000_v1(Nep000EventKind::VaultDeposit(data)).emit()
+        new_vault_event(VaultEventKind::VaultDeposit(data)).emit()
+    }
+}
+
+/// Data to log when a deposit was refunded in full instead of minting shares.
+/// To log this event, call [`.emit()`](VaultDepositRefund::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct VaultDepositRefund<'a> {
+    pub sender_id: &'a AccountIdRef,
+    pub owner_id: &'a AccountIdRef,
+    pub token_id: &'a str,
+    pub amount: U128,
+    /// Stable [`VaultError`](crate::error::VaultError) code of the refund reason
+    pub code: &'a str,
+    pub reason: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<&'a str>,
+}
+
+#[allow(unused)]
+impl VaultDepositRefund<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a vault deposit refund event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`VaultDepositRefund`] represents one refunded deposit.
+    pub fn emit_many(data: &[VaultDepositRefund<'_>]) {
+        new_vault_event(VaultEventKind::VaultDepositRefund(data)).emit()
     }
 }
 
-/// Data to log for an FT transfer event. To log this event,
-/// call [`.emit()`](FtTransfer::emit).
+/// Data to log when shares were redeemed and the assets sent out. To log this event,
+/// call [`.emit()`](VaultWithdraw::emit).
 #[must_use]
 #[derive(Serialize, Debug, Clone)]
 #[serde(crate = "near_sdk::serde")]
@@ -85,19 +123,356 @@ impl VaultWithdraw<'_> {
         Self::emit_many(&[self])
     }
 
-    /// Emits an FT transfer event, through [`env::log_str`](near_sdk::env::log_str),
-    /// where each [`FtTransfer`] represents the data of each transfer.
+    /// Emits a vault withdraw event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`VaultWithdraw`] represents one withdrawal.
     pub fn emit_many(data: &[VaultWithdraw<'_>]) {
-        new_000_v1(Nep000EventKind::VaultWithdraw(data)).emit()
+        new_vault_event(VaultEventKind::VaultWithdraw(data)).emit()
+    }
+}
+
+/// Data to log when the asset transfer of a withdrawal failed and the burned
+/// shares were minted back. To log this event, call [`.emit()`](VaultWithdrawFailed::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct VaultWithdrawFailed<'a> {
+    pub owner_id: &'a AccountIdRef,
+    pub receiver_id: &'a AccountIdRef,
+    pub token_id: &'a str,
+    pub shares: U128,
+    pub assets: U128,
+    pub reason: &'a str,
+    /// Stable [`VaultError`](crate::error::VaultError) code of the failure
+    pub code: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<&'a str>,
+}
+
+#[allow(unused)]
+impl VaultWithdrawFailed<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a vault withdraw failed event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`VaultWithdrawFailed`] represents one rolled back withdrawal.
+    pub fn emit_many(data: &[VaultWithdrawFailed<'_>]) {
+        new_vault_event(VaultEventKind::VaultWithdrawFailed(data)).emit()
+    }
+}
+
+/// Data to log for a vault sync event. To log this event,
+/// call [`.emit()`](VaultSync::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct VaultSync<'a> {
+    pub token_id: &'a str,
+    pub balance: U128,
+    pub previous_total_assets: U128,
+    pub total_assets: U128,
+    pub profit: U128,
+    pub loss: U128,
+    pub sweepable_surplus: U128,
+}
+
+#[allow(unused)]
+impl VaultSync<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a vault sync event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`VaultSync`] represents the result of one reconciliation.
+    pub fn emit_many(data: &[VaultSync<'_>]) {
+        new_vault_event(VaultEventKind::VaultSync(data)).emit()
+    }
+}
+
+/// Data to log for a vault rescue event. To log this event,
+/// call [`.emit()`](VaultRescue::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct VaultRescue<'a> {
+    pub token: &'a AccountIdRef,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_id: Option<&'a str>,
+    pub amount: U128,
+    pub receiver_id: &'a AccountIdRef,
+}
+
+#[allow(unused)]
+impl VaultRescue<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a vault rescue event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`VaultRescue`] represents tokens sent out of the vault by the owner.
+    pub fn emit_many(data: &[VaultRescue<'_>]) {
+        new_vault_event(VaultEventKind::VaultRescue(data)).emit()
+    }
+}
+
+/// Data to log for a vault flash loan event. To log this event,
+/// call [`.emit()`](VaultFlashLoan::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct VaultFlashLoan<'a> {
+    pub borrower_id: &'a AccountIdRef,
+    pub token_id: &'a str,
+    pub amount: U128,
+    pub fee: U128,
+    pub repaid: U128,
+    pub bond_seized: U128,
+}
+
+#[allow(unused)]
+impl VaultFlashLoan<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a vault flash loan event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`VaultFlashLoan`] represents a resolved loan.
+    pub fn emit_many(data: &[VaultFlashLoan<'_>]) {
+        new_vault_event(VaultEventKind::VaultFlashLoan(data)).emit()
+    }
+}
+
+/// Data to log when a flash loan bond is posted or withdrawn. To log this event,
+/// call [`.emit()`](VaultFlashLoanBond::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct VaultFlashLoanBond<'a> {
+    pub account_id: &'a AccountIdRef,
+    pub token_id: &'a str,
+    pub posted: U128,
+    pub withdrawn: U128,
+    pub bond: U128,
+}
+
+#[allow(unused)]
+impl VaultFlashLoanBond<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a vault flash loan bond event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`VaultFlashLoanBond`] represents a change to a borrower's bond.
+    pub fn emit_many(data: &[VaultFlashLoanBond<'_>]) {
+        new_vault_event(VaultEventKind::VaultFlashLoanBond(data)).emit()
+    }
+}
+
+/// Data to log when fees are credited to the vault's assets. To log this event,
+/// call [`.emit()`](VaultFeeAccrued::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct VaultFeeAccrued<'a> {
+    pub token_id: &'a str,
+    pub source: &'a str,
+    pub amount: U128,
+}
+
+#[allow(unused)]
+impl VaultFeeAccrued<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a vault fee accrued event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`VaultFeeAccrued`] represents fees added to `total_assets`.
+    pub fn emit_many(data: &[VaultFeeAccrued<'_>]) {
+        new_vault_event(VaultEventKind::VaultFeeAccrued(data)).emit()
+    }
+}
+
+/// Data to log for a vault owner change event. To log this event,
+/// call [`.emit()`](VaultOwnerChange::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct VaultOwnerChange<'a> {
+    pub previous_owner_id: &'a AccountIdRef,
+    pub new_owner_id: &'a AccountIdRef,
+}
+
+#[allow(unused)]
+impl VaultOwnerChange<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a vault owner change event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`VaultOwnerChange`] represents one transfer of ownership.
+    pub fn emit_many(data: &[VaultOwnerChange<'_>]) {
+        new_vault_event(VaultEventKind::VaultOwnerChange(data)).emit()
+    }
+}
+
+/// Data to log for a vault config change event. To log this event,
+/// call [`.emit()`](VaultConfigChange::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct VaultConfigChange<'a> {
+    /// Name of the setting, matching its view method
+    pub parameter: &'a str,
+    pub old_value: serde_json::Value,
+    pub new_value: serde_json::Value,
+}
+
+#[allow(unused)]
+impl VaultConfigChange<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a vault config change event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`VaultConfigChange`] represents one owner setting that changed.
+    pub fn emit_many(data: &[VaultConfigChange<'_>]) {
+        new_vault_event(VaultEventKind::VaultConfigChange(data)).emit()
+    }
+}
+
+/// Data to log for a vault delegate event. To log this event,
+/// call [`.emit()`](VaultDelegate::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct VaultDelegate<'a> {
+    pub delegator_id: &'a AccountIdRef,
+    pub from_delegatee_id: &'a AccountIdRef,
+    pub to_delegatee_id: &'a AccountIdRef,
+}
+
+#[allow(unused)]
+impl VaultDelegate<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a vault delegate event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`VaultDelegate`] represents one change of delegatee.
+    pub fn emit_many(data: &[VaultDelegate<'_>]) {
+        new_vault_event(VaultEventKind::VaultDelegate(data)).emit()
+    }
+}
+
+/// Data to log for a vault allowlist update event. To log this event,
+/// call [`.emit()`](VaultAllowlistUpdate::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct VaultAllowlistUpdate<'a> {
+    pub account_id: &'a AccountIdRef,
+    /// `true` if the account was added, `false` if it was removed
+    pub allowed: bool,
+}
+
+#[allow(unused)]
+impl VaultAllowlistUpdate<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a vault allowlist update event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`VaultAllowlistUpdate`] represents one account added or removed.
+    pub fn emit_many(data: &[VaultAllowlistUpdate<'_>]) {
+        new_vault_event(VaultEventKind::VaultAllowlistUpdate(data)).emit()
+    }
+}
+
+/// Data to log for a vault freeze event. To log this event,
+/// call [`.emit()`](VaultFreeze::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct VaultFreeze<'a> {
+    pub account_id: &'a AccountIdRef,
+    /// `true` if the account was frozen, `false` if it was unfrozen
+    pub frozen: bool,
+    /// Owner or guardian who changed the status
+    pub actor_id: &'a AccountIdRef,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<&'a str>,
+}
+
+#[allow(unused)]
+impl VaultFreeze<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a vault freeze event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`VaultFreeze`] represents one account frozen or unfrozen.
+    pub fn emit_many(data: &[VaultFreeze<'_>]) {
+        new_vault_event(VaultEventKind::VaultFreeze(data)).emit()
+    }
+}
+
+/// Data to log for a vault seize event. To log this event,
+/// call [`.emit()`](VaultSeize::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct VaultSeize<'a> {
+    pub account_id: &'a AccountIdRef,
+    pub recovery_id: &'a AccountIdRef,
+    pub guardian_id: &'a AccountIdRef,
+    pub shares: U128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<&'a str>,
+}
+
+#[allow(unused)]
+impl VaultSeize<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a vault seize event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`VaultSeize`] represents the shares taken from one frozen account.
+    pub fn emit_many(data: &[VaultSeize<'_>]) {
+        new_vault_event(VaultEventKind::VaultSeize(data)).emit()
     }
 }
 
 #[derive(Serialize, Debug)]
 #[serde(crate = "near_sdk::serde")]
-pub(crate) struct Nep000Event<'a> {
+pub(crate) struct VaultEvent<'a> {
     version: &'static str,
     #[serde(flatten)]
-    event_kind: Nep000EventKind<'a>,
+    event_kind: VaultEventKind<'a>,
 }
 
 #[derive(Serialize, Debug)]
@@ -105,18 +480,31 @@ pub(crate) struct Nep000Event<'a> {
 #[serde(tag = "event", content = "data")]
 #[serde(rename_all = "snake_case")]
 #[allow(clippy::enum_variant_names)]
-enum Nep000EventKind<'a> {
+enum VaultEventKind<'a> {
     VaultDeposit(&'a [VaultDeposit<'a>]),
+    VaultDepositRefund(&'a [VaultDepositRefund<'a>]),
     VaultWithdraw(&'a [VaultWithdraw<'a>]),
+    VaultWithdrawFailed(&'a [VaultWithdrawFailed<'a>]),
+    VaultSync(&'a [VaultSync<'a>]),
+    VaultRescue(&'a [VaultRescue<'a>]),
+    VaultFlashLoan(&'a [VaultFlashLoan<'a>]),
+    VaultFlashLoanBond(&'a [VaultFlashLoanBond<'a>]),
+    VaultFeeAccrued(&'a [VaultFeeAccrued<'a>]),
+    VaultOwnerChange(&'a [VaultOwnerChange<'a>]),
+    VaultConfigChange(&'a [VaultConfigChange<'a>]),
+    VaultDelegate(&'a [VaultDelegate<'a>]),
+    VaultAllowlistUpdate(&'a [VaultAllowlistUpdate<'a>]),
+    VaultFreeze(&'a [VaultFreeze<'a>]),
+    VaultSeize(&'a [VaultSeize<'a>]),
 }
 
-fn new_000<'a>(version: &'static str, event_kind: Nep000EventKind<'a>) -> NearEvent<'a> {
-    NearEvent::Nep000(Nep000Event {
+fn new_vault<'a>(version: &'static str, event_kind: VaultEventKind<'a>) -> NearEvent<'a> {
+    NearEvent::Vault(VaultEvent {
         version,
         event_kind,
     })
 }
 
-fn new_000_v1(event_kind: Nep000EventKind) -> NearEvent {
-    new_000("1.0.0", event_kind)
+fn new_vault_event(event_kind: VaultEventKind) -> NearEvent {
+    new_vault(VAULT_STANDARD_VERSION, event_kind)
 }
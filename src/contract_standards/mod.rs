@@ -1,13 +1,21 @@
 use near_contract_standards::fungible_token::FungibleTokenCore;
 use near_sdk::{json_types::U128, AccountId, PromiseOrValue};
-use uint::construct_uint;
 
+use crate::mul_div::Rounding;
 use crate::multi_token::MultiTokenReceiver;
 
+mod conversion;
 pub mod events;
 
-construct_uint! {
-    pub struct U256(4);
+pub use conversion::{convert_to_assets, convert_to_shares};
+
+pub use uint_types::U256;
+
+#[allow(clippy::manual_div_ceil)]
+mod uint_types {
+    uint::construct_uint! {
+        pub struct U256(4);
+    }
 }
 
 #[allow(unused)]
@@ -28,83 +36,67 @@ pub trait VaultCore: FungibleTokenCore + MultiTokenReceiver {
         memo: Option<String>,
     ) -> PromiseOrValue<U128>;
 
+    /// `assets` in shares at the current rate. Every default below converts through here.
+    fn convert_to_shares_rounded(&self, assets: u128, rounding: Rounding) -> u128 {
+        convert_to_shares(
+            assets,
+            self.total_assets().0,
+            self.ft_total_supply().0,
+            rounding,
+        )
+    }
+
+    /// `shares` in assets at the current rate. Every default below converts through here.
+    fn convert_to_assets_rounded(&self, shares: u128, rounding: Rounding) -> u128 {
+        convert_to_assets(
+            shares,
+            self.total_assets().0,
+            self.ft_total_supply().0,
+            rounding,
+        )
+    }
+
     fn convert_to_shares(&self, assets: U128) -> U128 {
-        if (self.total_assets().0 == 0u128) {
-            return assets;
-        }
-
-        U256::from(self.ft_total_supply().0)
-            .checked_mul(U256::from(assets.0))
-            .expect("Too much assets")
-            .checked_div(U256::from(self.total_assets().0))
-            .unwrap()
-            .as_u128()
-            .into()
+        U128(self.convert_to_shares_rounded(assets.0, Rounding::Down))
     }
 
     fn convert_to_assets(&self, shares: U128) -> U128 {
-        assert!(self.ft_total_supply().0 > 0, "No shares issued yet");
-
-        U256::from(shares.0)
-            .checked_mul(U256::from(self.total_assets().0))
-            .expect("Too many shares")
-            .checked_div(U256::from(self.ft_total_supply().0))
-            .unwrap()
-            .as_u128()
-            .into()
+        U128(self.convert_to_assets_rounded(shares.0, Rounding::Down))
     }
 
     fn max_deposit(&self, receiver_id: AccountId) -> U128 {
-        let max_assets = u128::MAX - self.total_assets().0;
-        let max_assets_from_shares = self
-            .convert_to_assets(U128(u128::MAX - self.ft_total_supply().0))
-            .0;
-
-        if max_assets < max_assets_from_shares {
-            max_assets.into()
-        } else {
-            max_assets_from_shares.into()
-        }
+        U128(u128::MAX - self.total_assets().0)
     }
 
+    /// Shares a deposit of `assets` mints, rounded down in favour of the vault
     fn preview_deposit(&self, assets: U128) -> U128 {
-        assert!(assets <= self.max_deposit(near_sdk::env::predecessor_account_id()));
-        self.convert_to_shares(assets)
+        U128(self.convert_to_shares_rounded(assets.0, Rounding::Down))
     }
 
     fn max_mint(&self, receiver_id: AccountId) -> U128 {
-        let max_shares = u128::MAX - self.ft_total_supply().0;
-        let max_shares_from_assets = self
-            .convert_to_shares(U128(u128::MAX - self.total_assets().0))
-            .0;
-
-        if max_shares < max_shares_from_assets {
-            max_shares.into()
-        } else {
-            max_shares_from_assets.into()
-        }
+        U128(u128::MAX - self.ft_total_supply().0)
     }
 
+    /// Assets needed to mint exactly `shares`, rounded up in favour of the vault
     fn preview_mint(&self, shares: U128) -> U128 {
-        assert!(shares <= self.max_mint(near_sdk::env::predecessor_account_id()));
-        self.convert_to_assets(shares)
+        U128(self.convert_to_assets_rounded(shares.0, Rounding::Up))
     }
 
     fn max_redeem(&self, owner_id: AccountId) -> U128 {
         self.ft_balance_of(owner_id)
     }
 
+    /// Assets paid out for redeeming `shares`, rounded down in favour of the vault
     fn preview_redeem(&self, shares: U128) -> U128 {
-        assert!(shares <= self.max_redeem(near_sdk::env::predecessor_account_id()));
-        self.convert_to_assets(shares)
+        U128(self.convert_to_assets_rounded(shares.0, Rounding::Down))
     }
 
     fn max_withdraw(&self, owner_id: AccountId) -> U128 {
-        self.convert_to_assets(self.ft_balance_of(owner_id))
+        U128(self.convert_to_assets_rounded(self.ft_balance_of(owner_id).0, Rounding::Down))
     }
 
+    /// Shares burned to withdraw exactly `assets`, rounded up in favour of the vault
     fn preview_withdraw(&self, assets: U128) -> U128 {
-        assert!(assets <= self.max_withdraw(near_sdk::env::predecessor_account_id()));
-        self.convert_to_shares(assets)
+        U128(self.convert_to_shares_rounded(assets.0, Rounding::Up))
     }
 }
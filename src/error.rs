@@ -0,0 +1,253 @@
+//! Failures of the vault, each with a stable code.
+//!
+//! Every failure panics with `VAULT_ERROR[<CODE>] <message>`, so integrators can
+//! match the code and ignore the message, which may change. Deposits that are
+//! refunded instead of failing log the same code in a `vault_deposit_refund` event.
+
+use std::fmt;
+
+use near_sdk::{env, AccountId};
+
+use crate::mul_div::MathError;
+
+/// Prefix of every vault panic message, followed by `[<CODE>]`
+pub const ERROR_PREFIX: &str = "VAULT_ERROR";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VaultError {
+    // Access
+    NotOwner,
+    NotOwnerOrGuardian,
+    NotGuardian,
+    NotRegistered(AccountId),
+    NotAllowlisted(AccountId),
+    AccountFrozen(AccountId),
+    AlreadyFrozen(AccountId),
+    NotFrozen(AccountId),
+
+    // Storage
+    InsufficientStorage { required: u128, available: u128 },
+
+    // Deposits
+    WrongAsset,
+    WrongTokenId,
+    BatchDeposit,
+    SlippageExceeded { shares: u128, min_shares: u128 },
+    ZeroDeposit { assets: u128 },
+    SelfReferral,
+    ReferrerNotRegistered(AccountId),
+
+    // Withdrawals
+    ExceedsMaxRedeem { shares: u128, max_redeem: u128 },
+    ExceedsMaxWithdraw { assets: u128, max_withdraw: u128 },
+    InsufficientShares,
+    InsufficientAssets,
+    ZeroWithdrawal,
+    AssetTransferFailed,
+
+    // Flash loans
+    FlashLoansDisabled,
+    FlashLoanInProgress,
+    NoFlashLoan,
+    NotFlashLoanReceiver,
+    InsufficientFlashLoanBond { required: u128, bond: u128 },
+
+    // Owner operations
+    ZeroAmount,
+    ExceedsSweepableSurplus,
+    NothingToSeize,
+    FeeTooHigh { max_bps: u16 },
+    InvalidPriceHistoryLength { max: u32 },
+    BalanceQueryFailed,
+    AccountingChanged,
+
+    // Views
+    EmptyWindow,
+    PriceHistoryTooShort,
+    BlockNotInPast,
+
+    // Arithmetic
+    Math(u128, u128, u128, MathError),
+    Overflow(&'static str),
+}
+
+impl VaultError {
+    /// Stable identifier of the failure, independent of its message
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NotOwner => "NOT_OWNER",
+            Self::NotOwnerOrGuardian => "NOT_OWNER_OR_GUARDIAN",
+            Self::NotGuardian => "NOT_GUARDIAN",
+            Self::NotRegistered(_) => "NOT_REGISTERED",
+            Self::NotAllowlisted(_) => "NOT_ALLOWLISTED",
+            Self::AccountFrozen(_) => "ACCOUNT_FROZEN",
+            Self::AlreadyFrozen(_) => "ALREADY_FROZEN",
+            Self::NotFrozen(_) => "NOT_FROZEN",
+            Self::InsufficientStorage { .. } => "INSUFFICIENT_STORAGE",
+            Self::WrongAsset => "WRONG_ASSET",
+            Self::WrongTokenId => "WRONG_TOKEN_ID",
+            Self::BatchDeposit => "BATCH_DEPOSIT",
+            Self::SlippageExceeded { .. } => "SLIPPAGE_EXCEEDED",
+            Self::ZeroDeposit { .. } => "ZERO_DEPOSIT",
+            Self::SelfReferral => "SELF_REFERRAL",
+            Self::ReferrerNotRegistered(_) => "REFERRER_NOT_REGISTERED",
+            Self::ExceedsMaxRedeem { .. } => "EXCEEDS_MAX_REDEEM",
+            Self::ExceedsMaxWithdraw { .. } => "EXCEEDS_MAX_WITHDRAW",
+            Self::InsufficientShares => "INSUFFICIENT_SHARES",
+            Self::InsufficientAssets => "INSUFFICIENT_ASSETS",
+            Self::ZeroWithdrawal => "ZERO_WITHDRAWAL",
+            Self::AssetTransferFailed => "ASSET_TRANSFER_FAILED",
+            Self::FlashLoansDisabled => "FLASH_LOANS_DISABLED",
+            Self::FlashLoanInProgress => "FLASH_LOAN_IN_PROGRESS",
+            Self::NoFlashLoan => "NO_FLASH_LOAN",
+            Self::NotFlashLoanReceiver => "NOT_FLASH_LOAN_RECEIVER",
+            Self::InsufficientFlashLoanBond { .. } => "INSUFFICIENT_FLASH_LOAN_BOND",
+            Self::ZeroAmount => "ZERO_AMOUNT",
+            Self::ExceedsSweepableSurplus => "EXCEEDS_SWEEPABLE_SURPLUS",
+            Self::NothingToSeize => "NOTHING_TO_SEIZE",
+            Self::FeeTooHigh { .. } => "FEE_TOO_HIGH",
+            Self::InvalidPriceHistoryLength { .. } => "INVALID_PRICE_HISTORY_LENGTH",
+            Self::BalanceQueryFailed => "BALANCE_QUERY_FAILED",
+            Self::AccountingChanged => "ACCOUNTING_CHANGED",
+            Self::EmptyWindow => "EMPTY_WINDOW",
+            Self::PriceHistoryTooShort => "PRICE_HISTORY_TOO_SHORT",
+            Self::BlockNotInPast => "BLOCK_NOT_IN_PAST",
+            Self::Math(.., MathError::DivisionByZero) => "DIVISION_BY_ZERO",
+            Self::Math(.., MathError::Overflow) => "MATH_OVERFLOW",
+            Self::Overflow(_) => "OVERFLOW",
+        }
+    }
+
+    /// Human readable description, without the prefix and code
+    pub fn message(&self) -> String {
+        Message(self).to_string()
+    }
+
+    /// Aborts the call with this error
+    pub fn panic(&self) -> ! {
+        env::panic_str(&self.to_string())
+    }
+}
+
+impl fmt::Display for VaultError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}[{}] {}", ERROR_PREFIX, self.code(), Message(self))
+    }
+}
+
+struct Message<'a>(&'a VaultError);
+
+impl fmt::Display for Message<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            VaultError::NotOwner => write!(f, "Only the owner can call this method"),
+            VaultError::NotOwnerOrGuardian => {
+                write!(f, "Only the owner or the guardian can call this method")
+            }
+            VaultError::NotGuardian => write!(f, "Only the guardian can seize shares"),
+            VaultError::NotRegistered(account_id) => {
+                write!(f, "Account {} is not registered", account_id)
+            }
+            VaultError::NotAllowlisted(account_id) => {
+                write!(f, "Account {} is not allowlisted", account_id)
+            }
+            VaultError::AccountFrozen(account_id) => write!(f, "Account {} is frozen", account_id),
+            VaultError::AlreadyFrozen(account_id) => {
+                write!(f, "Account {} is already frozen", account_id)
+            }
+            VaultError::NotFrozen(account_id) => write!(f, "Account {} is not frozen", account_id),
+            VaultError::InsufficientStorage {
+                required,
+                available,
+            } => write!(
+                f,
+                "Storage of {} yoctoNEAR required, only {} deposited",
+                required, available
+            ),
+            VaultError::WrongAsset => write!(f, "Only the underlying asset can be deposited"),
+            VaultError::WrongTokenId => write!(f, "Only the configured token_id can be deposited"),
+            VaultError::BatchDeposit => write!(f, "Only single token deposits supported"),
+            VaultError::SlippageExceeded { shares, min_shares } => write!(
+                f,
+                "Deposit would mint {} shares, below the minimum of {}",
+                shares, min_shares
+            ),
+            VaultError::ZeroDeposit { assets } => {
+                write!(f, "Depositing {} assets would mint no shares", assets)
+            }
+            VaultError::SelfReferral => write!(f, "Cannot refer your own deposit"),
+            VaultError::ReferrerNotRegistered(referrer_id) => {
+                write!(f, "Referrer {} is not registered", referrer_id)
+            }
+            VaultError::ExceedsMaxRedeem { shares, max_redeem } => write!(
+                f,
+                "Exceeds max redeem: {} shares requested, {} redeemable",
+                shares, max_redeem
+            ),
+            VaultError::ExceedsMaxWithdraw {
+                assets,
+                max_withdraw,
+            } => write!(
+                f,
+                "Exceeds max withdraw: {} assets requested, {} withdrawable",
+                assets, max_withdraw
+            ),
+            VaultError::InsufficientShares => write!(f, "Insufficient shares"),
+            VaultError::InsufficientAssets => write!(f, "Insufficient vault assets"),
+            VaultError::ZeroWithdrawal => write!(f, "No assets to withdraw"),
+            VaultError::AssetTransferFailed => write!(f, "Asset transfer failed"),
+            VaultError::FlashLoansDisabled => write!(f, "Flash loans are disabled"),
+            VaultError::FlashLoanInProgress => write!(f, "Flash loan in progress"),
+            VaultError::NoFlashLoan => write!(f, "No flash loan in progress"),
+            VaultError::NotFlashLoanReceiver => {
+                write!(
+                    f,
+                    "Only the receiver can take a flash loan against its bond"
+                )
+            }
+            VaultError::InsufficientFlashLoanBond { required, bond } => write!(
+                f,
+                "Flash loan bond of {} does not cover the required {}",
+                bond, required
+            ),
+            VaultError::ZeroAmount => write!(f, "Amount must be positive"),
+            VaultError::ExceedsSweepableSurplus => write!(
+                f,
+                "Cannot rescue more than the sweepable surplus of the vault asset"
+            ),
+            VaultError::NothingToSeize => write!(f, "No shares to seize"),
+            VaultError::FeeTooHigh { max_bps } => write!(f, "Fee cannot exceed {} bps", max_bps),
+            VaultError::InvalidPriceHistoryLength { max } => {
+                write!(f, "Price history length must be between 1 and {}", max)
+            }
+            VaultError::BalanceQueryFailed => write!(f, "Failed to fetch the vault asset balance"),
+            VaultError::AccountingChanged => {
+                write!(f, "Vault accounting changed during sync, retry")
+            }
+            VaultError::EmptyWindow => write!(f, "Window must not be empty"),
+            VaultError::PriceHistoryTooShort => {
+                write!(f, "Price history does not cover the requested window")
+            }
+            VaultError::BlockNotInPast => write!(f, "Block height must be in the past"),
+            VaultError::Math(x, y, denominator, error) => {
+                write!(
+                    f,
+                    "Cannot compute {} * {} / {}: {}",
+                    x, y, denominator, error
+                )
+            }
+            VaultError::Overflow(what) => write!(f, "{} out of range", what),
+        }
+    }
+}
+
+/// Panics with the error unless `condition` holds. The error is only built on failure.
+macro_rules! ensure {
+    ($condition:expr, $error:expr) => {
+        if !$condition {
+            $crate::error::VaultError::panic(&$error)
+        }
+    };
+}
+
+pub(crate) use ensure;
@@ -0,0 +1,374 @@
+use near_sdk::{
+    assert_one_yocto, env, ext_contract, json_types::U128, near, near_bindgen, AccountId, Gas,
+    NearToken, Promise, PromiseOrValue, PromiseResult,
+};
+
+use crate::contract_standards::events::{
+    VaultConfigChange, VaultFeeAccrued, VaultFlashLoan, VaultFlashLoanBond,
+};
+use crate::error::{ensure, VaultError};
+use crate::internal::ext_self;
+use crate::mul_div::{mul_div, Rounding};
+use crate::multi_token::ext_mt_core;
+use crate::{TokenizedMTVault, TokenizedMTVaultExt, GAS_FOR_FT_TRANSFER};
+
+const GAS_FOR_RESOLVE_FLASH_LOAN_TRANSFER: Gas = Gas::from_tgas(20);
+const GAS_FOR_RESOLVE_FLASH_LOAN: Gas = Gas::from_tgas(20);
+const GAS_FOR_RESOLVE_BOND_WITHDRAWAL: Gas = Gas::from_tgas(10);
+const MAX_FLASH_LOAN_FEE_BPS: u16 = 10_000;
+
+/// Interface the borrower of a flash loan must implement.
+#[ext_contract(ext_flash_loan_receiver)]
+pub trait FlashLoanReceiver {
+    /// Called once `amount` of the vault asset has been transferred to the borrower.
+    ///
+    /// Before the returned promise resolves, the borrower must send `amount + fee`
+    /// back to the vault with `mt_transfer_call`. Anything missing is taken from its bond.
+    fn on_flash_loan(
+        &mut self,
+        initiator: AccountId,
+        token: AccountId,
+        token_id: String,
+        amount: U128,
+        fee: U128,
+        msg: String,
+    ) -> PromiseOrValue<()>;
+}
+
+/// Flash loan that has been sent out and not resolved yet
+#[near(serializers = [borsh])]
+#[derive(Clone, Debug)]
+pub struct FlashLoan {
+    pub borrower: AccountId,
+    pub amount: u128,
+    pub fee: u128,
+    pub repaid: u128,
+}
+
+#[near_bindgen]
+impl TokenizedMTVault {
+    /// Lends `amount` of the vault asset to `receiver_id` and calls its `on_flash_loan`.
+    ///
+    /// Deposits, withdrawals and syncs are rejected until the loan is resolved.
+    /// NEAR cannot revert the initial transfer, so `receiver_id` must have posted a
+    /// bond covering the amount plus fee, and whatever it does not repay is taken
+    /// from that bond. The fee is earned either way, so only `receiver_id` itself
+    /// may take out a loan against its bond.
+    pub fn flash_loan(&mut self, receiver_id: AccountId, amount: U128, msg: String) -> Promise {
+        ensure!(self.flash_loans_enabled, VaultError::FlashLoansDisabled);
+        ensure!(
+            env::predecessor_account_id() == receiver_id,
+            VaultError::NotFlashLoanReceiver
+        );
+        self.assert_no_flash_loan();
+        ensure!(amount.0 > 0, VaultError::ZeroAmount);
+        ensure!(
+            amount.0 <= self.total_assets,
+            VaultError::InsufficientAssets
+        );
+
+        let fee = mul_div(
+            amount.0,
+            self.flash_loan_fee_bps as u128,
+            MAX_FLASH_LOAN_FEE_BPS as u128,
+            Rounding::Up,
+        );
+        let owed = amount
+            .0
+            .checked_add(fee)
+            .unwrap_or_else(|| VaultError::Overflow("Flash loan").panic());
+        let bond = self.flash_loan_bonds.get(&receiver_id).unwrap_or(0);
+        ensure!(
+            owed <= bond,
+            VaultError::InsufficientFlashLoanBond {
+                required: owed,
+                bond
+            }
+        );
+
+        self.flash_loan = Some(FlashLoan {
+            borrower: receiver_id.clone(),
+            amount: amount.0,
+            fee,
+            repaid: 0,
+        });
+        self.internal_on_accounting_change();
+
+        ext_mt_core::ext(self.asset.clone())
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .with_static_gas(GAS_FOR_FT_TRANSFER)
+            .with_unused_gas_weight(0)
+            .mt_transfer(
+                receiver_id,
+                self.asset_token_id.clone(),
+                amount,
+                None,
+                Some("Flash loan".to_string()),
+            )
+            .then(
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_FLASH_LOAN_TRANSFER)
+                    .resolve_flash_loan_transfer(env::predecessor_account_id(), msg),
+            )
+    }
+
+    #[private]
+    pub fn resolve_flash_loan_transfer(
+        &mut self,
+        initiator: AccountId,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        let loan = self
+            .flash_loan
+            .clone()
+            .unwrap_or_else(|| VaultError::NoFlashLoan.panic());
+
+        match env::promise_result(0) {
+            PromiseResult::Successful(_) => PromiseOrValue::Promise(
+                ext_flash_loan_receiver::ext(loan.borrower.clone())
+                    .on_flash_loan(
+                        initiator,
+                        self.asset.clone(),
+                        self.asset_token_id.clone(),
+                        U128(loan.amount),
+                        U128(loan.fee),
+                        msg,
+                    )
+                    .then(
+                        ext_self::ext(env::current_account_id())
+                            .with_static_gas(GAS_FOR_RESOLVE_FLASH_LOAN)
+                            .with_unused_gas_weight(0)
+                            .resolve_flash_loan(),
+                    ),
+            ),
+            _ => {
+                // Nothing left the vault, just close the loan
+                self.flash_loan = None;
+                self.internal_on_accounting_change();
+
+                PromiseOrValue::Value(0.into())
+            }
+        }
+    }
+
+    #[private]
+    pub fn resolve_flash_loan(&mut self) -> U128 {
+        let loan = self
+            .flash_loan
+            .take()
+            .unwrap_or_else(|| VaultError::NoFlashLoan.panic());
+
+        // The bond cannot be withdrawn while the loan is open, so it covers the shortfall
+        let shortfall = (loan.amount + loan.fee).saturating_sub(loan.repaid);
+        if shortfall > 0 {
+            let bond = self.flash_loan_bonds.get(&loan.borrower).unwrap_or(0);
+            self.internal_set_flash_loan_bond(&loan.borrower, bond.saturating_sub(shortfall));
+        }
+
+        self.total_assets = self
+            .total_assets
+            .checked_add(loan.fee)
+            .unwrap_or_else(|| VaultError::Overflow("Total assets").panic());
+        self.internal_on_accounting_change();
+
+        if loan.fee > 0 {
+            VaultFeeAccrued {
+                token_id: &self.asset_token_id,
+                source: "flash_loan",
+                amount: U128(loan.fee),
+            }
+            .emit();
+        }
+
+        VaultFlashLoan {
+            borrower_id: &loan.borrower,
+            token_id: &self.asset_token_id,
+            amount: U128(loan.amount),
+            fee: U128(loan.fee),
+            repaid: U128(loan.repaid),
+            bond_seized: U128(shortfall),
+        }
+        .emit();
+
+        U128(loan.repaid)
+    }
+
+    #[payable]
+    pub fn set_flash_loan_config(&mut self, enabled: bool, fee_bps: u16) {
+        assert_one_yocto();
+        self.assert_owner();
+        ensure!(
+            fee_bps <= MAX_FLASH_LOAN_FEE_BPS,
+            VaultError::FeeTooHigh {
+                max_bps: MAX_FLASH_LOAN_FEE_BPS
+            }
+        );
+
+        VaultConfigChange::emit_many(&[
+            VaultConfigChange {
+                parameter: "flash_loans_enabled",
+                old_value: self.flash_loans_enabled.into(),
+                new_value: enabled.into(),
+            },
+            VaultConfigChange {
+                parameter: "flash_loan_fee_bps",
+                old_value: self.flash_loan_fee_bps.into(),
+                new_value: fee_bps.into(),
+            },
+        ]);
+
+        self.flash_loans_enabled = enabled;
+        self.flash_loan_fee_bps = fee_bps;
+    }
+
+    pub fn flash_loans_enabled(&self) -> bool {
+        self.flash_loans_enabled
+    }
+
+    pub fn flash_loan_fee_bps(&self) -> u16 {
+        self.flash_loan_fee_bps
+    }
+
+    pub fn is_flash_loan_open(&self) -> bool {
+        self.flash_loan.is_some()
+    }
+
+    /// Bond `account_id` has posted to borrow, see [`flash_loan`](TokenizedMTVault::flash_loan)
+    pub fn flash_loan_bond(&self, account_id: AccountId) -> U128 {
+        U128(self.flash_loan_bonds.get(&account_id).unwrap_or(0))
+    }
+
+    /// Sends `amount` of the caller's flash loan bond back to it.
+    #[payable]
+    pub fn withdraw_flash_loan_bond(&mut self, amount: U128) -> Promise {
+        assert_one_yocto();
+        self.assert_no_flash_loan();
+        ensure!(amount.0 > 0, VaultError::ZeroAmount);
+
+        let account_id = env::predecessor_account_id();
+        let bond = self.flash_loan_bonds.get(&account_id).unwrap_or(0);
+        ensure!(
+            amount.0 <= bond,
+            VaultError::InsufficientFlashLoanBond {
+                required: amount.0,
+                bond
+            }
+        );
+
+        self.internal_set_flash_loan_bond(&account_id, bond - amount.0);
+        self.internal_start_outflow(amount.0);
+
+        ext_mt_core::ext(self.asset.clone())
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .with_static_gas(GAS_FOR_FT_TRANSFER)
+            .mt_transfer(
+                account_id.clone(),
+                self.asset_token_id.clone(),
+                amount,
+                None,
+                Some("Flash loan bond".to_string()),
+            )
+            .then(
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_BOND_WITHDRAWAL)
+                    .resolve_withdraw_flash_loan_bond(account_id, amount),
+            )
+    }
+
+    #[private]
+    pub fn resolve_withdraw_flash_loan_bond(
+        &mut self,
+        account_id: AccountId,
+        amount: U128,
+    ) -> U128 {
+        self.internal_finish_outflow(amount.0);
+
+        match env::promise_result(0) {
+            PromiseResult::Successful(_) => {
+                VaultFlashLoanBond {
+                    account_id: &account_id,
+                    token_id: &self.asset_token_id,
+                    posted: U128(0),
+                    withdrawn: amount,
+                    bond: self.flash_loan_bond(account_id.clone()),
+                }
+                .emit();
+
+                amount
+            }
+            _ => {
+                // Transfer failed - the bond is still held by the vault
+                let bond = self.flash_loan_bonds.get(&account_id).unwrap_or(0);
+                self.internal_set_flash_loan_bond(
+                    &account_id,
+                    bond.checked_add(amount.0)
+                        .unwrap_or_else(|| VaultError::Overflow("Flash loan bond").panic()),
+                );
+
+                0.into()
+            }
+        }
+    }
+}
+
+impl TokenizedMTVault {
+    pub fn assert_no_flash_loan(&self) {
+        ensure!(self.flash_loan.is_none(), VaultError::FlashLoanInProgress);
+    }
+
+    /// Books a transfer from the borrower of the open flash loan as repayment.
+    /// Returns the unused amount, i.e. anything above principal plus fee.
+    pub fn internal_repay_flash_loan(&mut self, amount: u128) -> u128 {
+        let loan = self
+            .flash_loan
+            .as_mut()
+            .unwrap_or_else(|| VaultError::NoFlashLoan.panic());
+
+        let outstanding = (loan.amount + loan.fee).saturating_sub(loan.repaid);
+        let used = amount.min(outstanding);
+        loan.repaid += used;
+
+        amount - used
+    }
+
+    /// Adds a transfer from `account_id` to its flash loan bond and logs it
+    pub fn internal_post_flash_loan_bond(&mut self, account_id: &AccountId, amount: u128) {
+        let bond = self
+            .flash_loan_bonds
+            .get(account_id)
+            .unwrap_or(0)
+            .checked_add(amount)
+            .unwrap_or_else(|| VaultError::Overflow("Flash loan bond").panic());
+        self.internal_set_flash_loan_bond(account_id, bond);
+
+        VaultFlashLoanBond {
+            account_id,
+            token_id: &self.asset_token_id,
+            posted: U128(amount),
+            withdrawn: U128(0),
+            bond: U128(bond),
+        }
+        .emit();
+    }
+
+    /// Sets the bond of `account_id`, keeping the total held for bonds in step
+    fn internal_set_flash_loan_bond(&mut self, account_id: &AccountId, bond: u128) {
+        let previous = self.flash_loan_bonds.get(account_id).unwrap_or(0);
+        self.total_flash_loan_bonds = (self.total_flash_loan_bonds - previous)
+            .checked_add(bond)
+            .unwrap_or_else(|| VaultError::Overflow("Flash loan bonds").panic());
+
+        if bond == 0 {
+            self.flash_loan_bonds.remove(account_id);
+        } else {
+            self.flash_loan_bonds.insert(account_id, &bond);
+        }
+        self.internal_bump_accounting_nonce();
+    }
+
+    pub fn is_flash_loan_borrower(&self, account_id: &AccountId) -> bool {
+        self.flash_loan
+            .as_ref()
+            .is_some_and(|loan| loan.borrower == *account_id)
+    }
+}
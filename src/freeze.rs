@@ -0,0 +1,130 @@
+use near_sdk::{assert_one_yocto, env, json_types::U128, near_bindgen, AccountId};
+
+use crate::contract_standards::events::{VaultConfigChange, VaultFreeze, VaultSeize};
+use crate::error::{ensure, VaultError};
+use crate::{TokenizedMTVault, TokenizedMTVaultExt};
+
+#[near_bindgen]
+impl TokenizedMTVault {
+    /// Sets the account that can seize frozen shares, `None` to disable seizing.
+    #[payable]
+    pub fn set_guardian(&mut self, guardian: Option<AccountId>) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        VaultConfigChange {
+            parameter: "guardian",
+            old_value: self.guardian.as_ref().map(|id| id.as_str()).into(),
+            new_value: guardian.as_ref().map(|id| id.as_str()).into(),
+        }
+        .emit();
+
+        self.guardian = guardian;
+    }
+
+    pub fn guardian(&self) -> Option<AccountId> {
+        self.guardian.clone()
+    }
+
+    /// Freezes `account_id`, which can then no longer send, receive or redeem shares.
+    /// Callable by the owner and the guardian. Balances and the share price are untouched.
+    #[payable]
+    pub fn freeze(&mut self, account_id: AccountId, memo: Option<String>) {
+        assert_one_yocto();
+        self.assert_owner_or_guardian();
+        ensure!(
+            self.frozen.insert(&account_id),
+            VaultError::AlreadyFrozen(account_id)
+        );
+
+        VaultFreeze {
+            account_id: &account_id,
+            frozen: true,
+            actor_id: &env::predecessor_account_id(),
+            memo: memo.as_deref(),
+        }
+        .emit();
+    }
+
+    #[payable]
+    pub fn unfreeze(&mut self, account_id: AccountId, memo: Option<String>) {
+        assert_one_yocto();
+        self.assert_owner_or_guardian();
+        ensure!(
+            self.frozen.remove(&account_id),
+            VaultError::NotFrozen(account_id)
+        );
+
+        VaultFreeze {
+            account_id: &account_id,
+            frozen: false,
+            actor_id: &env::predecessor_account_id(),
+            memo: memo.as_deref(),
+        }
+        .emit();
+    }
+
+    /// Moves all shares of the frozen `account_id` to `recovery_id` and returns them.
+    /// Only the guardian can seize. The shares change hands without being burned, so
+    /// the share price stays the same.
+    #[payable]
+    pub fn seize(
+        &mut self,
+        account_id: AccountId,
+        recovery_id: AccountId,
+        memo: Option<String>,
+    ) -> U128 {
+        assert_one_yocto();
+        let guardian = env::predecessor_account_id();
+        ensure!(
+            Some(&guardian) == self.guardian.as_ref(),
+            VaultError::NotGuardian
+        );
+        ensure!(
+            self.frozen.contains(&account_id),
+            VaultError::NotFrozen(account_id)
+        );
+        self.assert_not_frozen(&recovery_id);
+        self.assert_allowed(&recovery_id);
+
+        let shares = self.token.accounts.get(&account_id).unwrap_or(0);
+        ensure!(shares > 0, VaultError::NothingToSeize);
+
+        self.token
+            .internal_transfer(&account_id, &recovery_id, shares, memo.clone());
+        // Seizing is up to the guardian, so the vault covers the checkpoints
+        self.internal_on_share_balance_change(&[&account_id, &recovery_id], None);
+
+        VaultSeize {
+            account_id: &account_id,
+            recovery_id: &recovery_id,
+            guardian_id: &guardian,
+            shares: U128(shares),
+            memo: memo.as_deref(),
+        }
+        .emit();
+
+        U128(shares)
+    }
+
+    pub fn is_frozen(&self, account_id: AccountId) -> bool {
+        self.frozen.contains(&account_id)
+    }
+}
+
+impl TokenizedMTVault {
+    pub fn assert_not_frozen(&self, account_id: &AccountId) {
+        ensure!(
+            !self.frozen.contains(account_id),
+            VaultError::AccountFrozen(account_id.clone())
+        );
+    }
+
+    fn assert_owner_or_guardian(&self) {
+        let caller = env::predecessor_account_id();
+        ensure!(
+            caller == self.owner || Some(&caller) == self.guardian.as_ref(),
+            VaultError::NotOwnerOrGuardian
+        );
+    }
+}
@@ -1,9 +1,16 @@
 use near_contract_standards::fungible_token::{events::FtBurn, FungibleTokenCore};
-use near_sdk::{env, ext_contract, json_types::U128, AccountId, Gas, NearToken, Promise};
+use near_sdk::{
+    env, ext_contract,
+    json_types::{U128, U64},
+    AccountId, Gas, NearToken, Promise, PromiseOrValue,
+};
 
 use crate::{
+    contract_standards::{convert_to_assets, convert_to_shares, events::VaultDepositRefund},
+    error::{ensure, VaultError},
     mul_div::{mul_div, Rounding},
     multi_token::ext_mt_core,
+    sync::REFUND_SETTLEMENT_BLOCKS,
     TokenizedMTVault, GAS_FOR_FT_TRANSFER,
 };
 
@@ -17,6 +24,32 @@ pub trait _ExtSelf {
         assets: U128,
         memo: Option<String>,
     );
+
+    fn resolve_sync(&mut self, accounting_nonce: U64, unsettled_refunds: U128);
+
+    fn resolve_rescue_mt(
+        &mut self,
+        token: AccountId,
+        token_id: String,
+        amount: U128,
+        receiver_id: AccountId,
+    );
+
+    fn resolve_rescue_surplus(
+        &mut self,
+        amount: U128,
+        receiver_id: AccountId,
+        accounting_nonce: U64,
+        unsettled_refunds: U128,
+    );
+
+    fn resolve_rescue_ft(&mut self, token: AccountId, amount: U128, receiver_id: AccountId);
+
+    fn resolve_flash_loan_transfer(&mut self, initiator: AccountId, msg: String);
+
+    fn resolve_flash_loan(&mut self);
+
+    fn resolve_withdraw_flash_loan_bond(&mut self, account_id: AccountId, amount: U128);
 }
 
 impl TokenizedMTVault {
@@ -56,23 +89,30 @@ impl TokenizedMTVault {
         let receiver_id = receiver_id.unwrap_or(owner.clone());
 
         // Checks
-        assert!(
+        self.assert_no_flash_loan();
+        self.assert_not_frozen(&receiver_id);
+        ensure!(
             self.token.ft_balance_of(owner.clone()).0 >= shares_to_burn,
-            "Insufficient shares"
+            VaultError::InsufficientShares
         );
-        assert!(assets_to_transfer > 0, "No assets to withdraw");
-        assert!(
-            assets_to_transfer <= self.total_assets,
-            "Insufficient vault assets"
+        ensure!(assets_to_transfer > 0, VaultError::ZeroWithdrawal);
+        ensure!(
+            assets_to_transfer <= self.internal_free_assets(),
+            VaultError::InsufficientAssets
         );
 
         // Effects - CEI Pattern: Update state before external call
         // Burn shares immediately (prevents reuse)
         self.token.internal_withdraw(&owner, shares_to_burn);
+        self.internal_on_share_balance_change(&[&owner], Some(&owner));
         self.total_assets = self
             .total_assets
             .checked_sub(assets_to_transfer)
-            .expect("Total assets underflow");
+            .unwrap_or_else(|| VaultError::Overflow("Total assets").panic());
+        self.internal_on_accounting_change();
+
+        self.internal_start_pending_withdrawal(&owner, shares_to_burn, assets_to_transfer);
+        self.internal_start_outflow(assets_to_transfer);
 
         FtBurn {
             owner_id: &owner,
@@ -91,31 +131,180 @@ impl TokenizedMTVault {
         )
     }
 
-    pub fn internal_convert_to_shares(&self, assets: u128, rounding: Rounding) -> u128 {
-        let total_supply = self.token.ft_total_supply().0;
+    /// Returns the whole deposit to the sender through `mt_resolve_transfer`,
+    /// logging why it was refused
+    pub fn internal_refund_deposit(
+        &mut self,
+        sender_id: &AccountId,
+        owner_id: &AccountId,
+        amount: U128,
+        error: VaultError,
+        memo: Option<&str>,
+    ) -> PromiseOrValue<Vec<U128>> {
+        VaultDepositRefund {
+            sender_id,
+            owner_id,
+            token_id: &self.asset_token_id,
+            amount,
+            code: error.code(),
+            reason: &error.message(),
+            memo,
+        }
+        .emit();
+
+        self.internal_record_refund(amount.0);
+        PromiseOrValue::Value(vec![amount])
+    }
 
-        // Handle empty vault case - return 1:1 ratio for first deposit
-        if total_supply == 0 {
-            return assets;
+    /// Records `amount` of the vault asset handed back to a depositor. The asset
+    /// contract only takes it back in `mt_resolve_transfer`, which the vault is not
+    /// told about, so it counts as in flight for `REFUND_SETTLEMENT_BLOCKS`.
+    pub fn internal_record_refund(&mut self, amount: u128) {
+        if amount == 0 {
+            return;
         }
 
-        let supply_adj = total_supply;
-        let assets_adj = self.total_assets + 1;
+        self.recent_refunds = self
+            .internal_unsettled_refunds()
+            .checked_add(amount)
+            .unwrap_or_else(|| VaultError::Overflow("Refunds").panic());
+        self.last_refund_block = env::block_height();
+        self.internal_bump_accounting_nonce();
+    }
+
+    /// Refunded deposits that may still be held by the vault
+    pub fn internal_unsettled_refunds(&self) -> u128 {
+        if env::block_height() > self.last_refund_block + REFUND_SETTLEMENT_BLOCKS {
+            return 0;
+        }
+
+        self.recent_refunds
+    }
+
+    /// Books `amount` of the vault asset as sent out, until its transfer resolves
+    pub fn internal_start_outflow(&mut self, amount: u128) {
+        self.in_flight_outflows = self
+            .in_flight_outflows
+            .checked_add(amount)
+            .unwrap_or_else(|| VaultError::Overflow("In-flight outflows").panic());
+    }
+
+    /// Must be called when a transfer booked with `internal_start_outflow` resolves,
+    /// whether it succeeded or not
+    pub fn internal_finish_outflow(&mut self, amount: u128) {
+        self.in_flight_outflows = self.in_flight_outflows.saturating_sub(amount);
+        self.internal_bump_accounting_nonce();
+    }
 
-        mul_div(assets, supply_adj, assets_adj, rounding)
+    pub fn internal_convert_to_shares(&self, assets: u128, rounding: Rounding) -> u128 {
+        convert_to_shares(
+            assets,
+            self.internal_free_assets(),
+            self.token.ft_total_supply().0,
+            rounding,
+        )
     }
 
     pub fn internal_convert_to_assets(&self, shares: u128, rounding: Rounding) -> u128 {
-        let total_supply = self.token.ft_total_supply().0;
+        convert_to_assets(
+            shares,
+            self.internal_free_assets(),
+            self.token.ft_total_supply().0,
+            rounding,
+        )
+    }
+
+    /// Assets redeemable for one whole share of `decimals` decimals, rounded down
+    pub fn internal_price_per_share(&self, decimals: u8) -> u128 {
+        self.internal_convert_to_assets(one_share(decimals), Rounding::Down)
+    }
+
+    pub fn assert_owner(&self) {
+        ensure!(
+            env::predecessor_account_id() == self.owner,
+            VaultError::NotOwner
+        );
+    }
+
+    /// Part of the donated yield that is still locked, decaying linearly to zero
+    /// over `profit_unlock_period` after the last report
+    pub fn internal_locked_profit(&self) -> u128 {
+        let elapsed = env::block_timestamp().saturating_sub(self.last_report);
+        if elapsed >= self.profit_unlock_period {
+            return 0;
+        }
+
+        let remaining = (self.profit_unlock_period - elapsed) as u128;
+        mul_div(
+            self.locked_profit,
+            remaining,
+            self.profit_unlock_period as u128,
+            Rounding::Up,
+        )
+    }
 
-        // For empty vault, assume 1:1 ratio for consistency
-        if total_supply == 0 {
-            return shares;
+    /// Assets backing the shares, i.e. `total_assets` without the locked profit
+    pub fn internal_free_assets(&self) -> u128 {
+        self.total_assets
+            .saturating_sub(self.internal_locked_profit())
+    }
+
+    pub fn internal_start_pending_withdrawal(
+        &mut self,
+        owner: &AccountId,
+        shares: u128,
+        assets: u128,
+    ) {
+        let mut pending = self.pending_withdrawals.get(owner).unwrap_or_default();
+        pending.requests += 1;
+        pending.shares.0 += shares;
+        pending.assets.0 += assets;
+        self.pending_withdrawals.insert(owner, &pending);
+    }
+
+    pub fn internal_finish_pending_withdrawal(
+        &mut self,
+        owner: &AccountId,
+        shares: u128,
+        assets: u128,
+    ) {
+        let mut pending = self.pending_withdrawals.get(owner).unwrap_or_default();
+        pending.requests = pending.requests.saturating_sub(1);
+        if pending.requests == 0 {
+            self.pending_withdrawals.remove(owner);
+            return;
         }
 
-        let supply_adj = total_supply;
-        let assets_adj = self.total_assets + 1;
+        pending.shares.0 = pending.shares.0.saturating_sub(shares);
+        pending.assets.0 = pending.assets.0.saturating_sub(assets);
+        self.pending_withdrawals.insert(owner, &pending);
+    }
+
+    /// Writes `loss` off `total_assets`, absorbing it with the locked profit first
+    pub fn internal_realize_loss(&mut self, loss: u128) {
+        self.locked_profit = self.internal_locked_profit().saturating_sub(loss);
+        self.last_report = env::block_timestamp();
+        self.total_assets = self.total_assets.saturating_sub(loss);
+    }
 
-        mul_div(shares, assets_adj, supply_adj, rounding)
+    /// Must be called after every change to `total_assets` or to the asset balance
+    /// held outside of it, so that in-flight syncs can tell their snapshot is stale
+    /// and the price history picks up the new share price
+    pub fn internal_on_accounting_change(&mut self) {
+        self.internal_bump_accounting_nonce();
+        self.internal_record_price_checkpoint();
     }
+
+    /// Invalidates in-flight syncs without touching the price history, for changes
+    /// to the held balance that leave the share price as it is
+    pub fn internal_bump_accounting_nonce(&mut self) {
+        self.accounting_nonce = self.accounting_nonce.wrapping_add(1);
+    }
+}
+
+/// Share units making up one whole share of `decimals` decimals
+pub fn one_share(decimals: u8) -> u128 {
+    10u128
+        .checked_pow(decimals as u32)
+        .expect("Share decimals too large")
 }
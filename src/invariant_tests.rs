@@ -0,0 +1,264 @@
+//! Property-based state machine over the vault accounting.
+//!
+//! Random sequences of deposits, redeems, withdrawals, share transfers, donations
+//! and failed payouts run against `TokenizedMTVault` natively. Payouts resolve
+//! through `resolve_withdraw` with a mocked promise result, so failed ones take the
+//! contract's own rollback path. The invariants are checked after every step.
+
+use near_contract_standards::fungible_token::FungibleTokenCore;
+use near_sdk::json_types::{U128, U64};
+use near_sdk::{AccountId, PromiseResult};
+use proptest::prelude::*;
+
+use crate::contract_standards::{VaultCore, U256};
+use crate::sync::SurplusPolicy;
+use crate::test_utils::{alice, bob, mt_deposit, set_callback_result, set_caller, VaultBuilder};
+use crate::TokenizedMTVault;
+
+const MAX_AMOUNT: u128 = 1_000_000_000_000;
+
+fn carol() -> AccountId {
+    "carol.near".parse().unwrap()
+}
+
+fn accounts() -> [AccountId; 3] {
+    [alice(), bob(), carol()]
+}
+
+#[derive(Clone, Debug)]
+enum Op {
+    Deposit {
+        account: usize,
+        assets: u128,
+    },
+    /// Redeems `percent` of the account's shares
+    Redeem {
+        account: usize,
+        percent: u128,
+        payout_fails: bool,
+    },
+    /// Withdraws `percent` of the account's max withdraw
+    Withdraw {
+        account: usize,
+        percent: u128,
+        payout_fails: bool,
+    },
+    /// Transfers `percent` of the sender's shares
+    Transfer {
+        from: usize,
+        to: usize,
+        percent: u128,
+    },
+    /// Sends assets to the vault without minting shares, then syncs
+    Donate {
+        assets: u128,
+    },
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    let account = 0..accounts().len();
+    let percent = 1..=100u128;
+    prop_oneof![
+        (account.clone(), 1..=MAX_AMOUNT)
+            .prop_map(|(account, assets)| Op::Deposit { account, assets }),
+        (account.clone(), percent.clone(), any::<bool>()).prop_map(
+            |(account, percent, payout_fails)| Op::Redeem {
+                account,
+                percent,
+                payout_fails
+            }
+        ),
+        (account.clone(), percent.clone(), any::<bool>()).prop_map(
+            |(account, percent, payout_fails)| Op::Withdraw {
+                account,
+                percent,
+                payout_fails
+            }
+        ),
+        (account.clone(), account, percent).prop_map(|(from, to, percent)| Op::Transfer {
+            from,
+            to,
+            percent
+        }),
+        (1..=MAX_AMOUNT).prop_map(|assets| Op::Donate { assets }),
+    ]
+}
+
+/// The vault together with the asset balance the asset contract holds for it
+struct Harness {
+    vault: TokenizedMTVault,
+    held: u128,
+}
+
+impl Harness {
+    fn new() -> Self {
+        let [alice, bob, carol] = accounts();
+        let mut vault = VaultBuilder::new()
+            .register(&alice)
+            .register(&bob)
+            .register(&carol)
+            .build();
+        // Donations raise the share price straight away
+        vault.surplus_policy = SurplusPolicy::Donate;
+        vault.profit_unlock_period = 0;
+
+        Self { vault, held: 0 }
+    }
+
+    fn apply(&mut self, op: &Op) {
+        match *op {
+            Op::Deposit { account, assets } => {
+                let unused = mt_deposit(&mut self.vault, &accounts()[account], assets, "");
+                self.held += assets - unused;
+            }
+            Op::Redeem {
+                account,
+                percent,
+                payout_fails,
+            } => {
+                let owner = &accounts()[account];
+                let shares = self.balance(owner) * percent / 100;
+                let assets = self.vault.preview_redeem(U128(shares)).0;
+                if self.payable(assets) {
+                    set_caller(owner, 1);
+                    let _ = self.vault.redeem(U128(shares), None, None);
+                    self.resolve(owner, shares, assets, payout_fails);
+                }
+            }
+            Op::Withdraw {
+                account,
+                percent,
+                payout_fails,
+            } => {
+                let owner = &accounts()[account];
+                let assets = self.vault.max_withdraw(owner.clone()).0 * percent / 100;
+                let shares = self.vault.preview_withdraw(U128(assets)).0;
+                if self.payable(assets) {
+                    set_caller(owner, 1);
+                    let _ = self.vault.withdraw(U128(assets), None, None);
+                    self.resolve(owner, shares, assets, payout_fails);
+                }
+            }
+            Op::Transfer { from, to, percent } => {
+                let (sender, receiver) = (&accounts()[from], &accounts()[to]);
+                let shares = self.balance(sender) * percent / 100;
+                if sender != receiver && shares > 0 {
+                    set_caller(sender, 1);
+                    self.vault.ft_transfer(receiver.clone(), U128(shares), None);
+                }
+            }
+            Op::Donate { assets } => {
+                self.held += assets;
+                set_caller(&alice(), 0);
+                let _ = self.vault.sync();
+                let nonce = U64(self.vault.accounting_nonce);
+                let unsettled_refunds = U128(self.vault.internal_unsettled_refunds());
+                set_callback_result(PromiseResult::Successful(
+                    serde_json::to_vec(&U128(self.held)).unwrap(),
+                ));
+                self.vault.resolve_sync(nonce, unsettled_refunds);
+            }
+        }
+    }
+
+    /// Whether the vault accepts paying out `assets`, so that steps it would
+    /// reject are skipped instead of aborting the run
+    fn payable(&self, assets: u128) -> bool {
+        assets > 0 && assets <= self.vault.total_assets().0
+    }
+
+    /// Resolves the payout of a redeem or withdrawal the way the callback would
+    fn resolve(&mut self, owner: &AccountId, shares: u128, assets: u128, payout_fails: bool) {
+        let result = if payout_fails {
+            PromiseResult::Failed
+        } else {
+            PromiseResult::Successful(vec![])
+        };
+        set_callback_result(result);
+
+        let paid = self
+            .vault
+            .resolve_withdraw(
+                owner.clone(),
+                owner.clone(),
+                U128(shares),
+                U128(assets),
+                None,
+            )
+            .0;
+        self.held -= paid;
+    }
+
+    fn balance(&self, account_id: &AccountId) -> u128 {
+        self.vault.ft_balance_of(account_id.clone()).0
+    }
+
+    fn check_invariants(&self) -> Result<(), TestCaseError> {
+        let vault = &self.vault;
+        let supply = vault.ft_total_supply().0;
+
+        let balances: u128 = accounts().iter().map(|id| self.balance(id)).sum();
+        prop_assert_eq!(balances, supply, "Balances must add up to the total supply");
+
+        prop_assert!(
+            vault.total_assets <= self.held,
+            "total_assets {} exceeds the held balance {}",
+            vault.total_assets,
+            self.held
+        );
+        for account_id in accounts() {
+            prop_assert!(
+                vault.pending_withdrawals.get(&account_id).is_none(),
+                "Every payout is resolved within its step"
+            );
+        }
+
+        if supply == 0 {
+            return Ok(());
+        }
+
+        // Exact rate is (free assets + 1) / (supply + 1), see `convert_to_shares`
+        let rate_assets = U256::from(vault.total_assets().0 + 1);
+        let supply = U256::from(supply + 1);
+
+        for amount in [1, 7, 1_000, 123_456_789, MAX_AMOUNT] {
+            let exact = U256::from(amount);
+
+            let shares = U256::from(vault.preview_deposit(U128(amount)).0);
+            prop_assert!(shares * rate_assets <= exact * supply, "deposit rounds up");
+            let shares = U256::from(vault.preview_withdraw(U128(amount)).0);
+            prop_assert!(
+                shares * rate_assets >= exact * supply,
+                "withdraw rounds down"
+            );
+            let assets = U256::from(vault.preview_mint(U128(amount)).0);
+            prop_assert!(assets * supply >= exact * rate_assets, "mint rounds down");
+            let assets = U256::from(vault.preview_redeem(U128(amount)).0);
+            prop_assert!(assets * supply <= exact * rate_assets, "redeem rounds up");
+
+            // Depositing then redeeming, or minting then withdrawing, never profits
+            let shares = vault.preview_deposit(U128(amount));
+            prop_assert!(vault.preview_redeem(shares).0 <= amount);
+            let cost = vault.preview_mint(U128(amount)).0;
+            let burned = vault.preview_withdraw(U128(cost)).0;
+            prop_assert!(burned >= amount);
+        }
+
+        Ok(())
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    /// Test that the accounting invariants hold after every step of random operations
+    #[test]
+    fn test_accounting_invariants(ops in prop::collection::vec(op(), 1..40)) {
+        let mut harness = Harness::new();
+
+        for op in &ops {
+            harness.apply(op);
+            harness.check_invariants()?;
+        }
+    }
+}
@@ -1,7 +1,24 @@
+pub mod allowlist;
 mod contract_standards;
+pub mod error;
+pub mod flash_loan;
+pub mod freeze;
 mod internal;
-mod mul_div;
-mod multi_token;
+#[cfg(test)]
+mod invariant_tests;
+pub mod mul_div;
+pub mod multi_token;
+pub mod price_history;
+pub mod referrals;
+mod rescue;
+pub mod storage;
+mod sync;
+#[cfg(test)]
+mod test_utils;
+#[cfg(test)]
+mod unit_tests;
+mod views;
+pub mod votes;
 
 use near_contract_standards::fungible_token::{
     core::FungibleTokenCore,
@@ -10,21 +27,34 @@ use near_contract_standards::fungible_token::{
     metadata::{FungibleTokenMetadata, FungibleTokenMetadataProvider},
     FungibleTokenResolver,
 };
-use near_contract_standards::storage_management::StorageManagement;
 use near_sdk::{
     assert_one_yocto,
     borsh::{self, BorshDeserialize, BorshSerialize},
     serde::Deserialize,
 };
-use near_sdk::{env, near_bindgen, AccountId, Gas, NearToken, PanicOnDefault, PromiseOrValue};
+use near_sdk::{
+    collections::{LookupMap, LookupSet, Vector},
+    env, near_bindgen, AccountId, Gas, PanicOnDefault, PromiseOrValue,
+};
 use near_sdk::{json_types::U128, BorshStorageKey};
 
-use crate::contract_standards::events::{VaultDeposit, VaultWithdraw};
+use crate::contract_standards::events::{
+    VaultDeposit, VaultOwnerChange, VaultWithdraw, VaultWithdrawFailed,
+};
 use crate::contract_standards::VaultCore;
+use crate::error::{ensure, VaultError};
+use crate::flash_loan::FlashLoan;
 use crate::mul_div::Rounding;
 use crate::multi_token::MultiTokenReceiver;
+use crate::price_history::{PriceCheckpoint, DEFAULT_PRICE_HISTORY_LENGTH};
+use crate::referrals::ReferralStats;
+use crate::storage::{storage_cost, DEPOSIT_STORAGE_BYTES};
+use crate::sync::{SurplusPolicy, DEFAULT_PROFIT_UNLOCK_PERIOD};
+use crate::views::PendingWithdrawals;
+use crate::votes::ShareHistory;
 
 const GAS_FOR_FT_TRANSFER: Gas = Gas::from_tgas(50);
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 #[derive(Deserialize)]
 #[serde(crate = "near_sdk::serde")]
@@ -33,22 +63,66 @@ pub struct DepositMessage {
     max_shares: Option<U128>,
     receiver_id: Option<AccountId>,
     memo: Option<String>,
+    referrer: Option<AccountId>,
+    flash_loan_bond: Option<bool>,
 }
 
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
 pub struct TokenizedMTVault {
-    pub token: FungibleToken,        // Vault shares (NEP-141)
-    metadata: FungibleTokenMetadata, // Metadata for shares
-    asset: AccountId,                // Underlying asset (NEP-245 Multi Token)
-    asset_token_id: String,          // Token ID of the underlying MT asset
-    total_assets: u128,              // Total managed assets
-    owner: AccountId,                // Vault owner
+    pub token: FungibleToken,                     // Vault shares (NEP-141)
+    metadata: FungibleTokenMetadata,              // Metadata for shares
+    asset: AccountId,                             // Underlying asset (NEP-245 Multi Token)
+    asset_token_id: String,                       // Token ID of the underlying MT asset
+    total_assets: u128,                           // Total managed assets
+    owner: AccountId,                             // Vault owner
+    surplus_policy: SurplusPolicy,                // What sync does with assets above total_assets
+    profit_unlock_period: u64,                    // Nanoseconds over which donated yield unlocks
+    locked_profit: u128,                          // Donated yield still locked as of last_report
+    last_report: u64,          // Timestamp of the last sync that moved total_assets
+    sweepable_surplus: u128,   // Surplus set aside for the owner instead of donated
+    accounting_nonce: u64,     // Bumped on every change to the asset accounting
+    in_flight_outflows: u128,  // Assets sent out of the vault whose transfer has not resolved
+    recent_refunds: u128,      // Refunded deposits the asset contract may not have taken back
+    last_refund_block: u64,    // Block height of the last refunded deposit
+    flash_loans_enabled: bool, // Whether flash_loan can be called
+    flash_loan_fee_bps: u16,   // Flash loan fee in basis points of the amount
+    flash_loan: Option<FlashLoan>, // Loan currently out, blocks deposits and withdrawals
+    flash_loan_bonds: LookupMap<AccountId, u128>, // Bonds posted by flash loan borrowers
+    total_flash_loan_bonds: u128, // Sum of flash_loan_bonds, held outside total_assets
+    pending_withdrawals: LookupMap<AccountId, PendingWithdrawals>, // Withdrawals awaiting resolve
+    price_history: Vector<PriceCheckpoint>, // Ring buffer of share price checkpoints
+    price_history_head: u32,   // Position of the oldest checkpoint in the ring
+    price_history_length: u32, // Maximum number of checkpoints kept
+    balance_checkpoints: LookupMap<AccountId, ShareHistory>, // Share balance history
+    supply_checkpoints: ShareHistory, // Share supply history
+    vote_checkpoints: LookupMap<AccountId, ShareHistory>, // Voting power history
+    delegates: LookupMap<AccountId, AccountId>, // Delegatees of accounts that delegated
+    referrals: LookupMap<AccountId, ReferralStats>, // Deposits attributed to each referrer
+    referral_fee_bps: u16,     // Shares minted to referrers, in bps of deposited shares
+    allowlist_enabled: bool,   // Whether only allowlisted accounts can get shares
+    allowlist: LookupSet<AccountId>, // Accounts approved for allowlist mode
+    frozen: LookupSet<AccountId>, // Accounts that cannot move or redeem shares
+    guardian: Option<AccountId>, // Account allowed to seize frozen shares
+    storage_balances: LookupMap<AccountId, u128>, // Unused storage deposits above registration
 }
 
 #[derive(BorshSerialize, BorshDeserialize, BorshStorageKey)]
 pub enum StorageKey {
     FungibleToken,
+    PendingWithdrawals,
+    PriceHistory,
+    BalanceCheckpoints,
+    AccountBalanceCheckpoints { account_hash: Vec<u8> },
+    SupplyCheckpoints,
+    VoteCheckpoints,
+    AccountVoteCheckpoints { account_hash: Vec<u8> },
+    Delegates,
+    Referrals,
+    Allowlist,
+    Frozen,
+    FlashLoanBonds,
+    StorageBalances,
 }
 
 #[near_bindgen]
@@ -62,9 +136,57 @@ impl TokenizedMTVault {
             asset_token_id,
             total_assets: 0,
             owner: env::predecessor_account_id(),
+            surplus_policy: SurplusPolicy::Sweep,
+            profit_unlock_period: DEFAULT_PROFIT_UNLOCK_PERIOD,
+            locked_profit: 0,
+            last_report: env::block_timestamp(),
+            sweepable_surplus: 0,
+            accounting_nonce: 0,
+            in_flight_outflows: 0,
+            recent_refunds: 0,
+            last_refund_block: 0,
+            flash_loans_enabled: false,
+            flash_loan_fee_bps: 0,
+            flash_loan: None,
+            flash_loan_bonds: LookupMap::new(StorageKey::FlashLoanBonds),
+            total_flash_loan_bonds: 0,
+            pending_withdrawals: LookupMap::new(StorageKey::PendingWithdrawals),
+            price_history: Vector::new(StorageKey::PriceHistory),
+            price_history_head: 0,
+            price_history_length: DEFAULT_PRICE_HISTORY_LENGTH,
+            balance_checkpoints: LookupMap::new(StorageKey::BalanceCheckpoints),
+            supply_checkpoints: ShareHistory::new(StorageKey::SupplyCheckpoints),
+            vote_checkpoints: LookupMap::new(StorageKey::VoteCheckpoints),
+            delegates: LookupMap::new(StorageKey::Delegates),
+            referrals: LookupMap::new(StorageKey::Referrals),
+            referral_fee_bps: 0,
+            allowlist_enabled: false,
+            allowlist: LookupSet::new(StorageKey::Allowlist),
+            frozen: LookupSet::new(StorageKey::Frozen),
+            guardian: None,
+            storage_balances: LookupMap::new(StorageKey::StorageBalances),
         }
     }
 
+    /// Hands the vault over to `new_owner`, who can change its settings from then on.
+    #[payable]
+    pub fn set_owner(&mut self, new_owner: AccountId) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        let previous_owner = std::mem::replace(&mut self.owner, new_owner);
+
+        VaultOwnerChange {
+            previous_owner_id: &previous_owner,
+            new_owner_id: &self.owner,
+        }
+        .emit();
+    }
+
+    pub fn owner(&self) -> AccountId {
+        self.owner.clone()
+    }
+
     #[private]
     pub fn resolve_withdraw(
         &mut self,
@@ -74,6 +196,9 @@ impl TokenizedMTVault {
         assets: U128,
         memo: Option<String>,
     ) -> U128 {
+        self.internal_finish_pending_withdrawal(&owner, shares.0, assets.0);
+        self.internal_finish_outflow(assets.0);
+
         // Check if the transfer succeeded
         match env::promise_result(0) {
             near_sdk::PromiseResult::Successful(_) => {
@@ -96,11 +221,13 @@ impl TokenizedMTVault {
                 // Transfer failed - rollback state changes using callback parameters
                 // Restore shares that were burned
                 self.token.internal_deposit(&owner, shares.0);
+                self.internal_on_share_balance_change(&[&owner], None);
                 // Restore total_assets that was reduced
                 self.total_assets = self
                     .total_assets
                     .checked_add(assets.0)
-                    .expect("Total assets overflow");
+                    .unwrap_or_else(|| VaultError::Overflow("Total assets").panic());
+                self.internal_on_accounting_change();
 
                 FtMint {
                     owner_id: &owner,
@@ -109,6 +236,18 @@ impl TokenizedMTVault {
                 }
                 .emit();
 
+                VaultWithdrawFailed {
+                    owner_id: &owner,
+                    receiver_id: &receiver,
+                    token_id: &self.asset_token_id,
+                    shares,
+                    assets,
+                    reason: &VaultError::AssetTransferFailed.message(),
+                    code: VaultError::AssetTransferFailed.code(),
+                    memo: memo.as_deref(),
+                }
+                .emit();
+
                 0.into()
             }
         }
@@ -127,7 +266,7 @@ impl VaultCore for TokenizedMTVault {
     }
 
     fn total_assets(&self) -> U128 {
-        U128(self.total_assets)
+        U128(self.internal_free_assets())
     }
 
     #[payable]
@@ -140,10 +279,15 @@ impl VaultCore for TokenizedMTVault {
         assert_one_yocto();
 
         let owner = env::predecessor_account_id();
-
-        assert!(
-            shares.0 <= self.max_redeem(owner.clone()).0,
-            "Exceeds max redeem"
+        self.assert_not_frozen(&owner);
+
+        let max_redeem = self.max_redeem(owner.clone()).0;
+        ensure!(
+            shares.0 <= max_redeem,
+            VaultError::ExceedsMaxRedeem {
+                shares: shares.0,
+                max_redeem
+            }
         );
 
         let assets = self.internal_convert_to_assets(shares.0, Rounding::Down);
@@ -167,9 +311,14 @@ impl VaultCore for TokenizedMTVault {
         assert_one_yocto();
 
         let owner = env::predecessor_account_id();
-        assert!(
-            assets.0 <= self.max_withdraw(owner.clone()).0,
-            "Exceeds max withdraw"
+        self.assert_not_frozen(&owner);
+        let max_withdraw = self.max_withdraw(owner.clone()).0;
+        ensure!(
+            assets.0 <= max_withdraw,
+            VaultError::ExceedsMaxWithdraw {
+                assets: assets.0,
+                max_withdraw
+            }
         );
 
         let shares = self.internal_convert_to_shares(assets.0, Rounding::Up);
@@ -187,10 +336,64 @@ impl VaultCore for TokenizedMTVault {
         U128(self.internal_convert_to_shares(assets.0, Rounding::Down))
     }
 
+    #[allow(unused_variables)]
+    fn max_deposit(&self, receiver_id: AccountId) -> U128 {
+        if self.flash_loan.is_some()
+            || !self.is_allowed(&receiver_id)
+            || self.frozen.contains(&receiver_id)
+        {
+            return U128(0);
+        }
+
+        U128(u128::MAX - self.total_assets)
+    }
+
+    fn max_redeem(&self, owner_id: AccountId) -> U128 {
+        if self.flash_loan.is_some() || self.frozen.contains(&owner_id) {
+            return U128(0);
+        }
+
+        // Shares are capped by what the free assets can actually pay out
+        let balance = self.token.ft_balance_of(owner_id).0;
+        let redeemable =
+            self.internal_convert_to_shares(self.internal_free_assets(), Rounding::Down);
+        U128(balance.min(redeemable))
+    }
+
+    fn max_withdraw(&self, owner_id: AccountId) -> U128 {
+        if self.flash_loan.is_some() || self.frozen.contains(&owner_id) {
+            return U128(0);
+        }
+
+        let balance = self.token.ft_balance_of(owner_id).0;
+        let assets = self.internal_convert_to_assets(balance, Rounding::Down);
+        U128(assets.min(self.internal_free_assets()))
+    }
+
     fn convert_to_assets(&self, shares: U128) -> U128 {
         U128(self.internal_convert_to_assets(shares.0, Rounding::Down))
     }
 
+    fn preview_deposit(&self, assets: U128) -> U128 {
+        U128(self.internal_convert_to_shares(assets.0, Rounding::Down))
+    }
+
+    fn max_mint(&self, receiver_id: AccountId) -> U128 {
+        if self.max_deposit(receiver_id).0 == 0 {
+            return U128(0);
+        }
+
+        U128(u128::MAX - self.token.ft_total_supply().0)
+    }
+
+    fn preview_mint(&self, shares: U128) -> U128 {
+        U128(self.internal_convert_to_assets(shares.0, Rounding::Up))
+    }
+
+    fn preview_redeem(&self, shares: U128) -> U128 {
+        U128(self.internal_convert_to_assets(shares.0, Rounding::Down))
+    }
+
     fn preview_withdraw(&self, assets: U128) -> U128 {
         U128(self.internal_convert_to_shares(assets.0, Rounding::Up))
     }
@@ -201,26 +404,39 @@ impl MultiTokenReceiver for TokenizedMTVault {
     fn mt_on_transfer(
         &mut self,
         sender_id: AccountId,
-        previous_owner_id: AccountId,
+        previous_owner_ids: Vec<AccountId>,
         token_ids: Vec<String>,
         amounts: Vec<U128>,
         msg: String,
     ) -> PromiseOrValue<Vec<U128>> {
-        assert_eq!(
-            env::predecessor_account_id(),
-            self.asset.clone(),
-            "Only the underlying asset can be deposited"
+        ensure!(
+            env::predecessor_account_id() == self.asset,
+            VaultError::WrongAsset
         );
 
         // Ensure only single token transfer for the expected token_id
-        assert_eq!(token_ids.len(), 1, "Only single token deposits supported");
-        assert_eq!(amounts.len(), 1, "Only single token deposits supported");
-        assert_eq!(
-            token_ids[0], self.asset_token_id,
-            "Only the configured token_id can be deposited"
+        ensure!(
+            token_ids.len() == 1 && amounts.len() == 1 && previous_owner_ids.len() == 1,
+            VaultError::BatchDeposit
+        );
+        ensure!(
+            token_ids[0] == self.asset_token_id,
+            VaultError::WrongTokenId
         );
 
         let amount = amounts[0];
+
+        // While a flash loan is open, only the borrower's repayment is accepted
+        if self.flash_loan.is_some() {
+            ensure!(
+                self.is_flash_loan_borrower(&sender_id),
+                VaultError::FlashLoanInProgress
+            );
+            let unused_amount = self.internal_repay_flash_loan(amount.0);
+            self.internal_record_refund(unused_amount);
+            return PromiseOrValue::Value(vec![U128(unused_amount)]);
+        }
+
         let parsed_msg = match serde_json::from_str::<DepositMessage>(&msg) {
             Ok(deposit_message) => deposit_message,
             Err(_) => DepositMessage {
@@ -228,16 +444,82 @@ impl MultiTokenReceiver for TokenizedMTVault {
                 max_shares: None,
                 receiver_id: None,
                 memo: None,
+                referrer: None,
+                flash_loan_bond: None,
             },
         };
 
+        let owner_id = parsed_msg.receiver_id.unwrap_or(sender_id.clone());
+
+        // Both ends of the deposit must be allowlisted in allowlist mode, and not frozen
+        let rejection = [&sender_id, &owner_id].into_iter().find_map(|account_id| {
+            if !self.is_allowed(account_id) {
+                Some(VaultError::NotAllowlisted(account_id.clone()))
+            } else if self.frozen.contains(account_id) {
+                Some(VaultError::AccountFrozen(account_id.clone()))
+            } else {
+                None
+            }
+        });
+        if let Some(error) = rejection {
+            return self.internal_refund_deposit(
+                &sender_id,
+                &owner_id,
+                amount,
+                error,
+                parsed_msg.memo.as_deref(),
+            );
+        }
+
+        // Bonds back flash loans and are held apart from the shareholders' assets
+        if parsed_msg.flash_loan_bond == Some(true) {
+            if !self.token.accounts.contains_key(&owner_id) {
+                return self.internal_refund_deposit(
+                    &sender_id,
+                    &owner_id,
+                    amount,
+                    VaultError::NotRegistered(owner_id.clone()),
+                    parsed_msg.memo.as_deref(),
+                );
+            }
+            self.internal_post_flash_loan_bond(&owner_id, amount.0);
+            return PromiseOrValue::Value(vec![U128(0)]);
+        }
+
+        // A referrer that cannot be paid refuses the deposit rather than dropping the fee
+        if let Some(referrer_id) = &parsed_msg.referrer {
+            if let Some(error) = self.internal_referrer_rejection(referrer_id, &owner_id) {
+                return self.internal_refund_deposit(
+                    &sender_id,
+                    &owner_id,
+                    amount,
+                    error,
+                    parsed_msg.memo.as_deref(),
+                );
+            }
+        }
+
         let calculated_shares = self.convert_to_shares(amount).0;
 
-        // Check slippage protection - if min_shares requirement can't be met, reject the deposit
+        // Check slippage protection - if min_shares requirement can't be met, reject the deposit.
+        // It bounds the shares the depositor keeps after any referral fee.
         if let Some(min_shares) = parsed_msg.min_shares {
-            if calculated_shares < min_shares.0 {
+            let kept_shares = match parsed_msg.referrer {
+                Some(_) => calculated_shares - self.internal_referral_fee(calculated_shares),
+                None => calculated_shares,
+            };
+            if kept_shares < min_shares.0 {
                 // Return all amount as unused (reject the entire deposit)
-                return PromiseOrValue::Value(vec![amount]);
+                return self.internal_refund_deposit(
+                    &sender_id,
+                    &owner_id,
+                    amount,
+                    VaultError::SlippageExceeded {
+                        shares: kept_shares,
+                        min_shares: min_shares.0,
+                    },
+                    parsed_msg.memo.as_deref(),
+                );
             }
         }
 
@@ -252,40 +534,71 @@ impl MultiTokenReceiver for TokenizedMTVault {
         };
 
         let used_amount = self.internal_convert_to_assets(shares, Rounding::Up);
+        if used_amount == 0 {
+            return self.internal_refund_deposit(
+                &sender_id,
+                &owner_id,
+                amount,
+                VaultError::ZeroDeposit { assets: amount.0 },
+                parsed_msg.memo.as_deref(),
+            );
+        }
+        // The sender pays for the share history the deposit adds
+        let required = storage_cost(DEPOSIT_STORAGE_BYTES);
+        let available = self.internal_storage_available(&sender_id);
+        if available < required {
+            return self.internal_refund_deposit(
+                &sender_id,
+                &owner_id,
+                amount,
+                VaultError::InsufficientStorage {
+                    required,
+                    available,
+                },
+                parsed_msg.memo.as_deref(),
+            );
+        }
+
         let unused_amount = amount
             .0
             .checked_sub(used_amount)
-            .expect("Overflow in unused amount calculation");
+            .unwrap_or_else(|| VaultError::Overflow("Unused amount").panic());
+        self.internal_record_refund(unused_amount);
 
-        assert!(
-            used_amount > 0,
-            "No assets to deposit, shares: {}, amount: {}",
-            shares,
-            amount.0
-        );
+        // A referrer's fee is paid out of the deposited shares
+        let fee_shares = match &parsed_msg.referrer {
+            Some(referrer_id) => {
+                self.internal_record_referral(referrer_id, &sender_id, used_amount, shares)
+            }
+            None => 0,
+        };
+        let owner_shares = shares - fee_shares;
 
-        let owner_id = parsed_msg.receiver_id.unwrap_or(sender_id.clone());
-        self.token.internal_deposit(&owner_id, shares);
-        self.total_assets = self
-            .total_assets
-            .checked_add(used_amount)
-            .expect("Total assets overflow");
+        self.token.internal_deposit(&owner_id, owner_shares);
+        self.internal_on_share_balance_change(&[&owner_id], Some(&sender_id));
 
         FtMint {
             owner_id: &owner_id,
-            amount: U128(shares),
+            amount: U128(owner_shares),
             memo: Some("Deposit"),
         }
         .emit();
 
+        self.total_assets = self
+            .total_assets
+            .checked_add(used_amount)
+            .unwrap_or_else(|| VaultError::Overflow("Total assets").panic());
+        self.internal_on_accounting_change();
+
         // Emit VaultDeposit event
         VaultDeposit {
             sender_id: &sender_id,
             owner_id: &owner_id,
             token_id: &self.asset_token_id,
             assets: U128(used_amount),
-            shares: U128(shares),
+            shares: U128(owner_shares),
             memo: parsed_msg.memo.as_deref(),
+            referrer: parsed_msg.referrer.as_deref(),
         }
         .emit();
 
@@ -298,7 +611,12 @@ impl MultiTokenReceiver for TokenizedMTVault {
 impl FungibleTokenCore for TokenizedMTVault {
     #[payable]
     fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>) {
-        self.token.ft_transfer(receiver_id, amount, memo)
+        self.assert_not_frozen(&env::predecessor_account_id());
+        self.assert_not_frozen(&receiver_id);
+        self.assert_allowed(&receiver_id);
+        self.token.ft_transfer(receiver_id.clone(), amount, memo);
+        let sender_id = env::predecessor_account_id();
+        self.internal_on_share_balance_change(&[&sender_id, &receiver_id], Some(&sender_id));
     }
 
     #[payable]
@@ -309,7 +627,15 @@ impl FungibleTokenCore for TokenizedMTVault {
         memo: Option<String>,
         msg: String,
     ) -> PromiseOrValue<U128> {
-        self.token.ft_transfer_call(receiver_id, amount, memo, msg)
+        self.assert_not_frozen(&env::predecessor_account_id());
+        self.assert_not_frozen(&receiver_id);
+        self.assert_allowed(&receiver_id);
+        let result = self
+            .token
+            .ft_transfer_call(receiver_id.clone(), amount, memo, msg);
+        let sender_id = env::predecessor_account_id();
+        self.internal_on_share_balance_change(&[&sender_id, &receiver_id], Some(&sender_id));
+        result
     }
 
     fn ft_total_supply(&self) -> U128 {
@@ -330,46 +656,12 @@ impl FungibleTokenResolver for TokenizedMTVault {
         receiver_id: AccountId,
         amount: U128,
     ) -> U128 {
-        self.token
-            .ft_resolve_transfer(sender_id, receiver_id, amount)
-    }
-}
-
-#[near_bindgen]
-impl StorageManagement for TokenizedMTVault {
-    #[payable]
-    fn storage_deposit(
-        &mut self,
-        account_id: Option<AccountId>,
-        registration_only: Option<bool>,
-    ) -> near_contract_standards::storage_management::StorageBalance {
-        self.token.storage_deposit(account_id, registration_only)
-    }
-
-    #[payable]
-    fn storage_withdraw(
-        &mut self,
-        amount: Option<NearToken>,
-    ) -> near_contract_standards::storage_management::StorageBalance {
-        self.token.storage_withdraw(amount)
-    }
-
-    fn storage_balance_bounds(
-        &self,
-    ) -> near_contract_standards::storage_management::StorageBalanceBounds {
-        self.token.storage_balance_bounds()
-    }
-
-    fn storage_balance_of(
-        &self,
-        account_id: AccountId,
-    ) -> Option<near_contract_standards::storage_management::StorageBalance> {
-        self.token.storage_balance_of(account_id)
-    }
-
-    #[payable]
-    fn storage_unregister(&mut self, force: Option<bool>) -> bool {
-        self.token.storage_unregister(force)
+        let used_amount =
+            self.token
+                .ft_resolve_transfer(sender_id.clone(), receiver_id.clone(), amount);
+        // Refunds move shares back to the sender, or burn them if it unregistered
+        self.internal_on_share_balance_change(&[&sender_id, &receiver_id], None);
+        used_amount
     }
 }
 
@@ -1,25 +1,75 @@
-#[derive(Clone, Copy, Debug)]
+use std::fmt;
+
+use crate::contract_standards::U256;
+use crate::error::VaultError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Rounding {
+    /// Towards zero
     Down,
+    /// Away from zero
     Up,
+    /// To the nearest integer, halves away from zero
+    HalfUp,
+    /// To the nearest integer, halves to the even neighbour
+    HalfEven,
 }
 
-pub fn mul_div(x: u128, y: u128, denominator: u128, rounding: Rounding) -> u128 {
-    use crate::contract_standards::U256;
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MathError {
+    DivisionByZero,
+    /// The rounded result does not fit in a `u128`
+    Overflow,
+}
+
+impl fmt::Display for MathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DivisionByZero => write!(f, "Division by zero"),
+            Self::Overflow => write!(f, "Result does not fit in 128 bits"),
+        }
+    }
+}
+
+/// Computes `x * y / denominator` rounded as asked, with a 256-bit intermediate
+/// product so that only the final result has to fit in a `u128`.
+pub fn checked_mul_div(
+    x: u128,
+    y: u128,
+    denominator: u128,
+    rounding: Rounding,
+) -> Result<u128, MathError> {
+    if denominator == 0 {
+        return Err(MathError::DivisionByZero);
+    }
 
     let numerator = U256::from(x) * U256::from(y);
     let denominator = U256::from(denominator);
-    let result = numerator / denominator;
+    let quotient = numerator / denominator;
     let remainder = numerator % denominator;
 
-    match rounding {
-        Rounding::Down => result.as_u128(),
-        Rounding::Up => {
-            if remainder > U256::zero() {
-                result.as_u128() + 1
-            } else {
-                result.as_u128()
-            }
+    // The remainder is below the denominator, so doubling it cannot overflow 256 bits
+    let round_up = match rounding {
+        Rounding::Down => false,
+        Rounding::Up => !remainder.is_zero(),
+        Rounding::HalfUp => remainder * 2 >= denominator,
+        Rounding::HalfEven => {
+            let doubled = remainder * 2;
+            doubled > denominator || (doubled == denominator && quotient.bit(0))
         }
+    };
+
+    let result = if round_up { quotient + 1 } else { quotient };
+    if result > U256::from(u128::MAX) {
+        return Err(MathError::Overflow);
     }
+
+    Ok(result.as_u128())
+}
+
+/// [`checked_mul_div`] for amounts that fit by construction, panicking with
+/// [`VaultError::Math`] if they do not
+pub fn mul_div(x: u128, y: u128, denominator: u128, rounding: Rounding) -> u128 {
+    checked_mul_div(x, y, denominator, rounding)
+        .unwrap_or_else(|error| VaultError::Math(x, y, denominator, error).panic())
 }
@@ -1,60 +1,156 @@
-use near_sdk::{ext_contract, json_types::U128, AccountId, PromiseOrValue};
+use near_sdk::{ext_contract, json_types::U128, near, AccountId, Promise, PromiseOrValue};
+
+/// Token identifier within a NEP-245 Multi Token contract
+pub type TokenId = String;
+
+/// Approval presented on a transfer: `(owner_id, approval_id)`
+pub type Approval = (AccountId, u64);
+
+/// Approval cleared by a transfer, returned to the resolver so it can be restored
+/// if the transfer is refunded: `(approved_account_id, approval_id, amount)`
+pub type ClearedApproval = (AccountId, u64, U128);
+
+/// Token record returned by [`MultiTokenCore::mt_token`]
+#[near(serializers = [json])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Token {
+    pub token_id: TokenId,
+    pub owner_id: Option<AccountId>,
+}
 
 /// Core trait for NEP-245 Multi Token standard
-/// This is a minimal implementation focused on the needs of the vault contract
+#[ext_contract(ext_mt_core)]
 pub trait MultiTokenCore {
     /// Transfer a specific amount of token_id from predecessor to receiver_id
     fn mt_transfer(
         &mut self,
         receiver_id: AccountId,
-        token_id: String,
+        token_id: TokenId,
         amount: U128,
-        approval: Option<u64>,
+        approval: Option<Approval>,
+        memo: Option<String>,
+    );
+
+    /// Transfer several token_ids from predecessor to receiver_id in one call
+    fn mt_batch_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        approvals: Option<Vec<Option<Approval>>>,
         memo: Option<String>,
     );
 
-    /// Transfer tokens and call a method on the receiver contract
+    /// Transfer tokens and call `mt_on_transfer` on the receiver contract
+    /// Resolves to the amounts actually used by the receiver
     fn mt_transfer_call(
         &mut self,
         receiver_id: AccountId,
-        token_id: String,
+        token_id: TokenId,
         amount: U128,
-        approval: Option<u64>,
+        approval: Option<Approval>,
         memo: Option<String>,
         msg: String,
-    ) -> PromiseOrValue<U128>;
+    ) -> PromiseOrValue<Vec<U128>>;
+
+    /// Batch version of [`MultiTokenCore::mt_transfer_call`]
+    fn mt_batch_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        approvals: Option<Vec<Option<Approval>>>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<Vec<U128>>;
+
+    /// Get token records for token_ids, `None` for unknown tokens
+    fn mt_token(&self, token_ids: Vec<TokenId>) -> Vec<Option<Token>>;
 
     /// Get balance of account_id for token_id
-    fn mt_balance_of(&self, account_id: AccountId, token_id: String) -> U128;
+    fn mt_balance_of(&self, account_id: AccountId, token_id: TokenId) -> U128;
+
+    /// Get balances of account_id for several token_ids
+    fn mt_batch_balance_of(&self, account_id: AccountId, token_ids: Vec<TokenId>) -> Vec<U128>;
 
     /// Get total supply of token_id
-    fn mt_supply(&self, token_id: String) -> Option<U128>;
+    fn mt_supply(&self, token_id: TokenId) -> Option<U128>;
+
+    /// Get total supplies of several token_ids
+    fn mt_batch_supply(&self, token_ids: Vec<TokenId>) -> Vec<Option<U128>>;
+}
+
+/// Resolver trait for NEP-245 Multi Token standard
+/// Called by the token contract on itself once `mt_on_transfer` has returned
+#[ext_contract(ext_mt_resolver)]
+pub trait MultiTokenResolver {
+    /// Refunds the unused amounts reported by the receiver and restores cleared approvals
+    /// Returns the amounts that were finally transferred
+    fn mt_resolve_transfer(
+        &mut self,
+        previous_owner_ids: Vec<AccountId>,
+        receiver_id: AccountId,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        approvals: Option<Vec<Option<Vec<ClearedApproval>>>>,
+    ) -> Vec<U128>;
+}
+
+/// Approval management trait for NEP-245 Multi Token standard
+#[ext_contract(ext_mt_approval)]
+pub trait MultiTokenApproval {
+    /// Allow account_id to transfer amounts of token_ids on behalf of the predecessor
+    /// If msg is given, `mt_on_approve` is called on account_id
+    fn mt_approve(
+        &mut self,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        account_id: AccountId,
+        msg: Option<String>,
+    ) -> Option<Promise>;
+
+    /// Revoke the approval of account_id for token_ids
+    fn mt_revoke(&mut self, token_ids: Vec<TokenId>, account_id: AccountId);
+
+    /// Revoke every approval for token_ids
+    fn mt_revoke_all(&mut self, token_ids: Vec<TokenId>);
+
+    /// Check whether approved_account_id may transfer amounts of token_ids
+    fn mt_is_approved(
+        &self,
+        token_ids: Vec<TokenId>,
+        approved_account_id: AccountId,
+        amounts: Vec<U128>,
+        approval_ids: Option<Vec<u64>>,
+    ) -> bool;
 }
 
 /// Receiver trait for NEP-245 Multi Token standard
 /// Contracts implementing this can receive multi-token transfers
+#[ext_contract(ext_mt_receiver)]
 pub trait MultiTokenReceiver {
     /// Called when tokens are transferred to this contract via mt_transfer_call
-    /// Returns the amount of tokens used (the rest is returned to sender)
+    /// Returns the amounts of tokens unused (refunded to the previous owners)
     fn mt_on_transfer(
         &mut self,
         sender_id: AccountId,
-        previous_owner_id: AccountId,
-        token_ids: Vec<String>,
+        previous_owner_ids: Vec<AccountId>,
+        token_ids: Vec<TokenId>,
         amounts: Vec<U128>,
         msg: String,
     ) -> PromiseOrValue<Vec<U128>>;
 }
 
-/// External contract interface for making cross-contract calls to NEP-245 contracts
-#[ext_contract(ext_mt_core)]
-pub trait _ExtMultiTokenCore {
-    fn mt_transfer(
+/// Approval receiver trait for NEP-245 Multi Token standard
+/// Called on the approved account when `mt_approve` is given a msg
+#[ext_contract(ext_mt_approval_receiver)]
+pub trait MultiTokenApprovalReceiver {
+    fn mt_on_approve(
         &mut self,
-        receiver_id: AccountId,
-        token_id: String,
-        amount: U128,
-        approval: Option<u64>,
-        memo: Option<String>,
+        token_ids: Vec<TokenId>,
+        amounts: Vec<U128>,
+        owner_id: AccountId,
+        approval_ids: Vec<u64>,
+        msg: String,
     );
 }
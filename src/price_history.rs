@@ -0,0 +1,176 @@
+use near_contract_standards::fungible_token::metadata::FungibleTokenMetadataProvider;
+use near_contract_standards::fungible_token::FungibleTokenCore;
+use near_sdk::{
+    assert_one_yocto, env,
+    json_types::{U128, U64},
+    near, near_bindgen,
+};
+
+use crate::contract_standards::events::VaultConfigChange;
+use crate::contract_standards::{convert_to_assets, U256};
+use crate::error::{ensure, VaultError};
+use crate::internal::one_share;
+use crate::mul_div::Rounding;
+use crate::{TokenizedMTVault, TokenizedMTVaultExt};
+
+pub const DEFAULT_PRICE_HISTORY_LENGTH: u32 = 720;
+/// Keeps a TWAP over the whole history within the gas of a view call
+const MAX_PRICE_HISTORY_LENGTH: u32 = 1_000;
+
+/// Vault state at the end of a block in which it changed
+#[near(serializers = [borsh])]
+#[derive(Clone, Debug)]
+pub struct PriceCheckpoint {
+    pub timestamp: u64,
+    pub total_assets: u128,
+    pub total_supply: u128,
+}
+
+impl PriceCheckpoint {
+    /// Assets redeemable for `one_share` at this checkpoint, rounded down
+    fn price_per_share(&self, one_share: u128) -> u128 {
+        convert_to_assets(
+            one_share,
+            self.total_assets,
+            self.total_supply,
+            Rounding::Down,
+        )
+    }
+}
+
+#[near_bindgen]
+impl TokenizedMTVault {
+    /// Price per whole share as of `timestamp`, `None` if it predates the history.
+    pub fn price_per_share_at(&self, timestamp: U64) -> Option<U128> {
+        let one_share = one_share(self.ft_metadata().decimals);
+
+        self.internal_price_checkpoint_index_at(timestamp.0)
+            .map(|index| self.internal_price_checkpoint(index))
+            .map(|checkpoint| U128(checkpoint.price_per_share(one_share)))
+    }
+
+    /// Time-weighted average price per whole share over the last `window_ns` nanoseconds.
+    ///
+    /// Panics if the recorded history does not cover the whole window.
+    pub fn twap_price_per_share(&self, window_ns: U64) -> U128 {
+        ensure!(window_ns.0 > 0, VaultError::EmptyWindow);
+
+        let now = env::block_timestamp();
+        let window_start = now.saturating_sub(window_ns.0);
+        let one_share = one_share(self.ft_metadata().decimals);
+
+        // Only the checkpoint in effect at the start of the window and the later ones count
+        let first = self
+            .internal_price_checkpoint_index_at(window_start)
+            .unwrap_or_else(|| VaultError::PriceHistoryTooShort.panic());
+
+        let mut weighted_sum = U256::zero();
+        let mut segment_start = window_start;
+        let mut price = self
+            .internal_price_checkpoint(first)
+            .price_per_share(one_share);
+
+        for index in first + 1..self.price_history.len() {
+            let checkpoint = self.internal_price_checkpoint(index);
+            weighted_sum += U256::from(price) * U256::from(checkpoint.timestamp - segment_start);
+            segment_start = checkpoint.timestamp;
+            price = checkpoint.price_per_share(one_share);
+        }
+        weighted_sum += U256::from(price) * U256::from(now - segment_start);
+
+        U128((weighted_sum / U256::from(now - window_start)).as_u128())
+    }
+
+    #[payable]
+    pub fn set_price_history_length(&mut self, length: u32) {
+        assert_one_yocto();
+        self.assert_owner();
+        ensure!(
+            length > 0 && length <= MAX_PRICE_HISTORY_LENGTH,
+            VaultError::InvalidPriceHistoryLength {
+                max: MAX_PRICE_HISTORY_LENGTH
+            }
+        );
+
+        // Rewrite the ring in chronological order, keeping the most recent checkpoints
+        let len = self.price_history.len();
+        let kept = len.min(length as u64);
+        let checkpoints: Vec<PriceCheckpoint> = (len - kept..len)
+            .map(|index| self.internal_price_checkpoint(index))
+            .collect();
+
+        self.price_history.clear();
+        for checkpoint in &checkpoints {
+            self.price_history.push(checkpoint);
+        }
+        self.price_history_head = 0;
+
+        VaultConfigChange {
+            parameter: "price_history_length",
+            old_value: self.price_history_length.into(),
+            new_value: length.into(),
+        }
+        .emit();
+
+        self.price_history_length = length;
+    }
+
+    pub fn price_history_length(&self) -> u32 {
+        self.price_history_length
+    }
+}
+
+impl TokenizedMTVault {
+    /// Records the current share price, overwriting the oldest checkpoint once the
+    /// history is full. Several changes within one block share a single checkpoint.
+    pub fn internal_record_price_checkpoint(&mut self) {
+        let checkpoint = PriceCheckpoint {
+            timestamp: env::block_timestamp(),
+            total_assets: self.internal_free_assets(),
+            total_supply: self.token.ft_total_supply().0,
+        };
+
+        let len = self.price_history.len();
+        if len > 0 {
+            let newest = (self.price_history_head as u64 + len - 1) % len;
+            if self.internal_price_checkpoint(len - 1).timestamp == checkpoint.timestamp {
+                self.price_history.replace(newest, &checkpoint);
+                return;
+            }
+        }
+
+        if len < self.price_history_length as u64 {
+            self.price_history.push(&checkpoint);
+        } else {
+            self.price_history
+                .replace(self.price_history_head as u64, &checkpoint);
+            self.price_history_head = (self.price_history_head + 1) % self.price_history_length;
+        }
+    }
+
+    /// Chronological position of the last checkpoint recorded at or before `timestamp`,
+    /// `None` if the history starts after it
+    fn internal_price_checkpoint_index_at(&self, timestamp: u64) -> Option<u64> {
+        // Find the first checkpoint recorded after `timestamp`
+        let mut low = 0;
+        let mut high = self.price_history.len();
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if self.internal_price_checkpoint(mid).timestamp > timestamp {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+
+        low.checked_sub(1)
+    }
+
+    /// Checkpoint at chronological position `index`, 0 being the oldest
+    fn internal_price_checkpoint(&self, index: u64) -> PriceCheckpoint {
+        let len = self.price_history.len();
+        self.price_history
+            .get((self.price_history_head as u64 + index) % len)
+            .expect("Price checkpoint missing")
+    }
+}
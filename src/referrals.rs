@@ -0,0 +1,144 @@
+use near_contract_standards::fungible_token::events::FtMint;
+use near_sdk::{assert_one_yocto, json_types::U128, near, near_bindgen, AccountId};
+
+use crate::contract_standards::events::VaultConfigChange;
+use crate::error::{ensure, VaultError};
+use crate::mul_div::{mul_div, Rounding};
+use crate::{TokenizedMTVault, TokenizedMTVaultExt};
+
+const BPS_DENOMINATOR: u16 = 10_000;
+const MAX_REFERRAL_FEE_BPS: u16 = 1_000;
+
+/// Deposits attributed to a referrer since the vault was deployed
+#[near(serializers = [borsh, json])]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReferralStats {
+    pub deposits: u64,
+    pub assets: U128,
+    pub shares: U128,
+    /// Shares of the referred deposits paid to the referrer as its fee
+    pub fee_shares: U128,
+}
+
+#[near_bindgen]
+impl TokenizedMTVault {
+    pub fn referral_stats(&self, referrer_id: AccountId) -> ReferralStats {
+        self.referrals.get(&referrer_id).unwrap_or_default()
+    }
+
+    /// Sets the share of each referred deposit paid to its referrer, in basis points.
+    /// The fee comes out of the depositor's shares, so existing holders are not diluted.
+    #[payable]
+    pub fn set_referral_fee_bps(&mut self, fee_bps: u16) {
+        assert_one_yocto();
+        self.assert_owner();
+        ensure!(
+            fee_bps <= MAX_REFERRAL_FEE_BPS,
+            VaultError::FeeTooHigh {
+                max_bps: MAX_REFERRAL_FEE_BPS
+            }
+        );
+
+        VaultConfigChange {
+            parameter: "referral_fee_bps",
+            old_value: self.referral_fee_bps.into(),
+            new_value: fee_bps.into(),
+        }
+        .emit();
+
+        self.referral_fee_bps = fee_bps;
+    }
+
+    pub fn referral_fee_bps(&self) -> u16 {
+        self.referral_fee_bps
+    }
+}
+
+impl TokenizedMTVault {
+    /// Part of a deposit's `shares` paid to its referrer, rounded down in the depositor's favour
+    pub fn internal_referral_fee(&self, shares: u128) -> u128 {
+        mul_div(
+            shares,
+            self.referral_fee_bps as u128,
+            BPS_DENOMINATOR as u128,
+            Rounding::Down,
+        )
+    }
+
+    /// Why `referrer_id` cannot be paid for a deposit to `owner_id`, if it cannot.
+    ///
+    /// The referrer must be registered for shares, which also covers the storage of
+    /// its stats, allowlisted in allowlist mode and not frozen. Referring yourself is
+    /// rejected, though it would only pay yourself.
+    pub fn internal_referrer_rejection(
+        &self,
+        referrer_id: &AccountId,
+        owner_id: &AccountId,
+    ) -> Option<VaultError> {
+        if referrer_id == owner_id {
+            Some(VaultError::SelfReferral)
+        } else if !self.token.accounts.contains_key(referrer_id) {
+            Some(VaultError::ReferrerNotRegistered(referrer_id.clone()))
+        } else if !self.is_allowed(referrer_id) {
+            Some(VaultError::NotAllowlisted(referrer_id.clone()))
+        } else if self.frozen.contains(referrer_id) {
+            Some(VaultError::AccountFrozen(referrer_id.clone()))
+        } else {
+            None
+        }
+    }
+
+    /// Attributes a deposit of `assets` for `shares` to `referrer_id`, mints it the fee
+    /// out of those shares and returns the fee. The depositor gets the rest, and
+    /// `sender_id` pays for the referrer's new checkpoints.
+    ///
+    /// The referrer must have passed [`Self::internal_referrer_rejection`].
+    pub fn internal_record_referral(
+        &mut self,
+        referrer_id: &AccountId,
+        sender_id: &AccountId,
+        assets: u128,
+        shares: u128,
+    ) -> u128 {
+        let fee_shares = self.internal_referral_fee(shares);
+
+        let mut stats = self.referral_stats(referrer_id.clone());
+        stats.deposits += 1;
+        stats.assets = U128(
+            stats
+                .assets
+                .0
+                .checked_add(assets)
+                .unwrap_or_else(|| VaultError::Overflow("Referral assets").panic()),
+        );
+        stats.shares = U128(
+            stats
+                .shares
+                .0
+                .checked_add(shares)
+                .unwrap_or_else(|| VaultError::Overflow("Referral shares").panic()),
+        );
+        stats.fee_shares = U128(
+            stats
+                .fee_shares
+                .0
+                .checked_add(fee_shares)
+                .unwrap_or_else(|| VaultError::Overflow("Referral fee shares").panic()),
+        );
+        self.referrals.insert(referrer_id, &stats);
+
+        if fee_shares > 0 {
+            self.token.internal_deposit(referrer_id, fee_shares);
+            self.internal_on_share_balance_change(&[referrer_id], Some(sender_id));
+
+            FtMint {
+                owner_id: referrer_id,
+                amount: U128(fee_shares),
+                memo: Some("Referral fee"),
+            }
+            .emit();
+        }
+
+        fee_shares
+    }
+}
@@ -0,0 +1,189 @@
+use near_contract_standards::fungible_token::core::ext_ft_core;
+use near_sdk::{
+    assert_one_yocto, env,
+    json_types::{U128, U64},
+    near_bindgen, AccountId, Gas, NearToken, Promise, PromiseResult,
+};
+
+use crate::contract_standards::events::VaultRescue;
+use crate::error::{ensure, VaultError};
+use crate::internal::ext_self;
+use crate::multi_token::ext_mt_core;
+use crate::{TokenizedMTVault, TokenizedMTVaultExt, GAS_FOR_FT_TRANSFER};
+
+const GAS_FOR_RESOLVE_RESCUE: Gas = Gas::from_tgas(10);
+const GAS_FOR_RESCUE_SURPLUS: Gas = Gas::from_tgas(80);
+
+#[near_bindgen]
+impl TokenizedMTVault {
+    /// Sends NEP-245 tokens that were mistakenly transferred to the vault to `receiver_id`.
+    ///
+    /// The vault's own asset can only be rescued up to the sweepable surplus
+    /// recorded by the last [`sync`](TokenizedMTVault::sync), and only if the
+    /// balance read right before the transfer still holds that much above what
+    /// backs the shares.
+    #[payable]
+    pub fn rescue_mt(
+        &mut self,
+        token: AccountId,
+        token_id: String,
+        amount: U128,
+        receiver_id: AccountId,
+    ) -> Promise {
+        assert_one_yocto();
+        self.assert_owner();
+        ensure!(amount.0 > 0, VaultError::ZeroAmount);
+
+        if self.is_vault_asset(&token, &token_id) {
+            self.assert_no_flash_loan();
+            ensure!(
+                amount.0 <= self.sweepable_surplus,
+                VaultError::ExceedsSweepableSurplus
+            );
+
+            return self.internal_query_balance().then(
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESCUE_SURPLUS)
+                    .resolve_rescue_surplus(
+                        amount,
+                        receiver_id,
+                        U64(self.accounting_nonce),
+                        U128(self.internal_unsettled_refunds()),
+                    ),
+            );
+        }
+
+        self.internal_rescue_mt(token, token_id, amount, receiver_id)
+    }
+
+    /// Sends the rescued surplus of the vault asset once its balance is known
+    #[private]
+    pub fn resolve_rescue_surplus(
+        &mut self,
+        amount: U128,
+        receiver_id: AccountId,
+        accounting_nonce: U64,
+        unsettled_refunds: U128,
+    ) -> Promise {
+        let balance = self.internal_queried_balance(accounting_nonce);
+        let surplus = self.internal_surplus(balance, unsettled_refunds.0);
+        ensure!(
+            amount.0 <= self.sweepable_surplus.min(surplus),
+            VaultError::ExceedsSweepableSurplus
+        );
+
+        self.sweepable_surplus -= amount.0;
+        self.internal_start_outflow(amount.0);
+        self.internal_bump_accounting_nonce();
+
+        self.internal_rescue_mt(
+            self.asset.clone(),
+            self.asset_token_id.clone(),
+            amount,
+            receiver_id,
+        )
+    }
+
+    /// Sends NEP-141 tokens that were mistakenly transferred to the vault to `receiver_id`.
+    #[payable]
+    pub fn rescue_ft(&mut self, token: AccountId, amount: U128, receiver_id: AccountId) -> Promise {
+        assert_one_yocto();
+        self.assert_owner();
+        ensure!(amount.0 > 0, VaultError::ZeroAmount);
+
+        ext_ft_core::ext(token.clone())
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .with_static_gas(GAS_FOR_FT_TRANSFER)
+            .ft_transfer(receiver_id.clone(), amount, None)
+            .then(
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_RESCUE)
+                    .resolve_rescue_ft(token, amount, receiver_id),
+            )
+    }
+
+    #[private]
+    pub fn resolve_rescue_mt(
+        &mut self,
+        token: AccountId,
+        token_id: String,
+        amount: U128,
+        receiver_id: AccountId,
+    ) -> U128 {
+        if self.is_vault_asset(&token, &token_id) {
+            self.internal_finish_outflow(amount.0);
+        }
+
+        match env::promise_result(0) {
+            PromiseResult::Successful(_) => {
+                VaultRescue {
+                    token: &token,
+                    token_id: Some(&token_id),
+                    amount,
+                    receiver_id: &receiver_id,
+                }
+                .emit();
+
+                amount
+            }
+            _ => {
+                // Transfer failed - the surplus is still held by the vault
+                if self.is_vault_asset(&token, &token_id) {
+                    self.sweepable_surplus = self
+                        .sweepable_surplus
+                        .checked_add(amount.0)
+                        .unwrap_or_else(|| VaultError::Overflow("Sweepable surplus").panic());
+                }
+
+                0.into()
+            }
+        }
+    }
+
+    #[private]
+    pub fn resolve_rescue_ft(
+        &mut self,
+        token: AccountId,
+        amount: U128,
+        receiver_id: AccountId,
+    ) -> U128 {
+        match env::promise_result(0) {
+            PromiseResult::Successful(_) => {
+                VaultRescue {
+                    token: &token,
+                    token_id: None,
+                    amount,
+                    receiver_id: &receiver_id,
+                }
+                .emit();
+
+                amount
+            }
+            _ => 0.into(),
+        }
+    }
+}
+
+impl TokenizedMTVault {
+    fn internal_rescue_mt(
+        &self,
+        token: AccountId,
+        token_id: String,
+        amount: U128,
+        receiver_id: AccountId,
+    ) -> Promise {
+        ext_mt_core::ext(token.clone())
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .with_static_gas(GAS_FOR_FT_TRANSFER)
+            .mt_transfer(receiver_id.clone(), token_id.clone(), amount, None, None)
+            .then(
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_RESCUE)
+                    .resolve_rescue_mt(token, token_id, amount, receiver_id),
+            )
+    }
+
+    fn is_vault_asset(&self, token: &AccountId, token_id: &str) -> bool {
+        *token == self.asset && token_id == self.asset_token_id
+    }
+}
@@ -0,0 +1,164 @@
+use near_contract_standards::storage_management::{
+    StorageBalance, StorageBalanceBounds, StorageManagement,
+};
+use near_sdk::{assert_one_yocto, env, near_bindgen, AccountId, NearToken, Promise};
+
+use crate::error::{ensure, VaultError};
+use crate::{TokenizedMTVault, TokenizedMTVaultExt};
+
+/// Most share history storage a deposit can add: fresh balance and vote histories
+/// for the owner and the referrer, at most 267 bytes each, and a supply checkpoint
+/// of 73 bytes, rounded up
+pub const DEPOSIT_STORAGE_BYTES: u64 = 1_200;
+
+/// Registration covers the account itself. Anything deposited above the minimum pays
+/// for the share history the account adds, which is kept for good.
+#[near_bindgen]
+impl StorageManagement for TokenizedMTVault {
+    #[payable]
+    fn storage_deposit(
+        &mut self,
+        account_id: Option<AccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance {
+        let amount = env::attached_deposit().as_yoctonear();
+        let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+
+        let mut extra = amount;
+        if !self.token.accounts.contains_key(&account_id) {
+            let min = self.storage_balance_bounds().min.as_yoctonear();
+            ensure!(
+                amount >= min,
+                VaultError::InsufficientStorage {
+                    required: min,
+                    available: amount
+                }
+            );
+            self.token.internal_register_account(&account_id);
+            extra = amount - min;
+        }
+
+        if registration_only == Some(true) {
+            if extra > 0 {
+                Promise::new(env::predecessor_account_id())
+                    .transfer(NearToken::from_yoctonear(extra));
+            }
+        } else {
+            self.internal_add_storage_balance(&account_id, extra);
+        }
+
+        self.storage_balance_of(account_id).unwrap()
+    }
+
+    /// Withdraws storage deposited above the minimum that the share history has not used
+    #[payable]
+    fn storage_withdraw(&mut self, amount: Option<NearToken>) -> StorageBalance {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        ensure!(
+            self.token.accounts.contains_key(&account_id),
+            VaultError::NotRegistered(account_id)
+        );
+
+        let available = self.internal_storage_available(&account_id);
+        let amount = amount.map_or(available, |amount| amount.as_yoctonear());
+        ensure!(
+            amount <= available,
+            VaultError::InsufficientStorage {
+                required: amount,
+                available
+            }
+        );
+
+        self.storage_balances
+            .insert(&account_id, &(available - amount));
+        if amount > 0 {
+            Promise::new(account_id.clone()).transfer(NearToken::from_yoctonear(amount));
+        }
+
+        self.storage_balance_of(account_id).unwrap()
+    }
+
+    #[payable]
+    fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        let account_id = env::predecessor_account_id();
+        // Frozen shares must stay put until they are unfrozen or seized
+        self.assert_not_frozen(&account_id);
+        let unregistered = self.token.storage_unregister(force);
+        if unregistered {
+            // A forced unregister burns the remaining balance
+            self.internal_on_share_balance_change(&[&account_id], Some(&account_id));
+
+            // The history stays, only the unused part of the deposit goes back
+            let available = self.storage_balances.remove(&account_id).unwrap_or(0);
+            if available > 0 {
+                Promise::new(account_id).transfer(NearToken::from_yoctonear(available));
+            }
+        }
+        unregistered
+    }
+
+    fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        StorageBalanceBounds {
+            min: self.token.storage_balance_bounds().min,
+            max: None,
+        }
+    }
+
+    fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+        self.token
+            .storage_balance_of(account_id.clone())
+            .map(|registration| {
+                let available = self.internal_storage_available(&account_id);
+                StorageBalance {
+                    total: registration
+                        .total
+                        .saturating_add(NearToken::from_yoctonear(available)),
+                    available: NearToken::from_yoctonear(available),
+                }
+            })
+    }
+}
+
+impl TokenizedMTVault {
+    /// Storage deposit of `account_id` not used yet, in yoctoNEAR
+    pub fn internal_storage_available(&self, account_id: &AccountId) -> u128 {
+        self.storage_balances.get(account_id).unwrap_or(0)
+    }
+
+    pub fn internal_add_storage_balance(&mut self, account_id: &AccountId, amount: u128) {
+        if amount == 0 {
+            return;
+        }
+
+        let balance = self
+            .internal_storage_available(account_id)
+            .checked_add(amount)
+            .unwrap_or_else(|| VaultError::Overflow("Storage balance").panic());
+        self.storage_balances.insert(account_id, &balance);
+    }
+
+    /// Takes the storage used since `storage_before` out of the storage deposit of `payer`
+    pub fn internal_charge_storage(&mut self, payer: &AccountId, storage_before: u64) {
+        let used = env::storage_usage().saturating_sub(storage_before);
+        if used == 0 {
+            return;
+        }
+
+        let cost = storage_cost(used);
+        let available = self.internal_storage_available(payer);
+        ensure!(
+            cost <= available,
+            VaultError::InsufficientStorage {
+                required: cost,
+                available
+            }
+        );
+        self.storage_balances.insert(payer, &(available - cost));
+    }
+}
+
+/// Cost of `bytes` of contract storage in yoctoNEAR
+pub fn storage_cost(bytes: u64) -> u128 {
+    env::storage_byte_cost().as_yoctonear() * bytes as u128
+}
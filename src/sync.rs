@@ -0,0 +1,209 @@
+use near_sdk::{
+    assert_one_yocto, env,
+    json_types::{U128, U64},
+    near, near_bindgen, Gas, Promise, PromiseResult,
+};
+
+use crate::contract_standards::events::{VaultConfigChange, VaultSync};
+use crate::error::{ensure, VaultError};
+use crate::internal::ext_self;
+use crate::multi_token::ext_mt_core;
+use crate::{TokenizedMTVault, TokenizedMTVaultExt};
+
+const GAS_FOR_MT_BALANCE_OF: Gas = Gas::from_tgas(10);
+const GAS_FOR_RESOLVE_SYNC: Gas = Gas::from_tgas(20);
+
+/// Blocks after which a refunded deposit is assumed to be back with the depositor.
+/// `mt_resolve_transfer` normally runs a block or two after the refund, the rest
+/// is margin for congested shards.
+pub const REFUND_SETTLEMENT_BLOCKS: u64 = 100;
+
+/// One day, in nanoseconds
+pub const DEFAULT_PROFIT_UNLOCK_PERIOD: u64 = 86_400 * 1_000_000_000;
+
+/// What [`TokenizedMTVault::sync`] does with assets held above `total_assets`
+#[near(serializers = [borsh, json])]
+#[serde(rename_all = "snake_case")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SurplusPolicy {
+    /// Add the surplus to `total_assets` as yield, unlocking over `profit_unlock_period`
+    Donate,
+    /// Leave `total_assets` untouched and let the owner sweep the surplus
+    Sweep,
+}
+
+#[near_bindgen]
+impl TokenizedMTVault {
+    /// Reconciles `total_assets` against the vault's actual balance of the underlying asset.
+    ///
+    /// Anyone can call this. A surplus is handled according to the surplus policy,
+    /// a shortfall is realised as a loss, eating into the locked profit first.
+    ///
+    /// Payouts and refunds that may not have left the vault when its balance is read
+    /// are never counted as surplus. Changes to the accounting before the balance
+    /// comes back make the sync fail, so it has to be retried.
+    pub fn sync(&mut self) -> Promise {
+        self.assert_no_flash_loan();
+
+        self.internal_query_balance().then(
+            ext_self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_RESOLVE_SYNC)
+                .resolve_sync(
+                    U64(self.accounting_nonce),
+                    U128(self.internal_unsettled_refunds()),
+                ),
+        )
+    }
+
+    #[private]
+    pub fn resolve_sync(&mut self, accounting_nonce: U64, unsettled_refunds: U128) -> U128 {
+        let balance = self.internal_queried_balance(accounting_nonce);
+
+        let previous_total_assets = self.total_assets;
+        let locked_profit = self.internal_locked_profit();
+        let mut profit = 0;
+        let mut loss = 0;
+
+        // Flash loan bonds are owed to their borrowers, so only the rest backs the shares
+        let backing = balance.saturating_sub(self.total_flash_loan_bonds);
+        if backing >= self.total_assets {
+            let surplus = self.internal_surplus(balance, unsettled_refunds.0);
+
+            match self.surplus_policy {
+                SurplusPolicy::Donate if surplus > 0 => {
+                    profit = surplus;
+                    self.total_assets += surplus;
+                    self.locked_profit = locked_profit
+                        .checked_add(surplus)
+                        .unwrap_or_else(|| VaultError::Overflow("Locked profit").panic());
+                    self.last_report = env::block_timestamp();
+                    self.sweepable_surplus = 0;
+                }
+                _ => {
+                    self.sweepable_surplus = surplus;
+                }
+            }
+        } else {
+            loss = self.total_assets - backing;
+            self.internal_realize_loss(loss);
+            self.sweepable_surplus = 0;
+        }
+
+        if self.total_assets != previous_total_assets {
+            self.internal_on_accounting_change();
+        }
+
+        VaultSync {
+            token_id: &self.asset_token_id,
+            balance: U128(balance),
+            previous_total_assets: U128(previous_total_assets),
+            total_assets: U128(self.total_assets),
+            profit: U128(profit),
+            loss: U128(loss),
+            sweepable_surplus: U128(self.sweepable_surplus),
+        }
+        .emit();
+
+        U128(self.total_assets)
+    }
+
+    #[payable]
+    pub fn set_surplus_policy(&mut self, surplus_policy: SurplusPolicy) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        VaultConfigChange {
+            parameter: "surplus_policy",
+            old_value: serde_json::json!(self.surplus_policy),
+            new_value: serde_json::json!(surplus_policy),
+        }
+        .emit();
+
+        self.surplus_policy = surplus_policy;
+    }
+
+    #[payable]
+    pub fn set_profit_unlock_period(&mut self, profit_unlock_period: U64) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        // Re-anchor the currently locked amount so the new period only applies from now on
+        self.locked_profit = self.internal_locked_profit();
+        self.last_report = env::block_timestamp();
+
+        VaultConfigChange {
+            parameter: "profit_unlock_period",
+            old_value: serde_json::json!(U64(self.profit_unlock_period)),
+            new_value: serde_json::json!(profit_unlock_period),
+        }
+        .emit();
+
+        self.profit_unlock_period = profit_unlock_period.0;
+    }
+
+    pub fn surplus_policy(&self) -> SurplusPolicy {
+        self.surplus_policy
+    }
+
+    pub fn profit_unlock_period(&self) -> U64 {
+        U64(self.profit_unlock_period)
+    }
+
+    pub fn locked_profit(&self) -> U128 {
+        U128(self.internal_locked_profit())
+    }
+
+    pub fn sweepable_surplus(&self) -> U128 {
+        U128(self.sweepable_surplus)
+    }
+}
+
+impl TokenizedMTVault {
+    /// Asks the asset contract for the vault's balance of the asset
+    pub(crate) fn internal_query_balance(&self) -> Promise {
+        ext_mt_core::ext(self.asset.clone())
+            .with_static_gas(GAS_FOR_MT_BALANCE_OF)
+            .mt_balance_of(env::current_account_id(), self.asset_token_id.clone())
+    }
+
+    /// Balance returned by `internal_query_balance`, as long as the accounting did
+    /// not change since `accounting_nonce` was taken
+    pub(crate) fn internal_queried_balance(&self, accounting_nonce: U64) -> u128 {
+        let balance = match env::promise_result(0) {
+            PromiseResult::Successful(value) => {
+                serde_json::from_slice::<U128>(&value)
+                    .unwrap_or_else(|_| VaultError::BalanceQueryFailed.panic())
+                    .0
+            }
+            _ => VaultError::BalanceQueryFailed.panic(),
+        };
+
+        // A deposit or withdrawal landed between the balance query and now,
+        // so the balance cannot be compared against total_assets
+        ensure!(
+            accounting_nonce.0 == self.accounting_nonce,
+            VaultError::AccountingChanged
+        );
+
+        balance
+    }
+
+    /// Part of `balance` that backs no shares, is no flash loan bond and is not on
+    /// its way in or out.
+    ///
+    /// Payouts may or may not have left when the balance was read, and refunds may
+    /// not have been taken back yet. Neither is surplus, but neither hides a loss,
+    /// as the balance can only drop once they settle.
+    pub(crate) fn internal_surplus(&self, balance: u128, unsettled_refunds: u128) -> u128 {
+        let reserved = [
+            self.in_flight_outflows,
+            unsettled_refunds,
+            self.total_flash_loan_bonds,
+        ]
+        .into_iter()
+        .try_fold(self.total_assets, u128::checked_add)
+        .unwrap_or_else(|| VaultError::Overflow("Reserved assets").panic());
+
+        balance.saturating_sub(reserved)
+    }
+}
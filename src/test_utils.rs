@@ -0,0 +1,188 @@
+//! Helpers for native unit tests, which run the contract against near-sdk's
+//! mocked blockchain instead of a sandbox node.
+
+use near_contract_standards::fungible_token::metadata::{FungibleTokenMetadata, FT_METADATA_SPEC};
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::{get_logs, VMContextBuilder};
+use near_sdk::{
+    test_vm_config, testing_env, AccountId, NearToken, PromiseOrValue, PromiseResult,
+    RuntimeFeesConfig,
+};
+use serde_json::Value;
+
+use crate::multi_token::MultiTokenReceiver;
+use crate::TokenizedMTVault;
+
+pub const TOKEN_ID: &str = "token1";
+/// Storage deposit the builder gives every account above its registration
+pub const STORAGE_DEPOSIT: u128 = 10u128.pow(25);
+
+pub fn vault_id() -> AccountId {
+    "vault.near".parse().unwrap()
+}
+
+pub fn asset_id() -> AccountId {
+    "asset.near".parse().unwrap()
+}
+
+pub fn owner() -> AccountId {
+    "owner.near".parse().unwrap()
+}
+
+pub fn alice() -> AccountId {
+    "alice.near".parse().unwrap()
+}
+
+pub fn bob() -> AccountId {
+    "bob.near".parse().unwrap()
+}
+
+/// Makes `predecessor_id` call the vault with `deposit` yoctoNEAR attached
+pub fn set_caller(predecessor_id: &AccountId, deposit: u128) {
+    testing_env!(context(predecessor_id)
+        .attached_deposit(NearToken::from_yoctonear(deposit))
+        .build());
+}
+
+/// Makes `predecessor_id` call the vault at `timestamp` nanoseconds
+pub fn set_caller_at(predecessor_id: &AccountId, timestamp: u64) {
+    testing_env!(context(predecessor_id).block_timestamp(timestamp).build());
+}
+
+/// Makes `predecessor_id` call the vault in block `block_height` with `deposit` yoctoNEAR attached
+pub fn set_caller_in_block(predecessor_id: &AccountId, deposit: u128, block_height: u64) {
+    testing_env!(context(predecessor_id)
+        .attached_deposit(NearToken::from_yoctonear(deposit))
+        .block_height(block_height)
+        .build());
+}
+
+/// Makes the vault call itself back with `result` as the outcome of the awaited promise
+pub fn set_callback_result(result: PromiseResult) {
+    testing_env!(
+        context(&vault_id()).build(),
+        test_vm_config(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![result],
+    );
+}
+
+fn context(predecessor_id: &AccountId) -> VMContextBuilder {
+    let mut builder = VMContextBuilder::new();
+    builder
+        .current_account_id(vault_id())
+        .predecessor_account_id(predecessor_id.clone())
+        .signer_account_id(predecessor_id.clone());
+    builder
+}
+
+/// Vault state for a test, set up through the same entry points as on chain.
+///
+/// ```ignore
+/// let vault = VaultBuilder::new().deposit(&alice(), 1000).donate(500).build();
+/// ```
+#[derive(Default)]
+pub struct VaultBuilder {
+    accounts: Vec<AccountId>,
+    deposits: Vec<(AccountId, u128)>,
+    donation: u128,
+}
+
+impl VaultBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `account_id` for shares without depositing
+    pub fn register(mut self, account_id: &AccountId) -> Self {
+        self.accounts.push(account_id.clone());
+        self
+    }
+
+    /// Registers `account_id` and deposits `assets` for it, in call order
+    pub fn deposit(mut self, account_id: &AccountId, assets: u128) -> Self {
+        self.accounts.push(account_id.clone());
+        self.deposits.push((account_id.clone(), assets));
+        self
+    }
+
+    /// Adds `assets` to `total_assets` after the deposits without minting shares,
+    /// as an unlocked donation would
+    pub fn donate(mut self, assets: u128) -> Self {
+        self.donation += assets;
+        self
+    }
+
+    pub fn build(self) -> TokenizedMTVault {
+        // Start from empty storage, property tests build many vaults on one thread
+        near_sdk::mock::with_mocked_blockchain(|blockchain| blockchain.take_storage());
+        set_caller(&owner(), 0);
+        let mut vault = TokenizedMTVault::new(asset_id(), TOKEN_ID.to_string(), metadata());
+
+        for account_id in &self.accounts {
+            if !vault.token.accounts.contains_key(account_id) {
+                vault.token.internal_register_account(account_id);
+                vault.internal_add_storage_balance(account_id, STORAGE_DEPOSIT);
+            }
+        }
+
+        for (account_id, assets) in &self.deposits {
+            let unused = mt_deposit(&mut vault, account_id, *assets, "");
+            assert_eq!(unused, 0, "Builder deposits must be used in full");
+        }
+
+        if self.donation > 0 {
+            vault.total_assets += self.donation;
+            vault.internal_on_accounting_change();
+        }
+
+        vault
+    }
+}
+
+pub fn metadata() -> FungibleTokenMetadata {
+    FungibleTokenMetadata {
+        spec: FT_METADATA_SPEC.to_string(),
+        name: "USDT Vault".to_string(),
+        symbol: "vUSDT".to_string(),
+        icon: None,
+        reference: None,
+        reference_hash: None,
+        decimals: 24,
+    }
+}
+
+/// Deposits `amount` of the vault asset from `sender_id` with `msg`, as the asset
+/// contract would, and returns the unused amount
+pub fn mt_deposit(
+    vault: &mut TokenizedMTVault,
+    sender_id: &AccountId,
+    amount: u128,
+    msg: &str,
+) -> u128 {
+    set_caller(&asset_id(), 0);
+    let result = vault.mt_on_transfer(
+        sender_id.clone(),
+        vec![sender_id.clone()],
+        vec![TOKEN_ID.to_string()],
+        vec![U128(amount)],
+        msg.to_string(),
+    );
+
+    match result {
+        PromiseOrValue::Value(unused) => unused[0].0,
+        PromiseOrValue::Promise(_) => panic!("Deposits resolve without a promise"),
+    }
+}
+
+/// Data of every `vault` event called `name` logged since the context was last set
+pub fn vault_event_data(name: &str) -> Vec<Value> {
+    get_logs()
+        .iter()
+        .filter_map(|log| log.strip_prefix("EVENT_JSON:"))
+        .map(|json| serde_json::from_str::<Value>(json).expect("Invalid event JSON"))
+        .filter(|event| event["standard"] == "vault" && event["event"] == name)
+        .flat_map(|event| event["data"].as_array().cloned().unwrap_or_default())
+        .collect()
+}
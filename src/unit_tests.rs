@@ -0,0 +1,824 @@
+//! Native unit tests of the contract logic. The sandbox tests in `tests/` cover
+//! the same paths end to end against the compiled WASM.
+
+use near_contract_standards::fungible_token::FungibleTokenCore;
+use near_sdk::json_types::{U128, U64};
+use near_sdk::test_utils::get_created_receipts;
+use near_sdk::PromiseResult;
+use serde_json::json;
+
+use crate::contract_standards::VaultCore;
+use crate::multi_token::MultiTokenReceiver;
+use crate::sync::SurplusPolicy;
+use crate::test_utils::{
+    alice, asset_id, bob, mt_deposit, owner, set_callback_result, set_caller, set_caller_at,
+    set_caller_in_block, vault_event_data, VaultBuilder, TOKEN_ID,
+};
+
+// ===== mt_on_transfer =====
+
+/// Test that a plain deposit mints shares 1:1 into an empty vault
+#[test]
+fn test_deposit_into_empty_vault() {
+    let mut vault = VaultBuilder::new().register(&alice()).build();
+
+    let unused = mt_deposit(&mut vault, &alice(), 1000, "");
+
+    assert_eq!(unused, 0);
+    assert_eq!(vault.ft_balance_of(alice()).0, 1000);
+    assert_eq!(vault.ft_total_supply().0, 1000);
+    assert_eq!(vault.total_assets, 1000);
+
+    let deposits = vault_event_data("vault_deposit");
+    assert_eq!(deposits.len(), 1);
+    assert_eq!(deposits[0]["owner_id"], "alice.near");
+    assert_eq!(deposits[0]["shares"], "1000");
+}
+
+/// Test that a message that is not a deposit message deposits to the sender
+#[test]
+fn test_deposit_with_unparsable_message() {
+    let mut vault = VaultBuilder::new().register(&alice()).build();
+
+    let unused = mt_deposit(&mut vault, &alice(), 1000, "not json");
+
+    assert_eq!(unused, 0);
+    assert_eq!(vault.ft_balance_of(alice()).0, 1000);
+}
+
+/// Test that `receiver_id` and `memo` of the message are honoured
+#[test]
+fn test_deposit_to_receiver() {
+    let mut vault = VaultBuilder::new()
+        .register(&alice())
+        .register(&bob())
+        .build();
+
+    let msg = json!({"receiver_id": bob(), "memo": "for bob"}).to_string();
+    mt_deposit(&mut vault, &alice(), 1000, &msg);
+
+    assert_eq!(vault.ft_balance_of(alice()).0, 0);
+    assert_eq!(vault.ft_balance_of(bob()).0, 1000);
+
+    let deposits = vault_event_data("vault_deposit");
+    assert_eq!(deposits[0]["sender_id"], "alice.near");
+    assert_eq!(deposits[0]["owner_id"], "bob.near");
+    assert_eq!(deposits[0]["memo"], "for bob");
+}
+
+/// Test that a deposit below `min_shares` is refunded in full with its error code
+#[test]
+fn test_deposit_below_min_shares_is_refunded() {
+    let mut vault = VaultBuilder::new()
+        .deposit(&bob(), 1000)
+        .donate(1000)
+        .build();
+
+    let msg = json!({"min_shares": "1000"}).to_string();
+    let unused = mt_deposit(&mut vault, &alice(), 1000, &msg);
+
+    assert_eq!(unused, 1000);
+    assert_eq!(vault.ft_balance_of(alice()).0, 0);
+    assert_eq!(vault.total_assets, 2000);
+
+    let refunds = vault_event_data("vault_deposit_refund");
+    assert_eq!(refunds.len(), 1);
+    assert_eq!(refunds[0]["code"], "SLIPPAGE_EXCEEDED");
+    assert_eq!(refunds[0]["amount"], "1000");
+    assert!(vault_event_data("vault_deposit").is_empty());
+}
+
+/// Test that `max_shares` caps the mint and returns the assets it did not need
+#[test]
+fn test_deposit_capped_by_max_shares() {
+    let mut vault = VaultBuilder::new().register(&alice()).build();
+
+    let msg = json!({"max_shares": "400"}).to_string();
+    let unused = mt_deposit(&mut vault, &alice(), 1000, &msg);
+
+    assert_eq!(unused, 600);
+    assert_eq!(vault.ft_balance_of(alice()).0, 400);
+    assert_eq!(vault.total_assets, 400);
+}
+
+/// Test that a deposit from a token other than the vault asset is rejected
+#[test]
+#[should_panic(expected = "VAULT_ERROR[WRONG_ASSET]")]
+fn test_deposit_of_wrong_asset() {
+    let mut vault = VaultBuilder::new().register(&alice()).build();
+
+    set_caller(&alice(), 0);
+    vault.mt_on_transfer(
+        alice(),
+        vec![alice()],
+        vec![TOKEN_ID.to_string()],
+        vec![U128(1000)],
+        String::new(),
+    );
+}
+
+/// Test that a deposit of another token ID of the asset contract is rejected
+#[test]
+#[should_panic(expected = "VAULT_ERROR[WRONG_TOKEN_ID]")]
+fn test_deposit_of_wrong_token_id() {
+    let mut vault = VaultBuilder::new().register(&alice()).build();
+
+    set_caller(&asset_id(), 0);
+    vault.mt_on_transfer(
+        alice(),
+        vec![alice()],
+        vec!["token2".to_string()],
+        vec![U128(1000)],
+        String::new(),
+    );
+}
+
+/// Test that batch deposits are rejected
+#[test]
+#[should_panic(expected = "VAULT_ERROR[BATCH_DEPOSIT]")]
+fn test_batch_deposit() {
+    let mut vault = VaultBuilder::new().register(&alice()).build();
+
+    set_caller(&asset_id(), 0);
+    vault.mt_on_transfer(
+        alice(),
+        vec![alice(), alice()],
+        vec![TOKEN_ID.to_string(), TOKEN_ID.to_string()],
+        vec![U128(500), U128(500)],
+        String::new(),
+    );
+}
+
+// ===== Conversion rounding =====
+
+/// Test that every preview rounds in favour of the vault at an uneven rate
+#[test]
+fn test_preview_rounding() {
+    // 1000 shares backed by 1500 assets, i.e. 1501 / 1001 assets per share
+    let vault = VaultBuilder::new()
+        .deposit(&alice(), 1000)
+        .donate(500)
+        .build();
+
+    assert_eq!(vault.preview_deposit(U128(100)).0, 66);
+    assert_eq!(vault.preview_mint(U128(100)).0, 150);
+    assert_eq!(vault.preview_redeem(U128(100)).0, 149);
+    assert_eq!(vault.preview_withdraw(U128(100)).0, 67);
+    assert_eq!(vault.convert_to_shares(U128(100)).0, 66);
+    assert_eq!(vault.convert_to_assets(U128(100)).0, 149);
+}
+
+/// Test that depositing and redeeming straight away never returns more than deposited
+#[test]
+fn test_round_trip_does_not_profit() {
+    let mut vault = VaultBuilder::new()
+        .deposit(&alice(), 1000)
+        .donate(500)
+        .register(&bob())
+        .build();
+
+    let unused = mt_deposit(&mut vault, &bob(), 100, "");
+    let deposited = 100 - unused;
+    let shares = vault.ft_balance_of(bob()).0;
+
+    assert_eq!(shares, 66);
+    assert!(vault.preview_redeem(U128(shares)).0 <= deposited);
+}
+
+/// Test that a sole holder can redeem every share for every asset
+#[test]
+fn test_sole_holder_exits_in_full() {
+    let vault = VaultBuilder::new().deposit(&alice(), 1000).build();
+
+    assert_eq!(vault.max_redeem(alice()).0, 1000);
+    assert_eq!(vault.preview_redeem(U128(1000)).0, 1000);
+}
+
+// ===== internal_execute_withdrawal =====
+
+/// Test that a withdrawal burns shares and books the assets out before the transfer
+#[test]
+fn test_execute_withdrawal_effects() {
+    let mut vault = VaultBuilder::new().deposit(&alice(), 1000).build();
+
+    set_caller(&alice(), 1);
+    vault.internal_execute_withdrawal(alice(), Some(bob()), 300, 300, None);
+
+    assert_eq!(vault.ft_balance_of(alice()).0, 700);
+    assert_eq!(vault.ft_total_supply().0, 700);
+    assert_eq!(vault.total_assets, 700);
+
+    let pending = vault.pending_withdrawals.get(&alice()).unwrap_or_default();
+    assert_eq!(pending.requests, 1);
+    assert_eq!(pending.shares.0, 300);
+    assert_eq!(pending.assets.0, 300);
+
+    // The asset transfer and its callback
+    let receipts = get_created_receipts();
+    assert_eq!(receipts.len(), 2);
+    assert_eq!(receipts[0].receiver_id, asset_id());
+}
+
+/// Test that a withdrawal cannot burn more shares than the owner holds
+#[test]
+#[should_panic(expected = "VAULT_ERROR[INSUFFICIENT_SHARES]")]
+fn test_execute_withdrawal_insufficient_shares() {
+    let mut vault = VaultBuilder::new().deposit(&alice(), 1000).build();
+
+    set_caller(&alice(), 1);
+    vault.internal_execute_withdrawal(alice(), None, 1001, 1001, None);
+}
+
+/// Test that a withdrawal paying out nothing is rejected
+#[test]
+#[should_panic(expected = "VAULT_ERROR[ZERO_WITHDRAWAL]")]
+fn test_execute_withdrawal_of_nothing() {
+    let mut vault = VaultBuilder::new().deposit(&alice(), 1000).build();
+
+    set_caller(&alice(), 1);
+    vault.internal_execute_withdrawal(alice(), None, 1, 0, None);
+}
+
+// ===== resolve_withdraw =====
+
+/// Test that a successful transfer finalises the withdrawal as executed
+#[test]
+fn test_resolve_withdraw_success() {
+    let mut vault = VaultBuilder::new().deposit(&alice(), 1000).build();
+    set_caller(&alice(), 1);
+    vault.internal_execute_withdrawal(alice(), None, 300, 300, None);
+
+    set_callback_result(PromiseResult::Successful(vec![]));
+    let assets = vault.resolve_withdraw(alice(), alice(), U128(300), U128(300), None);
+
+    assert_eq!(assets.0, 300);
+    assert_eq!(vault.ft_balance_of(alice()).0, 700);
+    assert_eq!(vault.total_assets, 700);
+    assert_eq!(
+        vault
+            .pending_withdrawals
+            .get(&alice())
+            .unwrap_or_default()
+            .requests,
+        0
+    );
+
+    let withdrawals = vault_event_data("vault_withdraw");
+    assert_eq!(withdrawals.len(), 1);
+    assert_eq!(withdrawals[0]["assets"], "300");
+}
+
+/// Test that a failed transfer mints the shares back and restores the assets
+#[test]
+fn test_resolve_withdraw_failure_rolls_back() {
+    let mut vault = VaultBuilder::new().deposit(&alice(), 1000).build();
+    set_caller(&alice(), 1);
+    vault.internal_execute_withdrawal(alice(), Some(bob()), 300, 300, None);
+
+    set_callback_result(PromiseResult::Failed);
+    let assets = vault.resolve_withdraw(alice(), bob(), U128(300), U128(300), None);
+
+    assert_eq!(assets.0, 0);
+    assert_eq!(vault.ft_balance_of(alice()).0, 1000);
+    assert_eq!(vault.ft_total_supply().0, 1000);
+    assert_eq!(vault.total_assets, 1000);
+    assert_eq!(
+        vault
+            .pending_withdrawals
+            .get(&alice())
+            .unwrap_or_default()
+            .requests,
+        0
+    );
+
+    let failures = vault_event_data("vault_withdraw_failed");
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0]["code"], "ASSET_TRANSFER_FAILED");
+    assert_eq!(failures[0]["receiver_id"], "bob.near");
+}
+
+// ===== resolve_sync =====
+
+/// Starts a sync and returns the arguments its callback will be called with
+fn start_sync(vault: &mut crate::TokenizedMTVault) -> (U64, U128) {
+    set_caller(&bob(), 0);
+    let _ = vault.sync();
+    (
+        U64(vault.accounting_nonce),
+        U128(vault.internal_unsettled_refunds()),
+    )
+}
+
+fn balance_result(balance: u128) -> PromiseResult {
+    PromiseResult::Successful(serde_json::to_vec(&U128(balance)).unwrap())
+}
+
+/// Test that a payout still on its way out is not booked as surplus
+#[test]
+fn test_sync_ignores_in_flight_payouts() {
+    let mut vault = VaultBuilder::new().deposit(&alice(), 1000).build();
+    set_caller(&alice(), 1);
+    vault.internal_execute_withdrawal(alice(), None, 300, 300, None);
+
+    // The balance was read before the asset contract ran the transfer
+    let (nonce, unsettled_refunds) = start_sync(&mut vault);
+    set_callback_result(balance_result(1000));
+    vault.resolve_sync(nonce, unsettled_refunds);
+
+    assert_eq!(vault.total_assets, 700);
+    assert_eq!(vault.sweepable_surplus, 0);
+
+    set_callback_result(PromiseResult::Successful(vec![]));
+    vault.resolve_withdraw(alice(), alice(), U128(300), U128(300), None);
+    assert_eq!(vault.in_flight_outflows, 0);
+    assert_ne!(
+        vault.accounting_nonce, nonce.0,
+        "Resolving a payout must invalidate syncs in flight"
+    );
+}
+
+/// Test that a refund landing while a sync is in flight makes it fail
+#[test]
+#[should_panic(expected = "VAULT_ERROR[ACCOUNTING_CHANGED]")]
+fn test_refund_invalidates_sync() {
+    let mut vault = VaultBuilder::new().deposit(&alice(), 1000).build();
+    let (nonce, unsettled_refunds) = start_sync(&mut vault);
+
+    let msg = json!({"min_shares": "10000"}).to_string();
+    assert_eq!(mt_deposit(&mut vault, &bob(), 1000, &msg), 1000);
+
+    set_callback_result(balance_result(2000));
+    vault.resolve_sync(nonce, unsettled_refunds);
+}
+
+/// Test that a refund the asset contract has not taken back yet is not donated
+#[test]
+fn test_sync_ignores_unsettled_refunds() {
+    let mut vault = VaultBuilder::new().deposit(&alice(), 1000).build();
+    vault.surplus_policy = SurplusPolicy::Donate;
+
+    let msg = json!({"min_shares": "10000"}).to_string();
+    mt_deposit(&mut vault, &bob(), 1000, &msg);
+
+    // 1000 refunded and not yet taken back, 50 of actual yield
+    let (nonce, unsettled_refunds) = start_sync(&mut vault);
+    assert_eq!(unsettled_refunds.0, 1000);
+    set_callback_result(balance_result(2050));
+    vault.resolve_sync(nonce, unsettled_refunds);
+
+    assert_eq!(vault.total_assets, 1050);
+}
+
+/// Test that a deposit too small to mint a share is refunded with its error code
+#[test]
+fn test_zero_share_deposit_is_refunded() {
+    let mut vault = VaultBuilder::new()
+        .deposit(&alice(), 1000)
+        .donate(1000)
+        .register(&bob())
+        .build();
+
+    assert_eq!(mt_deposit(&mut vault, &bob(), 1, ""), 1);
+    assert_eq!(vault.ft_balance_of(bob()).0, 0);
+    assert_eq!(vault.internal_unsettled_refunds(), 1);
+
+    let refunds = vault_event_data("vault_deposit_refund");
+    assert_eq!(refunds[0]["code"], "ZERO_DEPOSIT");
+}
+
+// ===== resolve_rescue_surplus =====
+
+/// Builds a vault whose last sync found `surplus` above the 1000 deposited assets
+fn vault_with_surplus(surplus: u128) -> crate::TokenizedMTVault {
+    let mut vault = VaultBuilder::new().deposit(&alice(), 1000).build();
+    let (nonce, unsettled_refunds) = start_sync(&mut vault);
+    set_callback_result(balance_result(1000 + surplus));
+    vault.resolve_sync(nonce, unsettled_refunds);
+    assert_eq!(vault.sweepable_surplus, surplus);
+    vault
+}
+
+/// Test that the surplus is sent out when the balance still holds it
+#[test]
+fn test_rescue_surplus_within_live_balance() {
+    let mut vault = vault_with_surplus(300);
+
+    set_callback_result(balance_result(1300));
+    let nonce = U64(vault.accounting_nonce);
+    let _ = vault.resolve_rescue_surplus(U128(300), bob(), nonce, U128(0));
+
+    assert_eq!(vault.sweepable_surplus, 0);
+    assert_eq!(vault.in_flight_outflows, 300);
+    assert_eq!(vault.total_assets, 1000);
+}
+
+/// Test that a surplus recorded by sync but gone from the balance cannot be rescued
+#[test]
+#[should_panic(expected = "VAULT_ERROR[EXCEEDS_SWEEPABLE_SURPLUS]")]
+fn test_rescue_surplus_capped_by_live_balance() {
+    let mut vault = vault_with_surplus(300);
+
+    // Part of the surplus left the vault since the sync
+    set_callback_result(balance_result(1100));
+    let nonce = U64(vault.accounting_nonce);
+    let _ = vault.resolve_rescue_surplus(U128(300), bob(), nonce, U128(0));
+}
+
+/// Test that assets owed to an in-flight payout cannot be rescued
+#[test]
+#[should_panic(expected = "VAULT_ERROR[EXCEEDS_SWEEPABLE_SURPLUS]")]
+fn test_rescue_surplus_excludes_in_flight_payouts() {
+    let mut vault = vault_with_surplus(300);
+    set_caller(&alice(), 1);
+    vault.internal_execute_withdrawal(alice(), None, 500, 500, None);
+
+    // 200 was lost since the sync and the payout has not left yet, so only 100 of
+    // the 600 above total_assets is surplus
+    set_callback_result(balance_result(1100));
+    let nonce = U64(vault.accounting_nonce);
+    let _ = vault.resolve_rescue_surplus(U128(300), bob(), nonce, U128(0));
+}
+
+// ===== resolve_flash_loan =====
+
+/// Lends 1000 at a 1% fee to bob, who posted a bond of 1010, and resolves the
+/// loan after bob repaid `repaid`
+fn resolve_loan(repaid: u128) -> crate::TokenizedMTVault {
+    let mut vault = VaultBuilder::new()
+        .deposit(&alice(), 1000)
+        .register(&bob())
+        .build();
+    vault.flash_loans_enabled = true;
+    vault.flash_loan_fee_bps = 100;
+
+    let msg = json!({"flash_loan_bond": true}).to_string();
+    assert_eq!(mt_deposit(&mut vault, &bob(), 1010, &msg), 0);
+    assert_eq!(vault.flash_loan_bond(bob()).0, 1010);
+
+    set_caller(&bob(), 0);
+    let _ = vault.flash_loan(bob(), U128(1000), String::new());
+    if repaid > 0 {
+        assert_eq!(mt_deposit(&mut vault, &bob(), repaid, ""), 0);
+    }
+
+    set_callback_result(PromiseResult::Successful(vec![]));
+    assert_eq!(vault.resolve_flash_loan().0, repaid);
+    vault
+}
+
+/// Test that a loan repaid in full keeps the bond and credits the fee
+#[test]
+fn test_flash_loan_repaid_in_full() {
+    let vault = resolve_loan(1010);
+
+    assert_eq!(vault.total_assets, 1010);
+    assert_eq!(vault.flash_loan_bond(bob()).0, 1010);
+    assert_eq!(vault_event_data("vault_flash_loan")[0]["bond_seized"], "0");
+}
+
+/// Test that the fee of a loan repaid without it comes out of the bond
+#[test]
+fn test_flash_loan_repaid_principal_only() {
+    let vault = resolve_loan(1000);
+
+    assert_eq!(vault.total_assets, 1010);
+    assert_eq!(vault.flash_loan_bond(bob()).0, 1000);
+    assert_eq!(vault.total_flash_loan_bonds, 1000);
+
+    let fees = vault_event_data("vault_fee_accrued");
+    assert_eq!(fees[0]["amount"], "10");
+}
+
+/// Test that the shortfall of a partly repaid loan comes out of the bond
+#[test]
+fn test_flash_loan_partially_repaid() {
+    let vault = resolve_loan(500);
+
+    assert_eq!(vault.total_assets, 1010);
+    assert_eq!(vault.flash_loan_bond(bob()).0, 500);
+    assert_eq!(
+        vault_event_data("vault_flash_loan")[0]["bond_seized"],
+        "510"
+    );
+}
+
+/// Test that an unpaid loan is covered by the bond and leaves no loss for sync
+#[test]
+fn test_flash_loan_not_repaid() {
+    let mut vault = resolve_loan(0);
+
+    assert_eq!(vault.total_assets, 1010);
+    assert_eq!(vault.flash_loan_bond(bob()).0, 0);
+    assert_eq!(vault.total_flash_loan_bonds, 0);
+
+    // 1000 deposited plus the 1010 bond, less the 1000 lent out
+    let (nonce, unsettled_refunds) = start_sync(&mut vault);
+    set_callback_result(balance_result(1010));
+    vault.resolve_sync(nonce, unsettled_refunds);
+
+    assert_eq!(vault.total_assets, 1010);
+    assert_eq!(vault_event_data("vault_sync")[0]["loss"], "0");
+}
+
+/// Test that a loan larger than the bond covers is rejected
+#[test]
+#[should_panic(expected = "VAULT_ERROR[INSUFFICIENT_FLASH_LOAN_BOND]")]
+fn test_flash_loan_exceeding_bond() {
+    let mut vault = VaultBuilder::new()
+        .deposit(&alice(), 1000)
+        .register(&bob())
+        .build();
+    vault.flash_loans_enabled = true;
+    vault.flash_loan_fee_bps = 100;
+
+    let msg = json!({"flash_loan_bond": true}).to_string();
+    mt_deposit(&mut vault, &bob(), 1000, &msg);
+
+    set_caller(&bob(), 0);
+    let _ = vault.flash_loan(bob(), U128(1000), String::new());
+}
+
+/// Test that nobody else can take a loan against a borrower's bond
+#[test]
+#[should_panic(expected = "VAULT_ERROR[NOT_FLASH_LOAN_RECEIVER]")]
+fn test_flash_loan_against_someone_elses_bond() {
+    let mut vault = VaultBuilder::new()
+        .deposit(&alice(), 1000)
+        .register(&bob())
+        .build();
+    vault.flash_loans_enabled = true;
+    vault.flash_loan_fee_bps = 100;
+
+    let msg = json!({"flash_loan_bond": true}).to_string();
+    mt_deposit(&mut vault, &bob(), 1010, &msg);
+
+    set_caller(&alice(), 0);
+    let _ = vault.flash_loan(bob(), U128(1000), String::new());
+}
+
+/// Test that bonds are not mistaken for surplus
+#[test]
+fn test_sync_ignores_flash_loan_bonds() {
+    let mut vault = VaultBuilder::new()
+        .deposit(&alice(), 1000)
+        .register(&bob())
+        .build();
+    vault.surplus_policy = SurplusPolicy::Donate;
+
+    let msg = json!({"flash_loan_bond": true}).to_string();
+    mt_deposit(&mut vault, &bob(), 500, &msg);
+
+    let (nonce, unsettled_refunds) = start_sync(&mut vault);
+    set_callback_result(balance_result(1500));
+    vault.resolve_sync(nonce, unsettled_refunds);
+
+    assert_eq!(vault.total_assets, 1000);
+    assert_eq!(vault.ft_balance_of(bob()).0, 0);
+}
+
+// ===== Price history =====
+
+/// Builds a vault keeping 4 price checkpoints that recorded 6, at timestamps 10 to
+/// 60, so the ring has wrapped and holds those of 30 to 60
+fn vault_with_wrapped_price_history() -> crate::TokenizedMTVault {
+    let mut vault = VaultBuilder::new().deposit(&alice(), 1000).build();
+    vault.price_history.clear();
+    vault.price_history_length = 4;
+
+    for step in 1..=6u64 {
+        set_caller_at(&alice(), step * 10);
+        vault.total_assets = 1000 + step as u128 * 100;
+        vault.internal_record_price_checkpoint();
+    }
+    assert_eq!(vault.price_history_head, 2);
+    vault
+}
+
+/// Test that the price at a timestamp is found across the wrapped ring
+#[test]
+fn test_price_per_share_at_wrapped_history() {
+    let vault = vault_with_wrapped_price_history();
+    let price = |timestamp| {
+        vault
+            .price_per_share_at(U64(timestamp))
+            .map(|price| price.0)
+    };
+
+    assert_eq!(price(29), None, "Checkpoints before 30 were overwritten");
+    assert!(price(30).unwrap() < price(40).unwrap());
+    assert_eq!(price(45), price(40));
+    assert_eq!(price(60), price(1_000));
+}
+
+/// Test that the TWAP weighs every checkpoint in the window by how long it held
+#[test]
+fn test_twap_over_wrapped_history() {
+    let vault = vault_with_wrapped_price_history();
+    let price = |timestamp| vault.price_per_share_at(U64(timestamp)).unwrap().0;
+
+    set_caller_at(&alice(), 70);
+    let twap = vault.twap_price_per_share(U64(35)).0;
+
+    let expected = (5 * price(30) + 10 * price(40) + 10 * price(50) + 10 * price(60)) / 35;
+    assert_eq!(twap, expected);
+}
+
+/// Test that a TWAP window reaching past the oldest checkpoint is rejected
+#[test]
+#[should_panic(expected = "VAULT_ERROR[PRICE_HISTORY_TOO_SHORT]")]
+fn test_twap_beyond_history() {
+    let vault = vault_with_wrapped_price_history();
+
+    set_caller_at(&alice(), 70);
+    vault.twap_price_per_share(U64(41));
+}
+
+// ===== Share checkpoints =====
+
+/// Test that dust transfers every block neither cut bob's history short nor cost him
+/// storage, so a past-votes lookup keeps working
+#[test]
+fn test_dust_transfers_keep_past_votes() {
+    let mut vault = VaultBuilder::new()
+        .deposit(&alice(), 1_000_000)
+        .deposit(&bob(), 1000)
+        .build();
+    let bob_shares = vault.ft_balance_of(bob()).0;
+    let supply = vault.ft_total_supply().0;
+    let bob_storage = vault.internal_storage_available(&bob());
+    let alice_storage = vault.internal_storage_available(&alice());
+
+    let transfers = 250;
+    for block_height in 1..=transfers {
+        set_caller_in_block(&alice(), 1, block_height);
+        vault.ft_transfer(bob(), U128(1), None);
+    }
+    set_caller_in_block(&alice(), 0, transfers + 1);
+
+    let history = vault.balance_checkpoints.get(&bob()).unwrap();
+    assert_eq!(history.checkpoints.len(), transfers + 1);
+    assert_eq!(vault.get_past_votes(bob(), U64(0)).0, bob_shares);
+    assert_eq!(vault.ft_balance_of_at(bob(), U64(0)).0, bob_shares);
+    assert_eq!(
+        vault.ft_balance_of_at(bob(), U64(transfers)).0,
+        bob_shares + transfers as u128
+    );
+    assert_eq!(vault.ft_total_supply_at(U64(0)).0, supply);
+
+    // The sender paid for every checkpoint
+    assert_eq!(vault.internal_storage_available(&bob()), bob_storage);
+    assert!(vault.internal_storage_available(&alice()) < alice_storage);
+}
+
+/// Test that a transfer is rejected once the sender's storage deposit is used up
+#[test]
+#[should_panic(expected = "VAULT_ERROR[INSUFFICIENT_STORAGE]")]
+fn test_transfer_without_storage_deposit() {
+    let mut vault = VaultBuilder::new()
+        .deposit(&alice(), 1000)
+        .register(&bob())
+        .build();
+    vault.storage_balances.insert(&alice(), &0);
+
+    set_caller_in_block(&alice(), 1, 1);
+    vault.ft_transfer(bob(), U128(1), None);
+}
+
+/// Test that a deposit from a sender without storage deposit is refunded with a code
+#[test]
+fn test_deposit_without_storage_deposit() {
+    let mut vault = VaultBuilder::new().register(&alice()).build();
+    vault.storage_balances.insert(&alice(), &0);
+
+    assert_eq!(mt_deposit(&mut vault, &alice(), 1000, ""), 1000);
+    assert_eq!(vault.ft_balance_of(alice()).0, 0);
+
+    let refunds = vault_event_data("vault_deposit_refund");
+    assert_eq!(refunds[0]["code"], "INSUFFICIENT_STORAGE");
+}
+
+/// Test that the storage a deposit may add stays within what is required up front
+#[test]
+fn test_deposit_storage_bound() {
+    let mut vault = VaultBuilder::new()
+        .register(&alice())
+        .register(&owner())
+        .build();
+    vault.referral_fee_bps = 100;
+
+    // Fresh histories for the owner and the referrer
+    let msg = json!({"receiver_id": alice(), "referrer": owner()}).to_string();
+    let before = vault.internal_storage_available(&alice());
+    assert_eq!(mt_deposit(&mut vault, &alice(), 1000, &msg), 0);
+
+    let charged = before - vault.internal_storage_available(&alice());
+    assert!(charged > 0);
+    assert!(charged <= crate::storage::storage_cost(crate::storage::DEPOSIT_STORAGE_BYTES));
+}
+
+// ===== Referrals =====
+
+/// Test that the referral fee comes out of the depositor's shares without diluting holders
+#[test]
+fn test_referral_fee_taken_from_deposit() {
+    // 2002 / 1001 assets per share, so the deposit converts exactly
+    let mut vault = VaultBuilder::new()
+        .deposit(&alice(), 1000)
+        .donate(1001)
+        .register(&bob())
+        .register(&owner())
+        .build();
+    vault.referral_fee_bps = 100;
+    let price_before = vault.convert_to_assets(U128(1000)).0;
+    let shares = vault.preview_deposit(U128(20_000)).0;
+
+    let msg = json!({"referrer": owner()}).to_string();
+    assert_eq!(mt_deposit(&mut vault, &bob(), 20_000, &msg), 0);
+
+    let fee = shares / 100;
+    assert_eq!(vault.ft_balance_of(owner()).0, fee);
+    assert_eq!(vault.ft_balance_of(bob()).0, shares - fee);
+    assert_eq!(vault.ft_total_supply().0, 1000 + shares);
+    assert!(vault.convert_to_assets(U128(1000)).0 >= price_before);
+
+    let deposits = vault_event_data("vault_deposit");
+    assert_eq!(deposits[0]["shares"], (shares - fee).to_string());
+}
+
+/// Test that min_shares bounds the shares the depositor keeps after the referral fee
+#[test]
+fn test_min_shares_applies_after_referral_fee() {
+    let mut vault = VaultBuilder::new()
+        .register(&bob())
+        .register(&owner())
+        .build();
+    vault.referral_fee_bps = 100;
+
+    let msg = json!({"referrer": owner(), "min_shares": "1000"}).to_string();
+    assert_eq!(mt_deposit(&mut vault, &bob(), 1000, &msg), 1000);
+    assert_eq!(
+        vault_event_data("vault_deposit_refund")[0]["code"],
+        "SLIPPAGE_EXCEEDED"
+    );
+}
+
+/// Test that deposits referred by the depositor, an unregistered or a frozen account are refunded
+#[test]
+fn test_invalid_referrers_are_refunded() {
+    let mut vault = VaultBuilder::new()
+        .register(&bob())
+        .register(&owner())
+        .build();
+    vault.referral_fee_bps = 100;
+    vault.frozen.insert(&owner());
+
+    for (referrer, code) in [
+        (bob(), "SELF_REFERRAL"),
+        (alice(), "REFERRER_NOT_REGISTERED"),
+        (owner(), "ACCOUNT_FROZEN"),
+    ] {
+        let msg = json!({"referrer": referrer}).to_string();
+        assert_eq!(mt_deposit(&mut vault, &bob(), 1000, &msg), 1000);
+        assert_eq!(vault_event_data("vault_deposit_refund")[0]["code"], code);
+        assert_eq!(vault.referral_stats(referrer).deposits, 0);
+    }
+    assert_eq!(vault.ft_total_supply().0, 0);
+}
+
+// ===== Allowlist =====
+
+/// Test that a referrer outside the allowlist cannot be paid fee shares
+#[test]
+fn test_referrer_must_be_allowlisted() {
+    let mut vault = VaultBuilder::new()
+        .register(&bob())
+        .register(&owner())
+        .build();
+    vault.allowlist_enabled = true;
+    vault.allowlist.insert(&bob());
+
+    let msg = json!({"referrer": owner()}).to_string();
+    assert_eq!(mt_deposit(&mut vault, &bob(), 1000, &msg), 1000);
+    assert_eq!(vault.ft_balance_of(bob()).0, 0);
+    assert_eq!(
+        vault_event_data("vault_deposit_refund")[0]["code"],
+        "NOT_ALLOWLISTED"
+    );
+}
+
+/// Test that seized shares cannot go to an account outside the allowlist
+#[test]
+#[should_panic(expected = "VAULT_ERROR[NOT_ALLOWLISTED]")]
+fn test_seize_recovery_must_be_allowlisted() {
+    let mut vault = VaultBuilder::new()
+        .deposit(&alice(), 1000)
+        .register(&bob())
+        .build();
+    vault.allowlist_enabled = true;
+    vault.allowlist.insert(&alice());
+    vault.guardian = Some(owner());
+    vault.frozen.insert(&alice());
+
+    set_caller(&owner(), 1);
+    vault.seize(alice(), bob(), None);
+}
@@ -0,0 +1,106 @@
+use near_contract_standards::fungible_token::metadata::FungibleTokenMetadataProvider;
+use near_contract_standards::fungible_token::FungibleTokenCore;
+use near_contract_standards::storage_management::{StorageBalance, StorageManagement};
+use near_sdk::{
+    json_types::{U128, U64},
+    near, near_bindgen, AccountId,
+};
+
+use crate::contract_standards::VaultCore;
+use crate::mul_div::Rounding;
+use crate::sync::SurplusPolicy;
+use crate::{TokenizedMTVault, TokenizedMTVaultExt, CONTRACT_VERSION};
+
+/// Withdrawals of an account that burned shares but whose asset transfer
+/// has not been resolved yet
+#[near(serializers = [borsh, json])]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PendingWithdrawals {
+    pub requests: u32,
+    pub shares: U128,
+    pub assets: U128,
+}
+
+/// Everything a frontend needs to render one account's position in the vault
+#[near(serializers = [json])]
+pub struct AccountPosition {
+    pub account_id: AccountId,
+    pub shares: U128,
+    pub assets: U128,
+    pub max_withdraw: U128,
+    pub max_redeem: U128,
+    pub max_deposit: U128,
+    pub pending_withdrawals: PendingWithdrawals,
+    pub storage_balance: Option<StorageBalance>,
+}
+
+/// Vault-wide figures and configuration, for dashboards and monitoring
+#[near(serializers = [json])]
+#[derive(Clone, Debug)]
+pub struct VaultInfo {
+    pub asset: AccountId,
+    pub asset_token_id: String,
+    pub total_assets: U128,
+    pub total_supply: U128,
+    /// Assets redeemable for one whole share, i.e. `10^decimals` share units
+    pub price_per_share: U128,
+    pub decimals: u8,
+    pub owner: AccountId,
+    pub surplus_policy: SurplusPolicy,
+    pub profit_unlock_period: U64,
+    pub locked_profit: U128,
+    pub sweepable_surplus: U128,
+    pub flash_loans_enabled: bool,
+    pub flash_loan_fee_bps: u16,
+    pub flash_loan_open: bool,
+    pub referral_fee_bps: u16,
+    pub allowlist_enabled: bool,
+    pub guardian: Option<AccountId>,
+    pub version: String,
+}
+
+#[near_bindgen]
+impl TokenizedMTVault {
+    pub fn get_vault_info(&self) -> VaultInfo {
+        let metadata = self.ft_metadata();
+
+        VaultInfo {
+            asset: self.asset(),
+            asset_token_id: self.asset_token_id(),
+            total_assets: self.total_assets(),
+            total_supply: self.ft_total_supply(),
+            price_per_share: U128(self.internal_price_per_share(metadata.decimals)),
+            decimals: metadata.decimals,
+            owner: self.owner.clone(),
+            surplus_policy: self.surplus_policy(),
+            profit_unlock_period: self.profit_unlock_period(),
+            locked_profit: self.locked_profit(),
+            sweepable_surplus: self.sweepable_surplus(),
+            flash_loans_enabled: self.flash_loans_enabled(),
+            flash_loan_fee_bps: self.flash_loan_fee_bps(),
+            flash_loan_open: self.is_flash_loan_open(),
+            referral_fee_bps: self.referral_fee_bps(),
+            allowlist_enabled: self.allowlist_enabled(),
+            guardian: self.guardian(),
+            version: CONTRACT_VERSION.to_string(),
+        }
+    }
+
+    pub fn get_account_position(&self, account_id: AccountId) -> AccountPosition {
+        let shares = self.token.ft_balance_of(account_id.clone());
+
+        AccountPosition {
+            shares,
+            assets: U128(self.internal_convert_to_assets(shares.0, Rounding::Down)),
+            max_withdraw: self.max_withdraw(account_id.clone()),
+            max_redeem: self.max_redeem(account_id.clone()),
+            max_deposit: self.max_deposit(account_id.clone()),
+            pending_withdrawals: self
+                .pending_withdrawals
+                .get(&account_id)
+                .unwrap_or_default(),
+            storage_balance: self.storage_balance_of(account_id.clone()),
+            account_id,
+        }
+    }
+}
@@ -0,0 +1,267 @@
+use near_contract_standards::fungible_token::FungibleTokenCore;
+use near_sdk::{
+    assert_one_yocto,
+    collections::Vector,
+    env,
+    json_types::{U128, U64},
+    near, near_bindgen, AccountId,
+};
+
+use crate::contract_standards::events::VaultDelegate;
+use crate::error::{ensure, VaultError};
+use crate::{StorageKey, TokenizedMTVault, TokenizedMTVaultExt};
+
+/// Share amount at the end of a block in which it changed
+#[near(serializers = [borsh])]
+#[derive(Clone, Debug)]
+pub struct ShareCheckpoint {
+    pub block_height: u64,
+    pub amount: u128,
+}
+
+/// Every checkpoint of a share amount. Nothing is ever pruned, the account that
+/// adds a checkpoint pays for its storage.
+#[near(serializers = [borsh])]
+pub struct ShareHistory {
+    pub(crate) checkpoints: Vector<ShareCheckpoint>,
+}
+
+impl ShareHistory {
+    pub fn new(prefix: StorageKey) -> Self {
+        Self {
+            checkpoints: Vector::new(prefix),
+        }
+    }
+
+    /// Most recent amount, 0 if there is none
+    fn latest(&self) -> u128 {
+        match self.checkpoints.len().checked_sub(1) {
+            Some(newest) => self.get(newest).amount,
+            None => 0,
+        }
+    }
+
+    /// Amount of the last checkpoint at or before `block_height`, 0 if there is none
+    fn at(&self, block_height: u64) -> u128 {
+        // Find the first checkpoint written after `block_height`
+        let mut low = 0;
+        let mut high = self.checkpoints.len();
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if self.get(mid).block_height > block_height {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+
+        match low.checked_sub(1) {
+            Some(index) => self.get(index).amount,
+            None => 0,
+        }
+    }
+
+    /// Records `amount` for the current block, overwriting the newest checkpoint
+    /// if it was written earlier in the same block
+    fn push(&mut self, amount: u128) {
+        let checkpoint = ShareCheckpoint {
+            block_height: env::block_height(),
+            amount,
+        };
+
+        match self.checkpoints.len().checked_sub(1) {
+            Some(newest) if self.get(newest).block_height == checkpoint.block_height => {
+                self.checkpoints.replace(newest, &checkpoint);
+                return;
+            }
+            _ => {}
+        }
+
+        self.checkpoints.push(&checkpoint);
+    }
+
+    fn get(&self, index: u64) -> ShareCheckpoint {
+        self.checkpoints
+            .get(index)
+            .expect("Share checkpoint missing")
+    }
+}
+
+#[near_bindgen]
+impl TokenizedMTVault {
+    /// Shares held by `account_id` at the end of `block_height`, which must be in the past.
+    pub fn ft_balance_of_at(&self, account_id: AccountId, block_height: U64) -> U128 {
+        assert_past_block(block_height.0);
+
+        U128(
+            self.balance_checkpoints
+                .get(&account_id)
+                .map_or(0, |history| history.at(block_height.0)),
+        )
+    }
+
+    /// Total share supply at the end of `block_height`, which must be in the past.
+    pub fn ft_total_supply_at(&self, block_height: U64) -> U128 {
+        assert_past_block(block_height.0);
+
+        U128(self.supply_checkpoints.at(block_height.0))
+    }
+
+    /// Shares of every account delegating to `account_id`, including its own unless
+    /// it delegated them away.
+    pub fn get_votes(&self, account_id: AccountId) -> U128 {
+        U128(
+            self.vote_checkpoints
+                .get(&account_id)
+                .map_or(0, |history| history.latest()),
+        )
+    }
+
+    /// Votes of `account_id` at the end of `block_height`, which must be in the past.
+    pub fn get_past_votes(&self, account_id: AccountId, block_height: U64) -> U128 {
+        assert_past_block(block_height.0);
+
+        U128(
+            self.vote_checkpoints
+                .get(&account_id)
+                .map_or(0, |history| history.at(block_height.0)),
+        )
+    }
+
+    /// Account whose votes the shares of `account_id` count towards.
+    pub fn delegates(&self, account_id: AccountId) -> AccountId {
+        self.delegates.get(&account_id).unwrap_or(account_id)
+    }
+
+    /// Counts the caller's shares towards the votes of `delegatee`.
+    ///
+    /// Shares count towards their holder until delegated. Passing `None` takes
+    /// the delegation back. The caller pays for the vote checkpoints this adds.
+    #[payable]
+    pub fn delegate(&mut self, delegatee: Option<AccountId>) {
+        assert_one_yocto();
+
+        let delegator = env::predecessor_account_id();
+        ensure!(
+            self.token.accounts.contains_key(&delegator),
+            VaultError::NotRegistered(delegator)
+        );
+
+        let delegatee = delegatee.unwrap_or_else(|| delegator.clone());
+        let previous_delegatee = self.delegates(delegator.clone());
+        if previous_delegatee == delegatee {
+            return;
+        }
+
+        if delegatee == delegator {
+            self.delegates.remove(&delegator);
+        } else {
+            self.delegates.insert(&delegator, &delegatee);
+        }
+
+        let storage_before = env::storage_usage();
+        let balance = self.token.ft_balance_of(delegator.clone()).0;
+        self.internal_move_votes(Some(&previous_delegatee), Some(&delegatee), balance);
+        self.internal_charge_storage(&delegator, storage_before);
+
+        VaultDelegate {
+            delegator_id: &delegator,
+            from_delegatee_id: &previous_delegatee,
+            to_delegatee_id: &delegatee,
+        }
+        .emit();
+    }
+}
+
+impl TokenizedMTVault {
+    /// Must be called after every change to share balances, with each account whose
+    /// balance may have changed, to checkpoint the balances, votes and total supply.
+    ///
+    /// `payer` is charged for the storage of the new checkpoints. Only changes the vault
+    /// makes on its own, like rolling back a failed withdrawal, pass `None`.
+    pub fn internal_on_share_balance_change(
+        &mut self,
+        accounts: &[&AccountId],
+        payer: Option<&AccountId>,
+    ) {
+        let storage_before = env::storage_usage();
+
+        for &account_id in accounts {
+            let balance = self.token.ft_balance_of(account_id.clone()).0;
+            let mut history = self.balance_checkpoints.get(account_id).unwrap_or_else(|| {
+                ShareHistory::new(StorageKey::AccountBalanceCheckpoints {
+                    account_hash: env::sha256(account_id.as_bytes()),
+                })
+            });
+
+            let previous_balance = history.latest();
+            if balance == previous_balance {
+                continue;
+            }
+            history.push(balance);
+            self.balance_checkpoints.insert(account_id, &history);
+
+            let delegatee = self.delegates(account_id.clone());
+            if balance > previous_balance {
+                self.internal_move_votes(None, Some(&delegatee), balance - previous_balance);
+            } else {
+                self.internal_move_votes(Some(&delegatee), None, previous_balance - balance);
+            }
+        }
+
+        let total_supply = self.token.ft_total_supply().0;
+        if total_supply != self.supply_checkpoints.latest() {
+            self.supply_checkpoints.push(total_supply);
+        }
+
+        if let Some(payer) = payer {
+            self.internal_charge_storage(payer, storage_before);
+        }
+    }
+
+    fn internal_move_votes(
+        &mut self,
+        from: Option<&AccountId>,
+        to: Option<&AccountId>,
+        amount: u128,
+    ) {
+        if amount == 0 {
+            return;
+        }
+
+        if let Some(from) = from {
+            self.internal_update_votes(from, |votes| {
+                votes
+                    .checked_sub(amount)
+                    .unwrap_or_else(|| VaultError::Overflow("Votes").panic())
+            });
+        }
+        if let Some(to) = to {
+            self.internal_update_votes(to, |votes| {
+                votes
+                    .checked_add(amount)
+                    .unwrap_or_else(|| VaultError::Overflow("Votes").panic())
+            });
+        }
+    }
+
+    fn internal_update_votes(&mut self, account_id: &AccountId, update: impl FnOnce(u128) -> u128) {
+        let mut history = self.vote_checkpoints.get(account_id).unwrap_or_else(|| {
+            ShareHistory::new(StorageKey::AccountVoteCheckpoints {
+                account_hash: env::sha256(account_id.as_bytes()),
+            })
+        });
+
+        let votes = update(history.latest());
+        history.push(votes);
+        self.vote_checkpoints.insert(account_id, &history);
+    }
+}
+
+fn assert_past_block(block_height: u64) {
+    // Balances of the current block can still change
+    ensure!(
+        block_height < env::block_height(),
+        VaultError::BlockNotInPast
+    );
+}
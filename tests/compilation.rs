@@ -11,3 +11,10 @@ async fn test_mock_mt_contract_compilation() -> Result<(), Box<dyn std::error::E
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_mock_flash_borrower_contract_compilation() -> Result<(), Box<dyn std::error::Error>> {
+    near_workspaces::compile_project("./mock_contracts/mock_flash_borrower").await?;
+
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use meteor_near_erc4626_vault::error::VaultError;
+use meteor_near_erc4626_vault::mul_div::MathError;
+
+/// Test that errors format as the prefix, the stable code and the message
+#[test]
+fn test_error_format() {
+    let error = VaultError::ExceedsMaxRedeem {
+        shares: 2000,
+        max_redeem: 1000,
+    };
+    assert_eq!(error.code(), "EXCEEDS_MAX_REDEEM");
+    assert_eq!(
+        error.message(),
+        "Exceeds max redeem: 2000 shares requested, 1000 redeemable"
+    );
+    assert_eq!(
+        error.to_string(),
+        "VAULT_ERROR[EXCEEDS_MAX_REDEEM] Exceeds max redeem: 2000 shares requested, 1000 redeemable"
+    );
+
+    let error = VaultError::Math(u128::MAX, 2, 1, MathError::Overflow);
+    assert_eq!(error.code(), "MATH_OVERFLOW");
+    assert_eq!(
+        error.to_string(),
+        "VAULT_ERROR[MATH_OVERFLOW] Cannot compute 340282366920938463463374607431768211455 * 2 / 1: Result does not fit in 128 bits"
+    );
+}
+
+/// Test that panicking aborts with the formatted error
+#[test]
+#[should_panic(expected = "VAULT_ERROR[NOT_OWNER] Only the owner can call this method")]
+fn test_error_panic() {
+    VaultError::NotOwner.panic();
+}
@@ -0,0 +1,20 @@
+use std::fmt::Debug;
+
+/// Asserts that `result` failed with the vault error `code`, e.g. `EXCEEDS_MAX_REDEEM`.
+///
+/// Matches the stable `VAULT_ERROR[<CODE>]` prefix only, never the message.
+pub fn assert_vault_error<T, E: Debug>(result: Result<T, E>, code: &str) {
+    match result {
+        Err(error) => {
+            let error_message = format!("{:?}", error);
+            let expected = format!("VAULT_ERROR[{}]", code);
+            assert!(
+                error_message.contains(&expected),
+                "Expected {} error, got: {}",
+                code,
+                error_message
+            );
+        }
+        Ok(_) => panic!("Expected {} error, got success", code),
+    }
+}
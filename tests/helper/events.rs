@@ -0,0 +1,83 @@
+use serde_json::Value;
+use vault_events::{parse_event_log, ParseError, EVENT_LOG_PREFIX};
+
+/// Events of the `vault` standard among `logs`, in the order they were logged.
+///
+/// Every event must decode with the `vault-events` crate and format back
+/// to the same JSON, so the contract and the decoder cannot drift apart.
+pub fn vault_events(logs: Vec<&str>) -> Vec<Value> {
+    logs.into_iter()
+        .filter_map(|log| match parse_event_log(log) {
+            Ok(event) => Some((log, event)),
+            Err(ParseError::NotAnEvent | ParseError::OtherStandard(_)) => None,
+            Err(error) => panic!("Undecodable vault event {}: {}", log, error),
+        })
+        .map(|(log, event)| {
+            let logged = event_json(log);
+            assert_eq!(
+                event_json(&event.to_event_log()),
+                logged,
+                "Decoded event does not round-trip"
+            );
+            logged
+        })
+        .collect()
+}
+
+fn event_json(log: &str) -> Value {
+    let json = log
+        .strip_prefix(EVENT_LOG_PREFIX)
+        .expect("Event log prefix missing");
+    serde_json::from_str(json).expect("Invalid event JSON")
+}
+
+/// Asserts that `events` contains a `vault` event called `name` whose data entries
+/// have exactly `fields`, and returns its first data entry
+pub fn assert_vault_event(events: &[Value], name: &str, fields: &[&str]) -> Value {
+    let event = events
+        .iter()
+        .find(|event| event["event"] == name)
+        .unwrap_or_else(|| panic!("No {} event in {:?}", name, events));
+
+    let mut keys: Vec<&str> = event
+        .as_object()
+        .expect("Event must be an object")
+        .keys()
+        .map(String::as_str)
+        .collect();
+    keys.sort_unstable();
+    assert_eq!(keys, ["data", "event", "standard", "version"]);
+    assert_eq!(event["version"], "1.0.0");
+
+    let data = event["data"].as_array().expect("Event data must be a list");
+    assert!(!data.is_empty(), "Event data must not be empty");
+
+    let mut expected_fields = fields.to_vec();
+    expected_fields.sort_unstable();
+    for entry in data {
+        let mut entry_fields: Vec<&str> = entry
+            .as_object()
+            .expect("Event data entries must be objects")
+            .keys()
+            .map(String::as_str)
+            .collect();
+        entry_fields.sort_unstable();
+        assert_eq!(
+            entry_fields, expected_fields,
+            "Unexpected fields in {}",
+            name
+        );
+    }
+
+    data[0].clone()
+}
+
+/// Data entries of the NEP-245 events called `name` among `logs`, as logged by the mock MT
+pub fn mt_events(logs: Vec<&str>, name: &str) -> Vec<Value> {
+    logs.into_iter()
+        .filter(|log| log.starts_with(EVENT_LOG_PREFIX))
+        .map(event_json)
+        .filter(|event| event["standard"] == "nep245" && event["event"] == name)
+        .flat_map(|event| event["data"].as_array().cloned().unwrap_or_default())
+        .collect()
+}
@@ -0,0 +1,47 @@
+use near_sdk::json_types::U128;
+use near_workspaces::{Account, Contract};
+use serde_json::json;
+
+pub async fn deploy_and_init_mock_flash_borrower(
+    owner: &Account,
+) -> Result<Contract, Box<dyn std::error::Error>> {
+    let contract_code =
+        near_workspaces::compile_project("./mock_contracts/mock_flash_borrower").await?;
+
+    let contract = owner.deploy(&contract_code).await?.into_result()?;
+
+    contract.call("new").transact().await?.into_result()?;
+
+    Ok(contract)
+}
+
+/// Has the borrower take a flash loan of `amount` from the vault, returning what it repaid
+pub async fn flash_borrower_borrow(
+    contract: &Contract,
+    vault_contract: &Contract,
+    amount: u128,
+    msg: &str,
+) -> Result<U128, Box<dyn std::error::Error>> {
+    let result = contract
+        .call("borrow")
+        .args_json(json!({
+            "vault_id": vault_contract.id(),
+            "amount": amount.to_string(),
+            "msg": msg,
+        }))
+        .gas(near_workspaces::types::Gas::from_tgas(300))
+        .transact()
+        .await?
+        .into_result()?;
+
+    Ok(result.json()?)
+}
+
+pub async fn flash_borrower_loans(
+    contract: &Contract,
+    account: &Account,
+) -> Result<u64, Box<dyn std::error::Error>> {
+    let result: u64 = account.view(contract.id(), "loans").await?.json()?;
+
+    Ok(result)
+}
@@ -1,5 +1,5 @@
 use near_sdk::{json_types::U128, NearToken};
-use near_workspaces::{Account, Contract};
+use near_workspaces::{Account, AccountId, Contract};
 use serde_json::json;
 
 pub async fn deploy_and_init_mock_mt(
@@ -53,3 +53,198 @@ pub async fn mt_balance_of(
 
     Ok(result.0)
 }
+
+pub async fn mt_transfer(
+    contract: &Contract,
+    sender: &Account,
+    receiver_id: &AccountId,
+    token_id: &str,
+    amount: u128,
+) -> Result<(), Box<dyn std::error::Error>> {
+    sender
+        .call(contract.id(), "mt_transfer")
+        .args_json(json!({
+            "receiver_id": receiver_id,
+            "token_id": token_id,
+            "amount": amount.to_string(),
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?
+        .into_result()?;
+
+    Ok(())
+}
+
+pub async fn mt_batch_balance_of(
+    contract: &Contract,
+    account: &Account,
+    token_ids: &[&str],
+) -> Result<Vec<u128>, Box<dyn std::error::Error>> {
+    let result: Vec<U128> = account
+        .view(contract.id(), "mt_batch_balance_of")
+        .args_json(json!({
+            "account_id": account.id(),
+            "token_ids": token_ids,
+        }))
+        .await?
+        .json()?;
+
+    Ok(result.into_iter().map(|balance| balance.0).collect())
+}
+
+/// Allows `account_id` to transfer `amount` of each of `token_ids` owned by `owner`
+/// and returns the approval IDs, in the order of `token_ids`
+pub async fn mt_approve(
+    contract: &Contract,
+    owner: &Account,
+    token_ids: &[&str],
+    amount: u128,
+    account_id: &AccountId,
+) -> Result<Vec<u64>, Box<dyn std::error::Error>> {
+    owner
+        .call(contract.id(), "mt_approve")
+        .args_json(json!({
+            "token_ids": token_ids,
+            "amounts": vec![amount.to_string(); token_ids.len()],
+            "account_id": account_id,
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?
+        .into_result()?;
+
+    let mut approval_ids = Vec::with_capacity(token_ids.len());
+    for token_id in token_ids {
+        let approval = mt_approval(contract, owner, token_id, account_id)
+            .await?
+            .ok_or("Approval was not recorded")?;
+        approval_ids.push(approval.0);
+    }
+
+    Ok(approval_ids)
+}
+
+/// ID and remaining amount of the approval `owner` gave `account_id` for `token_id`
+pub async fn mt_approval(
+    contract: &Contract,
+    owner: &Account,
+    token_id: &str,
+    account_id: &AccountId,
+) -> Result<Option<(u64, u128)>, Box<dyn std::error::Error>> {
+    let approval: Option<serde_json::Value> = owner
+        .view(contract.id(), "mt_approval")
+        .args_json(json!({
+            "owner_id": owner.id(),
+            "token_id": token_id,
+            "account_id": account_id,
+        }))
+        .await?
+        .json()?;
+
+    Ok(approval.map(|approval| {
+        let amount: U128 = serde_json::from_value(approval["amount"].clone()).unwrap();
+        (approval["approval_id"].as_u64().unwrap(), amount.0)
+    }))
+}
+
+/// Whether `account_id` may transfer `amount` of `token_id` for some owner
+pub async fn mt_is_approved(
+    contract: &Contract,
+    token_id: &str,
+    account_id: &AccountId,
+    amount: u128,
+    approval_id: Option<u64>,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let approved = contract
+        .view("mt_is_approved")
+        .args_json(json!({
+            "token_ids": [token_id],
+            "approved_account_id": account_id,
+            "amounts": [amount.to_string()],
+            "approval_ids": approval_id.map(|approval_id| vec![approval_id]),
+        }))
+        .await?
+        .json()?;
+
+    Ok(approved)
+}
+
+/// Makes the mock misbehave as described by `mode`, e.g. `json!({"mode": "all_transfers"})`.
+/// The mock is owned by its own account, so the contract sets the mode itself.
+pub async fn mt_set_failure_mode(
+    contract: &Contract,
+    mode: serde_json::Value,
+) -> Result<(), Box<dyn std::error::Error>> {
+    contract
+        .call("set_failure_mode")
+        .args_json(json!({ "mode": mode }))
+        .transact()
+        .await?
+        .into_result()?;
+
+    Ok(())
+}
+
+/// Mints `amount` of yield to `account_id`, e.g. the vault, as the mock's owner
+pub async fn mt_credit_yield(
+    contract: &Contract,
+    account_id: &AccountId,
+    token_id: &str,
+    amount: u128,
+) -> Result<(), Box<dyn std::error::Error>> {
+    contract
+        .call("credit_yield")
+        .args_json(json!({
+            "account_id": account_id,
+            "token_id": token_id,
+            "amount": amount.to_string(),
+        }))
+        .transact()
+        .await?
+        .into_result()?;
+
+    Ok(())
+}
+
+/// Burns `amount` from `account_id` to simulate a loss, as the mock's owner
+pub async fn mt_slash(
+    contract: &Contract,
+    account_id: &AccountId,
+    token_id: &str,
+    amount: u128,
+) -> Result<(), Box<dyn std::error::Error>> {
+    contract
+        .call("slash")
+        .args_json(json!({
+            "account_id": account_id,
+            "token_id": token_id,
+            "amount": amount.to_string(),
+        }))
+        .transact()
+        .await?
+        .into_result()?;
+
+    Ok(())
+}
+
+/// Streams `amount_per_second` of yield to `account_id` from now on, as the mock's owner
+pub async fn mt_set_yield_rate(
+    contract: &Contract,
+    account_id: &AccountId,
+    token_id: &str,
+    amount_per_second: u128,
+) -> Result<(), Box<dyn std::error::Error>> {
+    contract
+        .call("set_yield_rate")
+        .args_json(json!({
+            "account_id": account_id,
+            "token_id": token_id,
+            "amount_per_second": amount_per_second.to_string(),
+        }))
+        .transact()
+        .await?
+        .into_result()?;
+
+    Ok(())
+}
@@ -1,2 +1,7 @@
+#![allow(dead_code)]
+
+pub mod errors;
+pub mod events;
+pub mod mock_flash_borrower;
 pub mod mock_mt;
 pub mod vault;
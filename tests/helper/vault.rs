@@ -1,6 +1,6 @@
 use near_contract_standards::fungible_token::metadata::FungibleTokenMetadata;
 use near_sdk::{json_types::U128, NearToken};
-use near_workspaces::{Account, Contract};
+use near_workspaces::{Account, AccountId, Contract};
 use serde_json::json;
 
 pub async fn deploy_and_init_vault(
@@ -72,6 +72,7 @@ pub async fn vault_storage_deposit(
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn mt_transfer_call_deposit(
     mt_contract: &Contract,
     vault_contract: &Contract,
@@ -107,13 +108,15 @@ pub async fn mt_transfer_call_deposit(
             "amount": amount.to_string(),
             "msg": msg,
         }))
-        .deposit(NearToken::from_yoctonear(0))
+        .deposit(NearToken::from_yoctonear(1))
         .gas(near_workspaces::types::Gas::from_tgas(300))
         .transact()
         .await?
         .into_result()?;
 
-    Ok(result.json()?)
+    // NEP-245 resolves to the used amount of every transferred token
+    let used: Vec<U128> = result.json()?;
+    Ok(used[0])
 }
 
 pub async fn vault_redeem(
@@ -254,3 +257,596 @@ pub async fn vault_total_supply(
         .json()?;
     Ok(result)
 }
+
+pub async fn vault_sync(
+    vault_contract: &Contract,
+    account: &Account,
+) -> Result<U128, Box<dyn std::error::Error>> {
+    let result = account
+        .call(vault_contract.id(), "sync")
+        .gas(near_workspaces::types::Gas::from_tgas(100))
+        .transact()
+        .await?
+        .into_result()?;
+
+    Ok(result.json()?)
+}
+
+pub async fn vault_set_surplus_policy(
+    vault_contract: &Contract,
+    owner: &Account,
+    surplus_policy: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    owner
+        .call(vault_contract.id(), "set_surplus_policy")
+        .args_json(json!({"surplus_policy": surplus_policy}))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?
+        .into_result()?;
+
+    Ok(())
+}
+
+pub async fn vault_set_profit_unlock_period(
+    vault_contract: &Contract,
+    owner: &Account,
+    profit_unlock_period: u64,
+) -> Result<(), Box<dyn std::error::Error>> {
+    owner
+        .call(vault_contract.id(), "set_profit_unlock_period")
+        .args_json(json!({"profit_unlock_period": profit_unlock_period.to_string()}))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?
+        .into_result()?;
+
+    Ok(())
+}
+
+pub async fn vault_sweepable_surplus(
+    vault_contract: &Contract,
+    account: &Account,
+) -> Result<U128, Box<dyn std::error::Error>> {
+    let result: U128 = account
+        .view(vault_contract.id(), "sweepable_surplus")
+        .await?
+        .json()?;
+    Ok(result)
+}
+
+pub async fn vault_locked_profit(
+    vault_contract: &Contract,
+    account: &Account,
+) -> Result<U128, Box<dyn std::error::Error>> {
+    let result: U128 = account
+        .view(vault_contract.id(), "locked_profit")
+        .await?
+        .json()?;
+    Ok(result)
+}
+
+pub async fn vault_rescue_mt(
+    vault_contract: &Contract,
+    owner: &Account,
+    token: &AccountId,
+    token_id: &str,
+    amount: u128,
+    receiver_id: &AccountId,
+) -> Result<U128, Box<dyn std::error::Error>> {
+    let result = owner
+        .call(vault_contract.id(), "rescue_mt")
+        .args_json(json!({
+            "token": token,
+            "token_id": token_id,
+            "amount": amount.to_string(),
+            "receiver_id": receiver_id,
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(near_workspaces::types::Gas::from_tgas(300))
+        .transact()
+        .await?
+        .into_result()?;
+
+    Ok(result.json()?)
+}
+
+pub async fn vault_set_flash_loan_config(
+    vault_contract: &Contract,
+    owner: &Account,
+    enabled: bool,
+    fee_bps: u16,
+) -> Result<(), Box<dyn std::error::Error>> {
+    owner
+        .call(vault_contract.id(), "set_flash_loan_config")
+        .args_json(json!({"enabled": enabled, "fee_bps": fee_bps}))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?
+        .into_result()?;
+
+    Ok(())
+}
+
+pub async fn vault_flash_loan(
+    vault_contract: &Contract,
+    account: &Account,
+    receiver_id: &AccountId,
+    amount: u128,
+    msg: &str,
+) -> Result<U128, Box<dyn std::error::Error>> {
+    let result = account
+        .call(vault_contract.id(), "flash_loan")
+        .args_json(json!({
+            "receiver_id": receiver_id,
+            "amount": amount.to_string(),
+            "msg": msg,
+        }))
+        .gas(near_workspaces::types::Gas::from_tgas(300))
+        .transact()
+        .await?
+        .into_result()?;
+
+    Ok(result.json()?)
+}
+
+pub async fn vault_is_flash_loan_open(
+    vault_contract: &Contract,
+    account: &Account,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let result: bool = account
+        .view(vault_contract.id(), "is_flash_loan_open")
+        .await?
+        .json()?;
+    Ok(result)
+}
+
+pub async fn vault_post_flash_loan_bond(
+    mt_contract: &Contract,
+    vault_contract: &Contract,
+    sender: &Account,
+    token_id: &str,
+    amount: u128,
+) -> Result<U128, Box<dyn std::error::Error>> {
+    let result = sender
+        .call(mt_contract.id(), "mt_transfer_call")
+        .args_json(json!({
+            "receiver_id": vault_contract.id(),
+            "token_id": token_id,
+            "amount": amount.to_string(),
+            "msg": json!({"flash_loan_bond": true}).to_string(),
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(near_workspaces::types::Gas::from_tgas(300))
+        .transact()
+        .await?
+        .into_result()?;
+
+    // NEP-245 resolves to the used amount of every transferred token
+    let used: Vec<U128> = result.json()?;
+    Ok(used[0])
+}
+
+pub async fn vault_withdraw_flash_loan_bond(
+    vault_contract: &Contract,
+    account: &Account,
+    amount: u128,
+) -> Result<U128, Box<dyn std::error::Error>> {
+    let result = account
+        .call(vault_contract.id(), "withdraw_flash_loan_bond")
+        .args_json(json!({"amount": amount.to_string()}))
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(near_workspaces::types::Gas::from_tgas(100))
+        .transact()
+        .await?
+        .into_result()?;
+
+    Ok(result.json()?)
+}
+
+pub async fn vault_flash_loan_bond(
+    vault_contract: &Contract,
+    account: &Account,
+    account_id: &AccountId,
+) -> Result<U128, Box<dyn std::error::Error>> {
+    let result: U128 = account
+        .view(vault_contract.id(), "flash_loan_bond")
+        .args_json(json!({"account_id": account_id}))
+        .await?
+        .json()?;
+    Ok(result)
+}
+
+pub async fn vault_get_account_position(
+    vault_contract: &Contract,
+    account: &Account,
+    account_id: &Account,
+) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+    let result: serde_json::Value = account
+        .view(vault_contract.id(), "get_account_position")
+        .args_json(json!({"account_id": account_id.id()}))
+        .await?
+        .json()?;
+    Ok(result)
+}
+
+pub async fn vault_get_vault_info(
+    vault_contract: &Contract,
+    account: &Account,
+) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+    let result: serde_json::Value = account
+        .view(vault_contract.id(), "get_vault_info")
+        .await?
+        .json()?;
+    Ok(result)
+}
+
+pub async fn vault_price_per_share_at(
+    vault_contract: &Contract,
+    account: &Account,
+    timestamp: u64,
+) -> Result<Option<U128>, Box<dyn std::error::Error>> {
+    let result: Option<U128> = account
+        .view(vault_contract.id(), "price_per_share_at")
+        .args_json(json!({"timestamp": timestamp.to_string()}))
+        .await?
+        .json()?;
+    Ok(result)
+}
+
+pub async fn vault_twap_price_per_share(
+    vault_contract: &Contract,
+    account: &Account,
+    window_ns: u64,
+) -> Result<U128, Box<dyn std::error::Error>> {
+    let result: U128 = account
+        .view(vault_contract.id(), "twap_price_per_share")
+        .args_json(json!({"window_ns": window_ns.to_string()}))
+        .await?
+        .json()?;
+    Ok(result)
+}
+
+pub async fn vault_set_price_history_length(
+    vault_contract: &Contract,
+    owner: &Account,
+    length: u32,
+) -> Result<(), Box<dyn std::error::Error>> {
+    owner
+        .call(vault_contract.id(), "set_price_history_length")
+        .args_json(json!({"length": length}))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?
+        .into_result()?;
+
+    Ok(())
+}
+
+pub async fn vault_price_history_length(
+    vault_contract: &Contract,
+    account: &Account,
+) -> Result<u32, Box<dyn std::error::Error>> {
+    let result: u32 = account
+        .view(vault_contract.id(), "price_history_length")
+        .await?
+        .json()?;
+    Ok(result)
+}
+
+pub async fn vault_ft_transfer(
+    vault_contract: &Contract,
+    sender: &Account,
+    receiver: &Account,
+    amount: u128,
+) -> Result<(), Box<dyn std::error::Error>> {
+    sender
+        .call(vault_contract.id(), "ft_transfer")
+        .args_json(json!({
+            "receiver_id": receiver.id(),
+            "amount": amount.to_string(),
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?
+        .into_result()?;
+
+    Ok(())
+}
+
+pub async fn vault_balance_of_at(
+    vault_contract: &Contract,
+    account: &Account,
+    account_id: &Account,
+    block_height: u64,
+) -> Result<U128, Box<dyn std::error::Error>> {
+    let result: U128 = account
+        .view(vault_contract.id(), "ft_balance_of_at")
+        .args_json(json!({
+            "account_id": account_id.id(),
+            "block_height": block_height.to_string(),
+        }))
+        .await?
+        .json()?;
+    Ok(result)
+}
+
+pub async fn vault_total_supply_at(
+    vault_contract: &Contract,
+    account: &Account,
+    block_height: u64,
+) -> Result<U128, Box<dyn std::error::Error>> {
+    let result: U128 = account
+        .view(vault_contract.id(), "ft_total_supply_at")
+        .args_json(json!({"block_height": block_height.to_string()}))
+        .await?
+        .json()?;
+    Ok(result)
+}
+
+pub async fn vault_delegate(
+    vault_contract: &Contract,
+    account: &Account,
+    delegatee: Option<&Account>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    account
+        .call(vault_contract.id(), "delegate")
+        .args_json(json!({"delegatee": delegatee.map(|delegatee| delegatee.id())}))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?
+        .into_result()?;
+
+    Ok(())
+}
+
+pub async fn vault_get_votes(
+    vault_contract: &Contract,
+    account: &Account,
+    account_id: &Account,
+) -> Result<U128, Box<dyn std::error::Error>> {
+    let result: U128 = account
+        .view(vault_contract.id(), "get_votes")
+        .args_json(json!({"account_id": account_id.id()}))
+        .await?
+        .json()?;
+    Ok(result)
+}
+
+pub async fn vault_get_past_votes(
+    vault_contract: &Contract,
+    account: &Account,
+    account_id: &Account,
+    block_height: u64,
+) -> Result<U128, Box<dyn std::error::Error>> {
+    let result: U128 = account
+        .view(vault_contract.id(), "get_past_votes")
+        .args_json(json!({
+            "account_id": account_id.id(),
+            "block_height": block_height.to_string(),
+        }))
+        .await?
+        .json()?;
+    Ok(result)
+}
+
+pub async fn mt_transfer_call_deposit_referred(
+    mt_contract: &Contract,
+    vault_contract: &Contract,
+    sender: &Account,
+    token_id: &str,
+    amount: u128,
+    referrer: &Account,
+) -> Result<U128, Box<dyn std::error::Error>> {
+    let result = sender
+        .call(mt_contract.id(), "mt_transfer_call")
+        .args_json(json!({
+            "receiver_id": vault_contract.id(),
+            "token_id": token_id,
+            "amount": amount.to_string(),
+            "msg": json!({"referrer": referrer.id()}).to_string(),
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(near_workspaces::types::Gas::from_tgas(300))
+        .transact()
+        .await?
+        .into_result()?;
+
+    // NEP-245 resolves to the used amount of every transferred token
+    let used: Vec<U128> = result.json()?;
+    Ok(used[0])
+}
+
+pub async fn vault_set_referral_fee_bps(
+    vault_contract: &Contract,
+    owner: &Account,
+    fee_bps: u16,
+) -> Result<(), Box<dyn std::error::Error>> {
+    owner
+        .call(vault_contract.id(), "set_referral_fee_bps")
+        .args_json(json!({"fee_bps": fee_bps}))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?
+        .into_result()?;
+
+    Ok(())
+}
+
+pub async fn vault_referral_stats(
+    vault_contract: &Contract,
+    account: &Account,
+    referrer: &Account,
+) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+    let result: serde_json::Value = account
+        .view(vault_contract.id(), "referral_stats")
+        .args_json(json!({"referrer_id": referrer.id()}))
+        .await?
+        .json()?;
+    Ok(result)
+}
+
+pub async fn vault_set_allowlist_enabled(
+    vault_contract: &Contract,
+    owner: &Account,
+    enabled: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    owner
+        .call(vault_contract.id(), "set_allowlist_enabled")
+        .args_json(json!({"enabled": enabled}))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?
+        .into_result()?;
+
+    Ok(())
+}
+
+pub async fn vault_add_to_allowlist(
+    vault_contract: &Contract,
+    owner: &Account,
+    accounts: &[&Account],
+) -> Result<(), Box<dyn std::error::Error>> {
+    owner
+        .call(vault_contract.id(), "add_to_allowlist")
+        .args_json(json!({
+            "account_ids": accounts.iter().map(|account| account.id()).collect::<Vec<_>>(),
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?
+        .into_result()?;
+
+    Ok(())
+}
+
+pub async fn vault_remove_from_allowlist(
+    vault_contract: &Contract,
+    owner: &Account,
+    accounts: &[&Account],
+) -> Result<(), Box<dyn std::error::Error>> {
+    owner
+        .call(vault_contract.id(), "remove_from_allowlist")
+        .args_json(json!({
+            "account_ids": accounts.iter().map(|account| account.id()).collect::<Vec<_>>(),
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?
+        .into_result()?;
+
+    Ok(())
+}
+
+pub async fn vault_is_allowlisted(
+    vault_contract: &Contract,
+    account: &Account,
+    account_id: &Account,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let result: bool = account
+        .view(vault_contract.id(), "is_allowlisted")
+        .args_json(json!({"account_id": account_id.id()}))
+        .await?
+        .json()?;
+    Ok(result)
+}
+
+pub async fn vault_max_deposit(
+    vault_contract: &Contract,
+    account: &Account,
+    receiver: &Account,
+) -> Result<U128, Box<dyn std::error::Error>> {
+    let result: U128 = account
+        .view(vault_contract.id(), "max_deposit")
+        .args_json(json!({"receiver_id": receiver.id()}))
+        .await?
+        .json()?;
+    Ok(result)
+}
+
+pub async fn vault_set_guardian(
+    vault_contract: &Contract,
+    owner: &Account,
+    guardian: Option<&Account>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    owner
+        .call(vault_contract.id(), "set_guardian")
+        .args_json(json!({"guardian": guardian.map(|guardian| guardian.id())}))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?
+        .into_result()?;
+
+    Ok(())
+}
+
+pub async fn vault_freeze(
+    vault_contract: &Contract,
+    caller: &Account,
+    account: &Account,
+) -> Result<(), Box<dyn std::error::Error>> {
+    caller
+        .call(vault_contract.id(), "freeze")
+        .args_json(json!({"account_id": account.id()}))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?
+        .into_result()?;
+
+    Ok(())
+}
+
+pub async fn vault_unfreeze(
+    vault_contract: &Contract,
+    caller: &Account,
+    account: &Account,
+) -> Result<(), Box<dyn std::error::Error>> {
+    caller
+        .call(vault_contract.id(), "unfreeze")
+        .args_json(json!({"account_id": account.id()}))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?
+        .into_result()?;
+
+    Ok(())
+}
+
+pub async fn vault_is_frozen(
+    vault_contract: &Contract,
+    account: &Account,
+    account_id: &Account,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let result: bool = account
+        .view(vault_contract.id(), "is_frozen")
+        .args_json(json!({"account_id": account_id.id()}))
+        .await?
+        .json()?;
+    Ok(result)
+}
+
+pub async fn vault_max_redeem(
+    vault_contract: &Contract,
+    account: &Account,
+    owner: &Account,
+) -> Result<U128, Box<dyn std::error::Error>> {
+    let result: U128 = account
+        .view(vault_contract.id(), "max_redeem")
+        .args_json(json!({"owner_id": owner.id()}))
+        .await?
+        .json()?;
+    Ok(result)
+}
+
+pub async fn vault_max_withdraw(
+    vault_contract: &Contract,
+    account: &Account,
+    owner: &Account,
+) -> Result<U128, Box<dyn std::error::Error>> {
+    let result: U128 = account
+        .view(vault_contract.id(), "max_withdraw")
+        .args_json(json!({"owner_id": owner.id()}))
+        .await?
+        .json()?;
+    Ok(result)
+}
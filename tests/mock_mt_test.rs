@@ -1,4 +1,14 @@
-use crate::helper::mock_mt::{deploy_and_init_mock_mt, mt_balance_of, mt_mint};
+use near_sdk::{json_types::U128, NearToken};
+use serde_json::json;
+
+use crate::helper::{
+    events::mt_events,
+    mock_mt::{
+        deploy_and_init_mock_mt, mt_approval, mt_approve, mt_balance_of, mt_batch_balance_of,
+        mt_is_approved, mt_mint,
+    },
+    vault::{deploy_and_init_vault, vault_balance_of, vault_storage_deposit},
+};
 
 mod helper;
 
@@ -34,3 +44,265 @@ async fn test_mock_mt_contract_is_working() -> Result<(), Box<dyn std::error::Er
 
     Ok(())
 }
+
+/// Test batch transfers, batch views and the NEP-245 events they log
+#[tokio::test]
+async fn test_mock_mt_batch_transfer() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let trent = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+    let bob = worker.dev_create_account().await?;
+
+    let mt_contract = deploy_and_init_mock_mt(&trent).await?;
+    mt_mint(&mt_contract, &alice, "token1", 1000).await?;
+    mt_mint(&mt_contract, &alice, "token2", 500).await?;
+
+    let result = alice
+        .call(mt_contract.id(), "mt_batch_transfer")
+        .args_json(json!({
+            "receiver_id": bob.id(),
+            "token_ids": ["token1", "token2"],
+            "amounts": ["300", "200"],
+            "memo": "batch",
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?
+        .into_result()?;
+
+    let transfers = mt_events(result.logs(), "mt_transfer");
+    assert_eq!(transfers.len(), 2);
+    assert_eq!(transfers[0]["old_owner_id"], alice.id().as_str());
+    assert_eq!(transfers[0]["new_owner_id"], bob.id().as_str());
+    assert_eq!(transfers[0]["token_ids"], json!(["token1"]));
+    assert_eq!(transfers[1]["amounts"], json!(["200"]));
+    assert_eq!(transfers[1]["memo"], "batch");
+    assert!(transfers[0].get("authorized_id").is_none());
+
+    assert_eq!(
+        mt_batch_balance_of(&mt_contract, &alice, &["token1", "token2"]).await?,
+        [700, 300]
+    );
+    assert_eq!(
+        mt_batch_balance_of(&mt_contract, &bob, &["token1", "token2"]).await?,
+        [300, 200]
+    );
+
+    let supplies: Vec<Option<U128>> = alice
+        .view(mt_contract.id(), "mt_batch_supply")
+        .args_json(json!({"token_ids": ["token1", "token2", "token3"]}))
+        .await?
+        .json()?;
+    assert_eq!(supplies, [Some(U128(1000)), Some(U128(500)), None]);
+
+    let tokens: serde_json::Value = alice
+        .view(mt_contract.id(), "mt_token")
+        .args_json(json!({"token_ids": ["token1", "token3"]}))
+        .await?
+        .json()?;
+    assert_eq!(
+        tokens,
+        json!([{"token_id": "token1", "owner_id": null}, null])
+    );
+
+    // Transfers require exactly one yoctoNEAR
+    let result = alice
+        .call(mt_contract.id(), "mt_transfer")
+        .args_json(json!({
+            "receiver_id": bob.id(),
+            "token_id": "token1",
+            "amount": "100",
+        }))
+        .transact()
+        .await?;
+    assert!(result.is_failure());
+
+    Ok(())
+}
+
+/// Test that approved accounts transfer on behalf of the owner within the approved amount
+#[tokio::test]
+async fn test_mock_mt_approvals() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let trent = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+    let bob = worker.dev_create_account().await?;
+    let carol = worker.dev_create_account().await?;
+
+    let mt_contract = deploy_and_init_mock_mt(&trent).await?;
+    mt_mint(&mt_contract, &alice, "token1", 1000).await?;
+
+    // Approving needs at least one yoctoNEAR
+    let result = alice
+        .call(mt_contract.id(), "mt_approve")
+        .args_json(json!({"token_ids": ["token1"], "amounts": ["300"], "account_id": bob.id()}))
+        .transact()
+        .await?;
+    assert!(result.is_failure());
+
+    let approval_ids = mt_approve(&mt_contract, &alice, &["token1"], 300, bob.id()).await?;
+    let approval = json!([alice.id(), approval_ids[0]]);
+    assert!(mt_is_approved(&mt_contract, "token1", bob.id(), 300, Some(approval_ids[0])).await?);
+    assert!(!mt_is_approved(&mt_contract, "token1", bob.id(), 301, None).await?);
+    assert!(!mt_is_approved(&mt_contract, "token1", carol.id(), 1, None).await?);
+
+    let result = bob
+        .call(mt_contract.id(), "mt_transfer")
+        .args_json(json!({
+            "receiver_id": carol.id(),
+            "token_id": "token1",
+            "amount": "200",
+            "approval": approval,
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?
+        .into_result()?;
+
+    let transfers = mt_events(result.logs(), "mt_transfer");
+    assert_eq!(transfers[0]["old_owner_id"], alice.id().as_str());
+    assert_eq!(transfers[0]["authorized_id"], bob.id().as_str());
+    assert_eq!(mt_balance_of(&mt_contract, &alice, "token1").await?, 800);
+    assert_eq!(mt_balance_of(&mt_contract, &carol, "token1").await?, 200);
+    assert_eq!(
+        mt_approval(&mt_contract, &alice, "token1", bob.id()).await?,
+        Some((approval_ids[0], 100))
+    );
+
+    // More than the rest of the approval
+    let result = bob
+        .call(mt_contract.id(), "mt_transfer")
+        .args_json(json!({
+            "receiver_id": carol.id(),
+            "token_id": "token1",
+            "amount": "200",
+            "approval": approval,
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?;
+    assert!(result.is_failure());
+
+    // A stale approval ID
+    let result = bob
+        .call(mt_contract.id(), "mt_transfer")
+        .args_json(json!({
+            "receiver_id": carol.id(),
+            "token_id": "token1",
+            "amount": "50",
+            "approval": [alice.id(), approval_ids[0] + 1],
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?;
+    assert!(result.is_failure());
+
+    alice
+        .call(mt_contract.id(), "mt_revoke")
+        .args_json(json!({"token_ids": ["token1"], "account_id": bob.id()}))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?
+        .into_result()?;
+    assert_eq!(
+        mt_approval(&mt_contract, &alice, "token1", bob.id()).await?,
+        None
+    );
+    assert!(!mt_is_approved(&mt_contract, "token1", bob.id(), 1, None).await?);
+    assert_eq!(mt_balance_of(&mt_contract, &alice, "token1").await?, 800);
+
+    Ok(())
+}
+
+/// Test that the vault rejects batch deposits and the mock refunds every token of the batch
+#[tokio::test]
+async fn test_batch_deposit_into_vault_is_refunded() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+    let vault = deploy_and_init_vault(&owner, &usdt, "token1", "USDT Vault", "vUSDT").await?;
+
+    vault_storage_deposit(&vault, &alice).await?;
+    mt_mint(&usdt, &alice, "token1", 10000).await?;
+
+    let result = alice
+        .call(usdt.id(), "mt_batch_transfer_call")
+        .args_json(json!({
+            "receiver_id": vault.id(),
+            "token_ids": ["token1", "token1"],
+            "amounts": ["300", "200"],
+            "msg": "",
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(near_workspaces::types::Gas::from_tgas(300))
+        .transact()
+        .await?
+        .into_result()?;
+
+    let used: Vec<U128> = result.json()?;
+    assert_eq!(used, [U128(0), U128(0)]);
+
+    let refunds: Vec<_> = mt_events(result.logs(), "mt_transfer")
+        .into_iter()
+        .filter(|transfer| transfer["old_owner_id"] == vault.id().as_str())
+        .collect();
+    assert_eq!(refunds.len(), 2);
+
+    assert_eq!(mt_balance_of(&usdt, &alice, "token1").await?, 10000);
+    assert_eq!(vault_balance_of(&vault, &alice, &alice).await?.0, 0);
+
+    Ok(())
+}
+
+/// Test deposits of an owner's tokens by an approved account: the sender gets the shares,
+/// and refunded tokens go back to the owner together with the approval they spent
+#[tokio::test]
+async fn test_approved_deposit_into_vault() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+    let bob = worker.dev_create_account().await?;
+
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+    let vault = deploy_and_init_vault(&owner, &usdt, "token1", "USDT Vault", "vUSDT").await?;
+
+    vault_storage_deposit(&vault, &bob).await?;
+    mt_mint(&usdt, &alice, "token1", 10000).await?;
+    let approval_ids = mt_approve(&usdt, &alice, &["token1"], 3000, bob.id()).await?;
+
+    let deposit = |amount: &str, msg: String| {
+        bob.call(usdt.id(), "mt_transfer_call")
+            .args_json(json!({
+                "receiver_id": vault.id(),
+                "token_id": "token1",
+                "amount": amount,
+                "approval": [alice.id(), approval_ids[0]],
+                "msg": msg,
+            }))
+            .deposit(NearToken::from_yoctonear(1))
+            .gas(near_workspaces::types::Gas::from_tgas(300))
+            .transact()
+    };
+
+    let used: Vec<U128> = deposit("1000", String::new())
+        .await?
+        .into_result()?
+        .json()?;
+    assert_eq!(used, [U128(1000)]);
+    assert_eq!(vault_balance_of(&vault, &bob, &bob).await?.0, 1000);
+    assert_eq!(mt_balance_of(&usdt, &alice, "token1").await?, 9000);
+
+    // Slippage refund
+    let msg = json!({"min_shares": "5000"}).to_string();
+    let used: Vec<U128> = deposit("1000", msg).await?.into_result()?.json()?;
+    assert_eq!(used, [U128(0)]);
+    assert_eq!(mt_balance_of(&usdt, &alice, "token1").await?, 9000);
+    assert_eq!(
+        mt_approval(&usdt, &alice, "token1", bob.id()).await?,
+        Some((approval_ids[0], 2000))
+    );
+
+    Ok(())
+}
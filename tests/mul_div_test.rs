@@ -0,0 +1,152 @@
+use meteor_near_erc4626_vault::mul_div::{checked_mul_div, mul_div, MathError, Rounding};
+use num_bigint::BigUint;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const ROUNDINGS: [Rounding; 4] = [
+    Rounding::Down,
+    Rounding::Up,
+    Rounding::HalfUp,
+    Rounding::HalfEven,
+];
+
+/// `x * y / denominator` computed with arbitrary precision
+fn reference(x: u128, y: u128, denominator: u128, rounding: Rounding) -> Result<u128, MathError> {
+    if denominator == 0 {
+        return Err(MathError::DivisionByZero);
+    }
+
+    let numerator = BigUint::from(x) * BigUint::from(y);
+    let denominator = BigUint::from(denominator);
+    let quotient = &numerator / &denominator;
+    let doubled_remainder = (&numerator % &denominator) * 2u32;
+    let zero = BigUint::from(0u32);
+
+    let round_up = match rounding {
+        Rounding::Down => false,
+        Rounding::Up => doubled_remainder > zero,
+        Rounding::HalfUp => doubled_remainder >= denominator,
+        Rounding::HalfEven => {
+            doubled_remainder > denominator
+                || (doubled_remainder == denominator && &quotient % 2u32 == BigUint::from(1u32))
+        }
+    };
+    let result = if round_up { quotient + 1u32 } else { quotient };
+
+    u128::try_from(result).map_err(|_| MathError::Overflow)
+}
+
+/// An operand that is often small, often close to `u128::MAX` and sometimes a power of two
+fn operand(rng: &mut StdRng) -> u128 {
+    match rng.gen_range(0..4) {
+        0 => rng.gen_range(0..1_000),
+        1 => u128::MAX - rng.gen_range(0..1_000),
+        2 => 1u128 << rng.gen_range(0..128),
+        _ => rng.gen(),
+    }
+}
+
+#[test]
+fn test_rounding() {
+    // 7 / 2 = 3.5, 5 / 2 = 2.5, 7 / 3 = 2.33, 8 / 3 = 2.67
+    let cases = [
+        (7, 2, [3, 4, 4, 4]),
+        (5, 2, [2, 3, 3, 2]),
+        (7, 3, [2, 3, 2, 2]),
+        (8, 3, [2, 3, 3, 3]),
+        (6, 3, [2, 2, 2, 2]),
+    ];
+
+    for (numerator, denominator, expected) in cases {
+        for (rounding, expected) in ROUNDINGS.into_iter().zip(expected) {
+            assert_eq!(
+                checked_mul_div(numerator, 1, denominator, rounding),
+                Ok(expected),
+                "{} / {} rounded {:?}",
+                numerator,
+                denominator,
+                rounding
+            );
+        }
+    }
+}
+
+#[test]
+fn test_errors() {
+    for rounding in ROUNDINGS {
+        assert_eq!(
+            checked_mul_div(1, 1, 0, rounding),
+            Err(MathError::DivisionByZero)
+        );
+        assert_eq!(
+            checked_mul_div(u128::MAX, 2, 1, rounding),
+            Err(MathError::Overflow)
+        );
+        assert_eq!(
+            checked_mul_div(u128::MAX, u128::MAX, u128::MAX, rounding),
+            Ok(u128::MAX)
+        );
+    }
+
+    // (2^43 - 1) * (2^86 + 2^43 + 1) = 2^129 - 1, so halving it floors to exactly
+    // u128::MAX with a remainder of 1, and every rounding but down overflows
+    let (x, y) = ((1u128 << 43) - 1, (1u128 << 86) + (1u128 << 43) + 1);
+    assert_eq!(checked_mul_div(x, y, 2, Rounding::Down), Ok(u128::MAX));
+    for rounding in [Rounding::Up, Rounding::HalfUp, Rounding::HalfEven] {
+        assert_eq!(
+            checked_mul_div(x, y, 2, rounding),
+            Err(MathError::Overflow),
+            "{:?}",
+            rounding
+        );
+    }
+
+    // This quotient is past u128::MAX before any rounding
+    assert_eq!(
+        checked_mul_div(u128::MAX, 3, 2, Rounding::Down),
+        Err(MathError::Overflow)
+    );
+    let just_below = u128::MAX - 1;
+    assert_eq!(
+        checked_mul_div(u128::MAX, just_below, just_below, Rounding::Up),
+        Ok(u128::MAX)
+    );
+    assert_eq!(
+        checked_mul_div(u128::MAX, u128::MAX, u128::MAX - 1, Rounding::Down),
+        Err(MathError::Overflow)
+    );
+}
+
+#[test]
+#[should_panic(
+    expected = "VAULT_ERROR[DIVISION_BY_ZERO] Cannot compute 1 * 1 / 0: Division by zero"
+)]
+fn test_mul_div_panics_with_reason() {
+    mul_div(1, 1, 0, Rounding::Down);
+}
+
+/// Test random operands against the arbitrary precision reference.
+/// Set `VAULT_PROPERTY_SEED` to replay a failing run.
+#[test]
+fn test_matches_reference() {
+    let seed = std::env::var("VAULT_PROPERTY_SEED")
+        .map(|seed| seed.parse().expect("Invalid VAULT_PROPERTY_SEED"))
+        .unwrap_or_else(|_| rand::random());
+    println!("VAULT_PROPERTY_SEED={}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for _ in 0..100_000 {
+        let (x, y, denominator) = (operand(&mut rng), operand(&mut rng), operand(&mut rng));
+        for rounding in ROUNDINGS {
+            assert_eq!(
+                checked_mul_div(x, y, denominator, rounding),
+                reference(x, y, denominator, rounding),
+                "{} * {} / {} rounded {:?}, replay with VAULT_PROPERTY_SEED={}",
+                x,
+                y,
+                denominator,
+                rounding,
+                seed
+            );
+        }
+    }
+}
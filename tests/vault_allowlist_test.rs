@@ -0,0 +1,145 @@
+use near_sdk::NearToken;
+use serde_json::json;
+
+use crate::helper::{
+    errors::assert_vault_error,
+    events::{assert_vault_event, vault_events},
+    mock_mt::{deploy_and_init_mock_mt, mt_balance_of, mt_mint},
+    vault::{
+        deploy_and_init_vault, mt_transfer_call_deposit, vault_add_to_allowlist, vault_balance_of,
+        vault_ft_transfer, vault_is_allowlisted, vault_max_deposit, vault_remove_from_allowlist,
+        vault_set_allowlist_enabled, vault_storage_deposit,
+    },
+};
+
+mod helper;
+
+/// Test that allowlist mode refunds deposits and blocks share transfers of unlisted accounts
+#[tokio::test]
+async fn test_allowlist_mode() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+    let bob = worker.dev_create_account().await?;
+
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+    let vault = deploy_and_init_vault(&owner, &usdt, "token1", "USDT Vault", "vUSDT").await?;
+    // The vault account initialised itself, so it is the owner
+    let vault_owner = vault.as_account();
+
+    vault_storage_deposit(&vault, &alice).await?;
+    vault_storage_deposit(&vault, &bob).await?;
+    mt_mint(&usdt, &alice, "token1", 10000).await?;
+    mt_mint(&usdt, &bob, "token1", 10000).await?;
+
+    vault_set_allowlist_enabled(&vault, vault_owner, true).await?;
+    vault_add_to_allowlist(&vault, vault_owner, &[&alice]).await?;
+    assert!(vault_is_allowlisted(&vault, &alice, &alice).await?);
+    assert!(!vault_is_allowlisted(&vault, &alice, &bob).await?);
+    assert_eq!(vault_max_deposit(&vault, &alice, &bob).await?.0, 0);
+    assert!(vault_max_deposit(&vault, &alice, &alice).await?.0 > 0);
+
+    // Unlisted senders and receivers are refunded
+    mt_transfer_call_deposit(&usdt, &vault, &bob, "token1", 1000, None, None, None, None).await?;
+    mt_transfer_call_deposit(
+        &usdt,
+        &vault,
+        &alice,
+        "token1",
+        1000,
+        Some(&bob),
+        None,
+        None,
+        None,
+    )
+    .await?;
+    assert_eq!(mt_balance_of(&usdt, &alice, "token1").await?, 10000);
+    assert_eq!(mt_balance_of(&usdt, &bob, "token1").await?, 10000);
+
+    mt_transfer_call_deposit(
+        &usdt, &vault, &alice, "token1", 1000, None, None, None, None,
+    )
+    .await?;
+    assert_eq!(vault_balance_of(&vault, &alice, &alice).await?.0, 1000);
+
+    // Shares only move to allowlisted accounts
+    assert_vault_error(
+        vault_ft_transfer(&vault, &alice, &bob, 100).await,
+        "NOT_ALLOWLISTED",
+    );
+    vault_add_to_allowlist(&vault, vault_owner, &[&bob]).await?;
+    vault_ft_transfer(&vault, &alice, &bob, 100).await?;
+    vault_remove_from_allowlist(&vault, vault_owner, &[&bob]).await?;
+    assert_vault_error(
+        vault_ft_transfer(&vault, &alice, &bob, 100).await,
+        "NOT_ALLOWLISTED",
+    );
+
+    // Turning the mode off lets everyone in again
+    vault_set_allowlist_enabled(&vault, vault_owner, false).await?;
+    vault_ft_transfer(&vault, &alice, &bob, 100).await?;
+    mt_transfer_call_deposit(&usdt, &vault, &bob, "token1", 1000, None, None, None, None).await?;
+    assert_eq!(vault_balance_of(&vault, &alice, &bob).await?.0, 1200);
+
+    Ok(())
+}
+
+/// Test that only the owner manages the allowlist and that changes are logged per account
+#[tokio::test]
+async fn test_allowlist_updates() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+    let bob = worker.dev_create_account().await?;
+
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+    let vault = deploy_and_init_vault(&owner, &usdt, "token1", "USDT Vault", "vUSDT").await?;
+    let vault_owner = vault.as_account();
+
+    assert_vault_error(
+        vault_set_allowlist_enabled(&vault, &alice, true).await,
+        "NOT_OWNER",
+    );
+    assert_vault_error(
+        vault_add_to_allowlist(&vault, &alice, &[&alice]).await,
+        "NOT_OWNER",
+    );
+
+    vault_add_to_allowlist(&vault, vault_owner, &[&alice]).await?;
+
+    // Alice is already listed, so only bob is logged
+    let result = vault_owner
+        .call(vault.id(), "add_to_allowlist")
+        .args_json(json!({"account_ids": [alice.id(), bob.id()]}))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?
+        .into_result()?;
+    let events = vault_events(result.logs());
+    let update = assert_vault_event(
+        &events,
+        "vault_allowlist_update",
+        &["account_id", "allowed"],
+    );
+    assert_eq!(update, json!({"account_id": bob.id(), "allowed": true}));
+    assert_eq!(events[0]["data"].as_array().unwrap().len(), 1);
+
+    let result = vault_owner
+        .call(vault.id(), "remove_from_allowlist")
+        .args_json(json!({"account_ids": [alice.id()]}))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?
+        .into_result()?;
+    let update = assert_vault_event(
+        &vault_events(result.logs()),
+        "vault_allowlist_update",
+        &["account_id", "allowed"],
+    );
+    assert_eq!(update, json!({"account_id": alice.id(), "allowed": false}));
+
+    assert!(!vault_is_allowlisted(&vault, &owner, &alice).await?);
+    assert!(vault_is_allowlisted(&vault, &owner, &bob).await?);
+
+    Ok(())
+}
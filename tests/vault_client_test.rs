@@ -0,0 +1,67 @@
+use near_sdk::json_types::U128;
+use vault_client::{vault_events::VaultEvent, DepositOptions, VaultClient};
+
+use crate::helper::{
+    mock_mt::{deploy_and_init_mock_mt, mt_balance_of, mt_mint},
+    vault::deploy_and_init_vault,
+};
+
+mod helper;
+
+/// Test a deposit, views and a redemption through the typed client
+#[tokio::test]
+async fn test_client_deposit_and_redeem() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+    let bob = worker.dev_create_account().await?;
+
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+    let vault = deploy_and_init_vault(&owner, &usdt, "token1", "USDT Vault", "vUSDT").await?;
+    mt_mint(&usdt, &alice, "token1", 10000).await?;
+
+    let client = VaultClient::connect(alice.clone(), vault.id().clone()).await?;
+    assert_eq!(client.asset(), usdt.id());
+    assert_eq!(client.asset_token_id(), "token1");
+
+    client.storage_deposit(None).await?;
+    client.storage_deposit(Some(bob.id().clone())).await?;
+
+    let deposit = client
+        .deposit(
+            1000,
+            DepositOptions {
+                receiver_id: Some(bob.id().clone()),
+                min_shares: Some(U128(1000)),
+                ..Default::default()
+            },
+        )
+        .await?;
+    assert_eq!(deposit.value, 1000);
+    assert!(matches!(
+        deposit.events.as_slice(),
+        [VaultEvent::VaultDeposit(deposits)] if deposits[0].owner_id == bob.id().as_str()
+    ));
+
+    assert_eq!(client.total_assets().await?, 1000);
+    assert_eq!(client.balance_of(bob.id()).await?, 1000);
+    assert_eq!(client.convert_to_assets(500).await?, 500);
+    assert_eq!(client.vault_info().await?.total_supply.0, 1000);
+    assert_eq!(client.account_position(bob.id()).await?.shares.0, 1000);
+
+    // Bob redeems through his own client, sending the assets to alice
+    let bob_client = VaultClient::connect(bob.clone(), vault.id().clone()).await?;
+    let redeemed = bob_client
+        .redeem(400, Some(alice.id().clone()), None)
+        .await?;
+    assert_eq!(redeemed.value, 400);
+    assert!(matches!(
+        redeemed.events.as_slice(),
+        [VaultEvent::VaultWithdraw(_)]
+    ));
+
+    assert_eq!(mt_balance_of(&usdt, &alice, "token1").await?, 9400);
+    assert_eq!(client.balance_of(bob.id()).await?, 600);
+
+    Ok(())
+}
@@ -0,0 +1,159 @@
+use near_sdk::json_types::U128;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use vault_client::{vault_events::VaultEvent, DepositOptions, Executed, VaultClient};
+
+use crate::helper::{
+    mock_mt::{deploy_and_init_mock_mt, mt_mint, mt_transfer},
+    vault::{
+        deploy_and_init_vault, vault_set_profit_unlock_period, vault_set_surplus_policy, vault_sync,
+    },
+};
+
+mod helper;
+
+const STEPS: usize = 40;
+
+/// Assets and shares of the deposits logged by a transaction
+fn deposited(executed: &Executed<u128>) -> (u128, u128) {
+    executed
+        .events
+        .iter()
+        .flat_map(|event| match event {
+            VaultEvent::VaultDeposit(deposits) => deposits.as_slice(),
+            _ => &[],
+        })
+        .fold((0, 0), |(assets, shares), deposit| {
+            (assets + deposit.assets, shares + deposit.shares)
+        })
+}
+
+/// Shares burned by the withdrawals logged by a transaction
+fn burned(executed: &Executed<u128>) -> u128 {
+    executed
+        .events
+        .iter()
+        .flat_map(|event| match event {
+            VaultEvent::VaultWithdraw(withdrawals) => withdrawals.as_slice(),
+            _ => &[],
+        })
+        .map(|withdrawal| withdrawal.shares)
+        .sum()
+}
+
+/// Test that every preview matches what the operation then does, over random
+/// deposits, mints, redemptions, withdrawals and donations that move the price.
+/// Set `VAULT_PROPERTY_SEED` to replay a failing run.
+#[tokio::test]
+async fn test_previews_match_execution() -> Result<(), Box<dyn std::error::Error>> {
+    let seed = std::env::var("VAULT_PROPERTY_SEED")
+        .map(|seed| seed.parse().expect("Invalid VAULT_PROPERTY_SEED"))
+        .unwrap_or_else(|_| rand::random());
+    println!("VAULT_PROPERTY_SEED={}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+    let vault = deploy_and_init_vault(&owner, &usdt, "token1", "USDT Vault", "vUSDT").await?;
+
+    // Donations go straight into the share price. The vault account initialised
+    // itself, so it is the owner.
+    vault_set_surplus_policy(&vault, vault.as_account(), "donate").await?;
+    vault_set_profit_unlock_period(&vault, vault.as_account(), 0).await?;
+
+    mt_mint(&usdt, &alice, "token1", u64::MAX as u128).await?;
+    mt_mint(&usdt, &owner, "token1", u64::MAX as u128).await?;
+
+    let client = VaultClient::connect(alice.clone(), vault.id().clone()).await?;
+    client.storage_deposit(None).await?;
+    client.deposit(1_000_000, DepositOptions::default()).await?;
+
+    for step in 0..STEPS {
+        let max_redeem = client.max_redeem(alice.id()).await?;
+
+        match rng.gen_range(0..5) {
+            0 => {
+                let assets = rng.gen_range(1..=10_000_000);
+                let preview = client.preview_deposit(assets).await?;
+                if preview == 0 {
+                    continue;
+                }
+
+                let executed = client.deposit(assets, DepositOptions::default()).await?;
+                assert_eq!(executed.value, preview, "step {}: deposit {}", step, assets);
+            }
+            1 => {
+                let shares = rng.gen_range(1..=10_000_000);
+                let preview = client.preview_mint(shares).await?;
+
+                // A mint is a deposit of the previewed assets capped at `shares`
+                let executed = client
+                    .deposit(
+                        preview,
+                        DepositOptions {
+                            max_shares: Some(U128(shares)),
+                            ..Default::default()
+                        },
+                    )
+                    .await?;
+                assert_eq!(
+                    deposited(&executed),
+                    (preview, shares),
+                    "step {}: mint {}",
+                    step,
+                    shares
+                );
+            }
+            2 if max_redeem > 0 => {
+                let shares = rng.gen_range(1..=max_redeem);
+                let preview = client.preview_redeem(shares).await?;
+                if preview == 0 {
+                    continue;
+                }
+
+                let executed = client.redeem(shares, None, None).await?;
+                assert_eq!(executed.value, preview, "step {}: redeem {}", step, shares);
+            }
+            3 => {
+                let max_withdraw = client.max_withdraw(alice.id()).await?;
+                if max_withdraw == 0 {
+                    continue;
+                }
+
+                let assets = rng.gen_range(1..=max_withdraw);
+                let preview = client.preview_withdraw(assets).await?;
+                let executed = client.withdraw(assets, None, None).await?;
+                assert_eq!(executed.value, assets, "step {}: withdraw {}", step, assets);
+                assert_eq!(
+                    burned(&executed),
+                    preview,
+                    "step {}: withdraw {}",
+                    step,
+                    assets
+                );
+            }
+            _ => {
+                let donation = rng.gen_range(1..=5_000_000);
+                mt_transfer(&usdt, &owner, vault.id(), "token1", donation).await?;
+                vault_sync(&vault, &owner).await?;
+            }
+        }
+
+        // The previews of the vault-wide conversions agree with the plain conversions
+        let amount = rng.gen_range(1..=1_000_000);
+        assert_eq!(
+            client.preview_deposit(amount).await?,
+            client.convert_to_shares(amount).await?
+        );
+        assert_eq!(
+            client.preview_redeem(amount).await?,
+            client.convert_to_assets(amount).await?
+        );
+        assert!(client.preview_mint(amount).await? >= client.convert_to_assets(amount).await?);
+        assert!(client.preview_withdraw(amount).await? >= client.convert_to_shares(amount).await?);
+    }
+
+    Ok(())
+}
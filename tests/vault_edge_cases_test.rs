@@ -1,4 +1,5 @@
 use crate::helper::{
+    errors::assert_vault_error,
     mock_mt::{deploy_and_init_mock_mt, mt_balance_of, mt_mint},
     vault::{
         deploy_and_init_vault, mt_transfer_call_deposit, vault_balance_of, vault_convert_to_assets,
@@ -8,7 +9,6 @@ use crate::helper::{
 };
 use near_sdk::json_types::U128;
 use near_workspaces::types::NearToken;
-use serde_json;
 
 mod helper;
 
@@ -106,11 +106,10 @@ async fn test_large_amounts() -> Result<(), Box<dyn std::error::Error>> {
     let total_assets = vault_total_assets(&vault, &alice).await?;
     assert_eq!(total_assets.0, large_deposit);
 
-    // Test conversions with large numbers (accounting for inflation resistance)
+    // Test conversions with large numbers
     let shares_converted = vault_convert_to_shares(&vault, &alice, large_deposit / 2).await?;
-    // With large amounts and 1:1 ratio after inflation resistance adjustment, should be close
-    let expected = (large_deposit / 2) * large_deposit / (large_deposit + 1);
-    assert_eq!(shares_converted.0, expected);
+    // The virtual share and asset offset each other, so the 1:1 ratio is exact
+    assert_eq!(shares_converted.0, large_deposit / 2);
 
     Ok(())
 }
@@ -138,31 +137,11 @@ async fn test_insufficient_balance_withdrawal() -> Result<(), Box<dyn std::error
 
     // Try to withdraw more than available
     let result = vault_withdraw(&vault, &alice, 2000, None, None).await;
-    match result {
-        Err(err) => {
-            let error_message = format!("{:?}", err);
-            assert!(
-                error_message.contains("Exceeds max withdraw"),
-                "Expected 'Exceeds max withdraw' error, got: {}",
-                error_message
-            );
-        }
-        Ok(_) => panic!("Expected withdrawal to fail when exceeding max_withdraw"),
-    }
+    assert_vault_error(result, "EXCEEDS_MAX_WITHDRAW");
 
     // Try to redeem more shares than owned
     let result = vault_redeem(&vault, &alice, 2000, None, None).await;
-    match result {
-        Err(err) => {
-            let error_message = format!("{:?}", err);
-            assert!(
-                error_message.contains("Exceeds max redeem"),
-                "Expected 'Exceeds max redeem' error, got: {}",
-                error_message
-            );
-        }
-        Ok(_) => panic!("Expected redeem to fail when exceeding max_redeem"),
-    }
+    assert_vault_error(result, "EXCEEDS_MAX_REDEEM");
 
     Ok(())
 }
@@ -360,9 +339,8 @@ async fn test_deposit_withdraw_round_trip() -> Result<(), Box<dyn std::error::Er
     let withdrawal_result = vault_redeem(&vault, &alice, shares_received, None, None).await?;
 
     // Calculate exact expected withdrawal for round-trip
-    // With inflation resistance, some precision loss is expected but should be minimal
-    // For 999 shares from second 1000 deposit: (999 * 2000) / 1999 = 999 assets
-    let expected_withdrawal = shares_received; // Should be close to 1:1 for this scenario
+    // For 1000 shares from second 1000 deposit: (1000 * 2001) / 2001 = 1000 assets
+    let expected_withdrawal = shares_received; // 1:1 for this scenario
     assert_eq!(
         withdrawal_result.0, expected_withdrawal,
         "Round-trip withdrawal should return exact calculated amount: {} shares -> {} assets",
@@ -371,7 +349,7 @@ async fn test_deposit_withdraw_round_trip() -> Result<(), Box<dyn std::error::Er
 
     // Check final balance - should be restored to exact pre-round-trip level
     let final_balance = mt_balance_of(&usdt, &alice, "token1").await?;
-    let expected_balance = pre_round_trip_balance - 1; // 1 token precision loss expected
+    let expected_balance = pre_round_trip_balance; // No precision loss at a 1:1 rate
 
     assert_eq!(
         final_balance, expected_balance,
@@ -0,0 +1,327 @@
+use near_sdk::NearToken;
+use serde_json::json;
+
+use crate::helper::{
+    events::{assert_vault_event, vault_events},
+    mock_flash_borrower::deploy_and_init_mock_flash_borrower,
+    mock_mt::{deploy_and_init_mock_mt, mt_mint, mt_transfer},
+    vault::{deploy_and_init_vault, vault_set_flash_loan_config, vault_storage_deposit},
+};
+
+mod helper;
+
+/// Test the events of deposits, withdrawals, rolled back withdrawals and delegation
+#[tokio::test]
+async fn test_share_events() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+    let bob = worker.dev_create_account().await?;
+
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+    let vault = deploy_and_init_vault(&owner, &usdt, "token1", "USDT Vault", "vUSDT").await?;
+
+    vault_storage_deposit(&vault, &alice).await?;
+    mt_mint(&usdt, &alice, "token1", 10000).await?;
+
+    let result = alice
+        .call(usdt.id(), "mt_transfer_call")
+        .args_json(json!({
+            "receiver_id": vault.id(),
+            "token_id": "token1",
+            "amount": "1000",
+            "msg": json!({"memo": "hello"}).to_string(),
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(near_workspaces::types::Gas::from_tgas(300))
+        .transact()
+        .await?
+        .into_result()?;
+    let deposit = assert_vault_event(
+        &vault_events(result.logs()),
+        "vault_deposit",
+        &[
+            "sender_id",
+            "owner_id",
+            "token_id",
+            "assets",
+            "shares",
+            "memo",
+        ],
+    );
+    assert_eq!(deposit["assets"], "1000");
+    assert_eq!(deposit["memo"], "hello");
+
+    let result = alice
+        .call(vault.id(), "redeem")
+        .args_json(json!({"shares": "100"}))
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(near_workspaces::types::Gas::from_tgas(300))
+        .transact()
+        .await?
+        .into_result()?;
+    let withdraw = assert_vault_event(
+        &vault_events(result.logs()),
+        "vault_withdraw",
+        &["owner_id", "receiver_id", "token_id", "shares", "assets"],
+    );
+    assert_eq!(withdraw["shares"], "100");
+
+    let result = alice
+        .call(vault.id(), "redeem")
+        .args_json(json!({
+            "shares": "100",
+            "receiver_id": "nonexistent.testnet",
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(near_workspaces::types::Gas::from_tgas(300))
+        .transact()
+        .await?
+        .into_result()?;
+    let failed = assert_vault_event(
+        &vault_events(result.logs()),
+        "vault_withdraw_failed",
+        &[
+            "owner_id",
+            "receiver_id",
+            "token_id",
+            "shares",
+            "assets",
+            "reason",
+            "code",
+        ],
+    );
+    assert_eq!(failed["receiver_id"], "nonexistent.testnet");
+    assert_eq!(failed["code"], "ASSET_TRANSFER_FAILED");
+
+    // A deposit below its minimum shares is refunded with the code of the refusal
+    let result = alice
+        .call(usdt.id(), "mt_transfer_call")
+        .args_json(json!({
+            "receiver_id": vault.id(),
+            "token_id": "token1",
+            "amount": "1000",
+            "msg": json!({"min_shares": "1000000", "memo": "too greedy"}).to_string(),
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(near_workspaces::types::Gas::from_tgas(300))
+        .transact()
+        .await?
+        .into_result()?;
+    let refund = assert_vault_event(
+        &vault_events(result.logs()),
+        "vault_deposit_refund",
+        &[
+            "sender_id",
+            "owner_id",
+            "token_id",
+            "amount",
+            "code",
+            "reason",
+            "memo",
+        ],
+    );
+    assert_eq!(refund["amount"], "1000");
+    assert_eq!(refund["code"], "SLIPPAGE_EXCEEDED");
+
+    let result = alice
+        .call(vault.id(), "delegate")
+        .args_json(json!({"delegatee": bob.id()}))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?
+        .into_result()?;
+    let delegate = assert_vault_event(
+        &vault_events(result.logs()),
+        "vault_delegate",
+        &["delegator_id", "from_delegatee_id", "to_delegatee_id"],
+    );
+    assert_eq!(delegate["from_delegatee_id"], alice.id().as_str());
+    assert_eq!(delegate["to_delegatee_id"], bob.id().as_str());
+
+    Ok(())
+}
+
+/// Test the events of owner actions: configuration, sync, rescue and ownership transfer
+#[tokio::test]
+async fn test_admin_events() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+    let vault = deploy_and_init_vault(&owner, &usdt, "token1", "USDT Vault", "vUSDT").await?;
+    // The vault account initialised itself, so it is the owner
+    let vault_owner = vault.as_account();
+
+    let result = vault_owner
+        .call(vault.id(), "set_surplus_policy")
+        .args_json(json!({"surplus_policy": "donate"}))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?
+        .into_result()?;
+    let config = assert_vault_event(
+        &vault_events(result.logs()),
+        "vault_config_change",
+        &["parameter", "old_value", "new_value"],
+    );
+    assert_eq!(config["parameter"], "surplus_policy");
+    assert_eq!(config["old_value"], "sweep");
+    assert_eq!(config["new_value"], "donate");
+
+    mt_mint(&usdt, &alice, "token1", 100).await?;
+    mt_transfer(&usdt, &alice, vault.id(), "token1", 100).await?;
+    let result = alice
+        .call(vault.id(), "sync")
+        .gas(near_workspaces::types::Gas::from_tgas(100))
+        .transact()
+        .await?
+        .into_result()?;
+    let sync = assert_vault_event(
+        &vault_events(result.logs()),
+        "vault_sync",
+        &[
+            "token_id",
+            "balance",
+            "previous_total_assets",
+            "total_assets",
+            "profit",
+            "loss",
+            "sweepable_surplus",
+        ],
+    );
+    assert_eq!(sync["profit"], "100");
+
+    mt_mint(&usdt, vault_owner, "token2", 50).await?;
+    let result = vault_owner
+        .call(vault.id(), "rescue_mt")
+        .args_json(json!({
+            "token": usdt.id(),
+            "token_id": "token2",
+            "amount": "50",
+            "receiver_id": alice.id(),
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(near_workspaces::types::Gas::from_tgas(100))
+        .transact()
+        .await?
+        .into_result()?;
+    let rescue = assert_vault_event(
+        &vault_events(result.logs()),
+        "vault_rescue",
+        &["token", "token_id", "amount", "receiver_id"],
+    );
+    assert_eq!(rescue["token_id"], "token2");
+
+    let result = vault_owner
+        .call(vault.id(), "set_owner")
+        .args_json(json!({"new_owner": alice.id()}))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?
+        .into_result()?;
+    let owner_change = assert_vault_event(
+        &vault_events(result.logs()),
+        "vault_owner_change",
+        &["previous_owner_id", "new_owner_id"],
+    );
+    assert_eq!(owner_change["new_owner_id"], alice.id().as_str());
+
+    let new_owner: String = alice.view(vault.id(), "owner").await?.json()?;
+    assert_eq!(new_owner, alice.id().as_str());
+
+    Ok(())
+}
+
+/// Test the events of a flash loan that pays a fee
+#[tokio::test]
+async fn test_flash_loan_events() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+    let borrower_account = worker.dev_create_account().await?;
+
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+    let vault = deploy_and_init_vault(&owner, &usdt, "token1", "USDT Vault", "vUSDT").await?;
+    let borrower = deploy_and_init_mock_flash_borrower(&borrower_account).await?;
+
+    vault_storage_deposit(&vault, &alice).await?;
+    mt_mint(&usdt, &alice, "token1", 10000).await?;
+    alice
+        .call(usdt.id(), "mt_transfer_call")
+        .args_json(json!({
+            "receiver_id": vault.id(),
+            "token_id": "token1",
+            "amount": "1000",
+            "msg": "{}",
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(near_workspaces::types::Gas::from_tgas(300))
+        .transact()
+        .await?
+        .into_result()?;
+
+    vault_set_flash_loan_config(&vault, vault.as_account(), true, 100).await?;
+    vault_storage_deposit(&vault, borrower.as_account()).await?;
+    mt_mint(&usdt, borrower.as_account(), "token1", 1020).await?;
+    let result = borrower
+        .as_account()
+        .call(usdt.id(), "mt_transfer_call")
+        .args_json(json!({
+            "receiver_id": vault.id(),
+            "token_id": "token1",
+            "amount": "1010",
+            "msg": json!({"flash_loan_bond": true}).to_string(),
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(near_workspaces::types::Gas::from_tgas(300))
+        .transact()
+        .await?
+        .into_result()?;
+    let bond = assert_vault_event(
+        &vault_events(result.logs()),
+        "vault_flash_loan_bond",
+        &["account_id", "token_id", "posted", "withdrawn", "bond"],
+    );
+    assert_eq!(bond["posted"], "1010");
+    assert_eq!(bond["bond"], "1010");
+
+    let result = borrower
+        .call("borrow")
+        .args_json(json!({
+            "vault_id": vault.id(),
+            "amount": "1000",
+            "msg": "repay",
+        }))
+        .gas(near_workspaces::types::Gas::from_tgas(300))
+        .transact()
+        .await?
+        .into_result()?;
+    let events = vault_events(result.logs());
+
+    let flash_loan = assert_vault_event(
+        &events,
+        "vault_flash_loan",
+        &[
+            "borrower_id",
+            "token_id",
+            "amount",
+            "fee",
+            "repaid",
+            "bond_seized",
+        ],
+    );
+    assert_eq!(flash_loan["repaid"], "1010");
+    assert_eq!(flash_loan["bond_seized"], "0");
+
+    let fee = assert_vault_event(
+        &events,
+        "vault_fee_accrued",
+        &["token_id", "source", "amount"],
+    );
+    assert_eq!(fee["source"], "flash_loan");
+    assert_eq!(fee["amount"], "10");
+
+    Ok(())
+}
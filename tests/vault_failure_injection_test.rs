@@ -0,0 +1,175 @@
+use near_sdk::json_types::U128;
+use serde_json::json;
+
+use crate::helper::{
+    events::{assert_vault_event, vault_events},
+    mock_mt::{deploy_and_init_mock_mt, mt_balance_of, mt_mint, mt_set_failure_mode},
+    vault::{
+        deploy_and_init_vault, mt_transfer_call_deposit, vault_balance_of, vault_redeem,
+        vault_storage_deposit, vault_total_assets,
+    },
+};
+
+mod helper;
+
+/// Test that payouts failing in the asset contract are rolled back for every failure mode
+#[tokio::test]
+async fn test_failed_payouts_roll_back() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+    let bob = worker.dev_create_account().await?;
+
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+    let vault = deploy_and_init_vault(&owner, &usdt, "token1", "USDT Vault", "vUSDT").await?;
+
+    vault_storage_deposit(&vault, &alice).await?;
+    mt_mint(&usdt, &alice, "token1", 10000).await?;
+    mt_transfer_call_deposit(
+        &usdt, &vault, &alice, "token1", 1000, None, None, None, None,
+    )
+    .await?;
+
+    // Every transfer fails
+    mt_set_failure_mode(&usdt, json!({"mode": "all_transfers"})).await?;
+    assert_eq!(vault_redeem(&vault, &alice, 100, None, None).await?.0, 0);
+    assert_eq!(vault_balance_of(&vault, &alice, &alice).await?.0, 1000);
+    assert_eq!(vault_total_assets(&vault, &alice).await?.0, 1000);
+
+    // Only transfers to bob fail
+    mt_set_failure_mode(
+        &usdt,
+        json!({"mode": "transfers_to", "account_id": bob.id()}),
+    )
+    .await?;
+    assert_eq!(
+        vault_redeem(&vault, &alice, 100, Some(&bob), None).await?.0,
+        0
+    );
+    assert_eq!(vault_redeem(&vault, &alice, 100, None, None).await?.0, 100);
+    assert_eq!(vault_balance_of(&vault, &alice, &alice).await?.0, 900);
+
+    // The first transfer succeeds, the second one fails
+    mt_set_failure_mode(&usdt, json!({"mode": "transfers_after", "calls": 1})).await?;
+    assert_eq!(vault_redeem(&vault, &alice, 100, None, None).await?.0, 100);
+    assert_eq!(vault_redeem(&vault, &alice, 100, None, None).await?.0, 0);
+    assert_eq!(vault_balance_of(&vault, &alice, &alice).await?.0, 800);
+
+    mt_set_failure_mode(&usdt, json!({"mode": "none"})).await?;
+    assert_eq!(vault_redeem(&vault, &alice, 100, None, None).await?.0, 100);
+
+    assert_eq!(vault_balance_of(&vault, &alice, &alice).await?.0, 700);
+    assert_eq!(vault_total_assets(&vault, &alice).await?.0, 700);
+    assert_eq!(mt_balance_of(&usdt, &alice, "token1").await?, 9300);
+
+    Ok(())
+}
+
+/// Test that a payout running out of gas leaves enough gas to roll it back
+#[tokio::test]
+async fn test_out_of_gas_payout_rolls_back() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+    let vault = deploy_and_init_vault(&owner, &usdt, "token1", "USDT Vault", "vUSDT").await?;
+
+    vault_storage_deposit(&vault, &alice).await?;
+    mt_mint(&usdt, &alice, "token1", 10000).await?;
+    mt_transfer_call_deposit(
+        &usdt, &vault, &alice, "token1", 1000, None, None, None, None,
+    )
+    .await?;
+
+    mt_set_failure_mode(&usdt, json!({"mode": "burn_gas"})).await?;
+
+    let result = alice
+        .call(vault.id(), "redeem")
+        .args_json(json!({"shares": "100"}))
+        .deposit(near_sdk::NearToken::from_yoctonear(1))
+        .gas(near_workspaces::types::Gas::from_tgas(300))
+        .transact()
+        .await?
+        .into_result()?;
+    assert_eq!(result.json::<U128>()?.0, 0);
+    let failed = assert_vault_event(
+        &vault_events(result.logs()),
+        "vault_withdraw_failed",
+        &[
+            "owner_id",
+            "receiver_id",
+            "token_id",
+            "shares",
+            "assets",
+            "reason",
+            "code",
+        ],
+    );
+    assert_eq!(failed["code"], "ASSET_TRANSFER_FAILED");
+
+    assert_eq!(vault_balance_of(&vault, &alice, &alice).await?.0, 1000);
+    assert_eq!(vault_total_assets(&vault, &alice).await?.0, 1000);
+
+    Ok(())
+}
+
+/// Test that a deposit still settles when the asset contract returns garbage from its resolve
+#[tokio::test]
+async fn test_malformed_resolve() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+    let vault = deploy_and_init_vault(&owner, &usdt, "token1", "USDT Vault", "vUSDT").await?;
+
+    vault_storage_deposit(&vault, &alice).await?;
+    mt_mint(&usdt, &alice, "token1", 10000).await?;
+
+    mt_set_failure_mode(&usdt, json!({"mode": "malformed_resolve"})).await?;
+
+    let result = alice
+        .call(usdt.id(), "mt_transfer_call")
+        .args_json(json!({
+            "receiver_id": vault.id(),
+            "token_id": "token1",
+            "amount": "1000",
+            "msg": "",
+        }))
+        .deposit(near_sdk::NearToken::from_yoctonear(1))
+        .gas(near_workspaces::types::Gas::from_tgas(300))
+        .transact()
+        .await?
+        .into_result()?;
+    assert_eq!(result.raw_bytes()?, b"not json");
+    assert!(result.json::<Vec<U128>>().is_err());
+
+    assert_eq!(vault_balance_of(&vault, &alice, &alice).await?.0, 1000);
+    assert_eq!(vault_total_assets(&vault, &alice).await?.0, 1000);
+    assert_eq!(mt_balance_of(&usdt, &alice, "token1").await?, 9000);
+
+    Ok(())
+}
+
+/// Test that only the owner of the mock can inject failures
+#[tokio::test]
+async fn test_failure_mode_requires_owner() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+
+    let result = alice
+        .call(usdt.id(), "set_failure_mode")
+        .args_json(json!({"mode": {"mode": "all_transfers"}}))
+        .transact()
+        .await?;
+    assert!(result.is_failure());
+
+    let mode: serde_json::Value = alice.view(usdt.id(), "failure_mode").await?.json()?;
+    assert_eq!(mode, json!({"mode": "none"}));
+
+    Ok(())
+}
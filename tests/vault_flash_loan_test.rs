@@ -0,0 +1,245 @@
+use near_workspaces::{network::Sandbox, Account, Contract, Worker};
+
+use crate::helper::{
+    errors::assert_vault_error,
+    mock_flash_borrower::{
+        deploy_and_init_mock_flash_borrower, flash_borrower_borrow, flash_borrower_loans,
+    },
+    mock_mt::{deploy_and_init_mock_mt, mt_balance_of, mt_mint},
+    vault::{
+        deploy_and_init_vault, mt_transfer_call_deposit, vault_flash_loan, vault_flash_loan_bond,
+        vault_is_flash_loan_open, vault_post_flash_loan_bond, vault_set_flash_loan_config,
+        vault_storage_deposit, vault_total_assets, vault_withdraw_flash_loan_bond,
+    },
+};
+
+mod helper;
+
+/// Vault holding 1000 of alice's assets with a 1% flash loan fee, and a registered
+/// borrower that has posted `bond` and holds `spare` more of the asset
+async fn setup(
+    worker: &Worker<Sandbox>,
+    bond: u128,
+    spare: u128,
+) -> Result<(Contract, Contract, Contract, Account), Box<dyn std::error::Error>> {
+    let owner = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+    let borrower_account = worker.dev_create_account().await?;
+
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+    let vault = deploy_and_init_vault(&owner, &usdt, "token1", "USDT Vault", "vUSDT").await?;
+    let borrower = deploy_and_init_mock_flash_borrower(&borrower_account).await?;
+
+    vault_storage_deposit(&vault, &alice).await?;
+    mt_mint(&usdt, &alice, "token1", 10000).await?;
+    mt_transfer_call_deposit(
+        &usdt, &vault, &alice, "token1", 1000, None, None, None, None,
+    )
+    .await?;
+
+    // The vault account initialised itself, so it is the owner. 1% fee
+    vault_set_flash_loan_config(&vault, vault.as_account(), true, 100).await?;
+
+    vault_storage_deposit(&vault, borrower.as_account()).await?;
+    mt_mint(&usdt, borrower.as_account(), "token1", bond + spare).await?;
+    if bond > 0 {
+        vault_post_flash_loan_bond(&usdt, &vault, borrower.as_account(), "token1", bond).await?;
+    }
+
+    Ok((usdt, vault, borrower, alice))
+}
+
+/// Test that a repaid flash loan adds its fee to total_assets and leaves the bond alone
+#[tokio::test]
+async fn test_flash_loan_repaid_with_fee() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    // Borrower holds just enough besides its bond to pay the fee
+    let (usdt, vault, borrower, alice) = setup(&worker, 1010, 10).await?;
+
+    let repaid = flash_borrower_borrow(&borrower, &vault, 1000, "repay").await?;
+    assert_eq!(repaid.0, 1010, "Principal plus fee should be repaid");
+
+    assert_eq!(flash_borrower_loans(&borrower, &alice).await?, 1);
+    assert_eq!(
+        mt_balance_of(&usdt, borrower.as_account(), "token1").await?,
+        0
+    );
+    assert_eq!(
+        mt_balance_of(&usdt, vault.as_account(), "token1").await?,
+        2020
+    );
+    assert_eq!(
+        vault_total_assets(&vault, &alice).await?.0,
+        1010,
+        "Fee should be added to total assets"
+    );
+    assert_eq!(
+        vault_flash_loan_bond(&vault, &alice, borrower.id())
+            .await?
+            .0,
+        1010
+    );
+    assert!(!vault_is_flash_loan_open(&vault, &alice).await?);
+
+    Ok(())
+}
+
+/// Test that a loan repaid without its fee still credits the fee, out of the bond
+#[tokio::test]
+async fn test_flash_loan_repaid_without_fee() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let (usdt, vault, borrower, alice) = setup(&worker, 1010, 0).await?;
+
+    let repaid = flash_borrower_borrow(&borrower, &vault, 1000, "repay_principal").await?;
+    assert_eq!(repaid.0, 1000);
+
+    assert_eq!(vault_total_assets(&vault, &alice).await?.0, 1010);
+    assert_eq!(
+        vault_flash_loan_bond(&vault, &alice, borrower.id())
+            .await?
+            .0,
+        1000,
+        "The fee should be taken from the bond"
+    );
+    assert_eq!(
+        mt_balance_of(&usdt, vault.as_account(), "token1").await?,
+        2010
+    );
+    assert!(!vault_is_flash_loan_open(&vault, &alice).await?);
+
+    Ok(())
+}
+
+/// Test that a partly repaid loan is made whole from the bond
+#[tokio::test]
+async fn test_flash_loan_partially_repaid() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let (usdt, vault, borrower, alice) = setup(&worker, 1010, 0).await?;
+
+    let repaid = flash_borrower_borrow(&borrower, &vault, 1000, "repay_partial").await?;
+    assert_eq!(repaid.0, 500);
+
+    assert_eq!(
+        vault_total_assets(&vault, &alice).await?.0,
+        1010,
+        "Shareholders should not bear the shortfall"
+    );
+    assert_eq!(
+        vault_flash_loan_bond(&vault, &alice, borrower.id())
+            .await?
+            .0,
+        500
+    );
+    assert_eq!(
+        mt_balance_of(&usdt, vault.as_account(), "token1").await?,
+        1510
+    );
+
+    Ok(())
+}
+
+/// Test that an unpaid loan is made whole from the bond
+#[tokio::test]
+async fn test_flash_loan_not_repaid() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let (usdt, vault, borrower, alice) = setup(&worker, 1010, 0).await?;
+
+    let repaid = flash_borrower_borrow(&borrower, &vault, 1000, "keep").await?;
+    assert_eq!(repaid.0, 0);
+
+    assert_eq!(
+        vault_total_assets(&vault, &alice).await?.0,
+        1010,
+        "Shareholders should not bear the shortfall"
+    );
+    assert_eq!(
+        vault_flash_loan_bond(&vault, &alice, borrower.id())
+            .await?
+            .0,
+        0
+    );
+    assert_eq!(
+        mt_balance_of(&usdt, vault.as_account(), "token1").await?,
+        1010
+    );
+    assert!(!vault_is_flash_loan_open(&vault, &alice).await?);
+
+    Ok(())
+}
+
+/// Test that a loan is rejected unless the bond covers principal plus fee
+#[tokio::test]
+async fn test_flash_loan_requires_bond() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let (_usdt, vault, borrower, alice) = setup(&worker, 1009, 0).await?;
+
+    let result = flash_borrower_borrow(&borrower, &vault, 1000, "repay").await;
+    assert_vault_error(result, "INSUFFICIENT_FLASH_LOAN_BOND");
+    assert_eq!(flash_borrower_loans(&borrower, &alice).await?, 0);
+
+    Ok(())
+}
+
+/// Test that a stranger cannot take a loan against the borrower's bond
+#[tokio::test]
+async fn test_flash_loan_against_someone_elses_bond() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let (_usdt, vault, borrower, alice) = setup(&worker, 1010, 0).await?;
+
+    let result = vault_flash_loan(&vault, &alice, borrower.id(), 1000, "keep").await;
+    assert_vault_error(result, "NOT_FLASH_LOAN_RECEIVER");
+
+    assert_eq!(flash_borrower_loans(&borrower, &alice).await?, 0);
+    assert_eq!(
+        vault_flash_loan_bond(&vault, &alice, borrower.id())
+            .await?
+            .0,
+        1010
+    );
+    assert_eq!(vault_total_assets(&vault, &alice).await?.0, 1000);
+
+    Ok(())
+}
+
+/// Test that a borrower can take its bond back
+#[tokio::test]
+async fn test_flash_loan_bond_withdrawal() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let (usdt, vault, borrower, alice) = setup(&worker, 1010, 0).await?;
+
+    let result = vault_withdraw_flash_loan_bond(&vault, borrower.as_account(), 1011).await;
+    assert_vault_error(result, "INSUFFICIENT_FLASH_LOAN_BOND");
+
+    let withdrawn = vault_withdraw_flash_loan_bond(&vault, borrower.as_account(), 1010).await?;
+    assert_eq!(withdrawn.0, 1010);
+
+    assert_eq!(
+        mt_balance_of(&usdt, borrower.as_account(), "token1").await?,
+        1010
+    );
+    assert_eq!(
+        vault_flash_loan_bond(&vault, &alice, borrower.id())
+            .await?
+            .0,
+        0
+    );
+    assert_eq!(vault_total_assets(&vault, &alice).await?.0, 1000);
+
+    Ok(())
+}
+
+/// Test that flash loans are rejected until the owner enables them
+#[tokio::test]
+async fn test_flash_loan_disabled_by_default() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+    let vault = deploy_and_init_vault(&owner, &usdt, "token1", "USDT Vault", "vUSDT").await?;
+
+    let result = vault_flash_loan(&vault, &alice, alice.id(), 1, "repay").await;
+    assert_vault_error(result, "FLASH_LOANS_DISABLED");
+
+    Ok(())
+}
@@ -0,0 +1,180 @@
+use near_sdk::{json_types::U128, NearToken};
+use serde_json::json;
+
+use crate::helper::{
+    errors::assert_vault_error,
+    events::{assert_vault_event, vault_events},
+    mock_mt::{deploy_and_init_mock_mt, mt_balance_of, mt_mint},
+    vault::{
+        deploy_and_init_vault, mt_transfer_call_deposit, vault_balance_of, vault_convert_to_assets,
+        vault_freeze, vault_ft_transfer, vault_is_frozen, vault_max_deposit, vault_max_redeem,
+        vault_max_withdraw, vault_redeem, vault_set_guardian, vault_storage_deposit,
+        vault_total_supply, vault_unfreeze, vault_withdraw,
+    },
+};
+
+mod helper;
+
+/// Test that a frozen account can neither move, redeem nor receive shares
+#[tokio::test]
+async fn test_frozen_account() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+    let bob = worker.dev_create_account().await?;
+
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+    let vault = deploy_and_init_vault(&owner, &usdt, "token1", "USDT Vault", "vUSDT").await?;
+    // The vault account initialised itself, so it is the owner
+    let vault_owner = vault.as_account();
+
+    vault_storage_deposit(&vault, &alice).await?;
+    vault_storage_deposit(&vault, &bob).await?;
+    mt_mint(&usdt, &alice, "token1", 10000).await?;
+    mt_mint(&usdt, &bob, "token1", 10000).await?;
+    mt_transfer_call_deposit(
+        &usdt, &vault, &alice, "token1", 1000, None, None, None, None,
+    )
+    .await?;
+    mt_transfer_call_deposit(&usdt, &vault, &bob, "token1", 1000, None, None, None, None).await?;
+
+    let price_before = vault_convert_to_assets(&vault, &bob, 1000).await?;
+
+    vault_freeze(&vault, vault_owner, &alice).await?;
+    assert!(vault_is_frozen(&vault, &bob, &alice).await?);
+    assert_eq!(
+        vault_convert_to_assets(&vault, &bob, 1000).await?,
+        price_before
+    );
+
+    assert_eq!(vault_max_redeem(&vault, &bob, &alice).await?.0, 0);
+    assert_eq!(vault_max_withdraw(&vault, &bob, &alice).await?.0, 0);
+    assert_eq!(vault_max_deposit(&vault, &bob, &alice).await?.0, 0);
+
+    assert_vault_error(
+        vault_ft_transfer(&vault, &alice, &bob, 100).await,
+        "ACCOUNT_FROZEN",
+    );
+    assert_vault_error(
+        vault_ft_transfer(&vault, &bob, &alice, 100).await,
+        "ACCOUNT_FROZEN",
+    );
+    assert_vault_error(
+        vault_redeem(&vault, &alice, 100, None, None).await,
+        "ACCOUNT_FROZEN",
+    );
+    assert_vault_error(
+        vault_withdraw(&vault, &alice, 100, None, None).await,
+        "ACCOUNT_FROZEN",
+    );
+    assert_vault_error(
+        vault_redeem(&vault, &bob, 100, Some(&alice), None).await,
+        "ACCOUNT_FROZEN",
+    );
+
+    // Deposits from or to a frozen account are refunded
+    mt_transfer_call_deposit(
+        &usdt, &vault, &alice, "token1", 1000, None, None, None, None,
+    )
+    .await?;
+    mt_transfer_call_deposit(
+        &usdt,
+        &vault,
+        &bob,
+        "token1",
+        1000,
+        Some(&alice),
+        None,
+        None,
+        None,
+    )
+    .await?;
+    assert_eq!(mt_balance_of(&usdt, &alice, "token1").await?, 9000);
+    assert_eq!(mt_balance_of(&usdt, &bob, "token1").await?, 9000);
+    assert_eq!(vault_balance_of(&vault, &bob, &alice).await?.0, 1000);
+
+    vault_unfreeze(&vault, vault_owner, &alice).await?;
+    vault_redeem(&vault, &alice, 500, None, None).await?;
+    assert_eq!(mt_balance_of(&usdt, &alice, "token1").await?, 9500);
+
+    Ok(())
+}
+
+/// Test that only the guardian seizes frozen shares, without changing the supply
+#[tokio::test]
+async fn test_seize() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let guardian = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+    let recovery = worker.dev_create_account().await?;
+
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+    let vault = deploy_and_init_vault(&owner, &usdt, "token1", "USDT Vault", "vUSDT").await?;
+    let vault_owner = vault.as_account();
+
+    vault_storage_deposit(&vault, &alice).await?;
+    vault_storage_deposit(&vault, &recovery).await?;
+    mt_mint(&usdt, &alice, "token1", 10000).await?;
+    mt_transfer_call_deposit(
+        &usdt, &vault, &alice, "token1", 1000, None, None, None, None,
+    )
+    .await?;
+
+    assert_vault_error(
+        vault_set_guardian(&vault, &alice, Some(&alice)).await,
+        "NOT_OWNER",
+    );
+    vault_set_guardian(&vault, vault_owner, Some(&guardian)).await?;
+    assert_vault_error(
+        vault_freeze(&vault, &alice, &alice).await,
+        "NOT_OWNER_OR_GUARDIAN",
+    );
+
+    let seize = |caller: &near_workspaces::Account| {
+        caller
+            .call(vault.id(), "seize")
+            .args_json(json!({
+                "account_id": alice.id(),
+                "recovery_id": recovery.id(),
+                "memo": "Court order",
+            }))
+            .deposit(NearToken::from_yoctonear(1))
+            .transact()
+    };
+
+    // Alice must be frozen first
+    assert_vault_error(seize(&guardian).await?.into_result(), "NOT_FROZEN");
+
+    let result = guardian
+        .call(vault.id(), "freeze")
+        .args_json(json!({"account_id": alice.id()}))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?
+        .into_result()?;
+    let freeze = assert_vault_event(
+        &vault_events(result.logs()),
+        "vault_freeze",
+        &["account_id", "frozen", "actor_id"],
+    );
+    assert_eq!(freeze["actor_id"], guardian.id().as_str());
+
+    // The owner can freeze but not seize
+    assert_vault_error(seize(vault_owner).await?.into_result(), "NOT_GUARDIAN");
+
+    let result = seize(&guardian).await?.into_result()?;
+    assert_eq!(result.json::<U128>()?.0, 1000);
+    let seizure = assert_vault_event(
+        &vault_events(result.logs()),
+        "vault_seize",
+        &["account_id", "recovery_id", "guardian_id", "shares", "memo"],
+    );
+    assert_eq!(seizure["shares"], "1000");
+
+    assert_eq!(vault_balance_of(&vault, &owner, &alice).await?.0, 0);
+    assert_eq!(vault_balance_of(&vault, &owner, &recovery).await?.0, 1000);
+    assert_eq!(vault_total_supply(&vault, &owner).await?.0, 1000);
+
+    Ok(())
+}
@@ -0,0 +1,445 @@
+//! Seeded fuzzing of the vault against its compiled WASM and the mock MT in a sandbox.
+//!
+//! Random operations by several accounts run in batches, and the on-chain invariants
+//! are checked after every batch. Unlike the native property tests this exercises gas
+//! limits, promise ordering and the release profile's `overflow-checks`.
+//!
+//! Set `VAULT_FUZZ_SEED` to replay a failing run, and `VAULT_FUZZ_BATCHES` and
+//! `VAULT_FUZZ_BATCH_SIZE` to make runs longer.
+
+use near_sdk::{json_types::U128, NearToken};
+use near_workspaces::{
+    result::{ExecutionFinalResult, ExecutionSuccess},
+    types::Gas,
+    Account, Contract,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde_json::json;
+
+use crate::helper::{
+    mock_mt::{
+        deploy_and_init_mock_mt, mt_balance_of, mt_credit_yield, mt_mint, mt_set_failure_mode,
+        mt_slash,
+    },
+    vault::{
+        deploy_and_init_vault, vault_balance_of, vault_convert_to_assets,
+        vault_get_account_position, vault_get_vault_info, vault_max_withdraw,
+        vault_set_profit_unlock_period, vault_set_surplus_policy, vault_storage_deposit,
+        vault_total_supply,
+    },
+};
+
+mod helper;
+
+const USERS: usize = 3;
+const INITIAL_BALANCE: u128 = 1_000_000_000_000;
+
+/// Panic messages of calls the contracts rejected on purpose. Any other failure,
+/// such as exceeded gas or an arithmetic overflow, is a finding.
+const EXPECTED_FAILURES: [&str; 2] = ["VAULT_ERROR[", "Injected failure"];
+
+fn env_or<T: std::str::FromStr>(name: &str, default: T) -> T {
+    std::env::var(name)
+        .map(|value| value.parse().unwrap_or_else(|_| panic!("Invalid {}", name)))
+        .unwrap_or(default)
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Op {
+    Deposit {
+        user: usize,
+        assets: u128,
+    },
+    /// Redeems `percent` of the user's shares, failing the payout if `payout_fails`
+    Redeem {
+        user: usize,
+        percent: u128,
+        payout_fails: bool,
+    },
+    /// Withdraws `percent` of the user's max withdraw, failing the payout if `payout_fails`
+    Withdraw {
+        user: usize,
+        percent: u128,
+        payout_fails: bool,
+    },
+    /// Transfers `percent` of the sender's shares
+    Transfer {
+        from: usize,
+        to: usize,
+        percent: u128,
+    },
+    /// Sends assets to the vault without `mt_transfer_call`, then syncs
+    Donate {
+        user: usize,
+        assets: u128,
+    },
+    /// Credits yield to the vault's asset balance, then syncs
+    Yield {
+        assets: u128,
+    },
+    /// Slashes `percent` of the vault's asset balance, then syncs
+    Slash {
+        percent: u128,
+    },
+}
+
+/// Amount spread over several orders of magnitude, so that both dust and large
+/// amounts come up
+fn amount(rng: &mut StdRng) -> u128 {
+    rng.gen_range(1..10) * 10u128.pow(rng.gen_range(0..=9))
+}
+
+fn random_op(rng: &mut StdRng) -> Op {
+    let user = rng.gen_range(0..USERS);
+    let percent = rng.gen_range(1..=100);
+
+    match rng.gen_range(0..10) {
+        0..=2 => Op::Deposit {
+            user,
+            assets: amount(rng),
+        },
+        3 | 4 => Op::Redeem {
+            user,
+            percent,
+            payout_fails: rng.gen_bool(0.2),
+        },
+        5 => Op::Withdraw {
+            user,
+            percent,
+            payout_fails: rng.gen_bool(0.2),
+        },
+        6 => Op::Transfer {
+            from: user,
+            to: rng.gen_range(0..USERS),
+            percent,
+        },
+        7 => Op::Donate {
+            user,
+            assets: amount(rng),
+        },
+        8 => Op::Yield {
+            assets: amount(rng),
+        },
+        _ => Op::Slash {
+            percent: rng.gen_range(1..=10),
+        },
+    }
+}
+
+struct Fuzzer {
+    vault: Contract,
+    usdt: Contract,
+    users: Vec<Account>,
+    /// Asset balance every user must hold given the outcomes of its operations
+    expected_balances: Vec<u128>,
+}
+
+impl Fuzzer {
+    async fn apply(&mut self, op: Op) -> Result<(), Box<dyn std::error::Error>> {
+        match op {
+            Op::Deposit { user, assets } => {
+                let assets = assets.min(self.expected_balances[user]);
+                if assets == 0 {
+                    return Ok(());
+                }
+
+                let result = self.users[user]
+                    .call(self.usdt.id(), "mt_transfer_call")
+                    .args_json(json!({
+                        "receiver_id": self.vault.id(),
+                        "token_id": "token1",
+                        "amount": assets.to_string(),
+                        "msg": "",
+                    }))
+                    .deposit(NearToken::from_yoctonear(1))
+                    .gas(Gas::from_tgas(300))
+                    .transact()
+                    .await?;
+                if let Some(success) = check_outcome(result)? {
+                    let used: Vec<U128> = success.json()?;
+                    self.expected_balances[user] -= used[0].0;
+                }
+            }
+            Op::Redeem {
+                user,
+                percent,
+                payout_fails,
+            } => {
+                let account = &self.users[user];
+                let shares =
+                    vault_balance_of(&self.vault, account, account).await?.0 * percent / 100;
+                if shares == 0 {
+                    return Ok(());
+                }
+
+                let args = json!({"shares": shares.to_string()});
+                self.payout(user, "redeem", args, payout_fails).await?;
+            }
+            Op::Withdraw {
+                user,
+                percent,
+                payout_fails,
+            } => {
+                let account = &self.users[user];
+                let assets =
+                    vault_max_withdraw(&self.vault, account, account).await?.0 * percent / 100;
+                if assets == 0 {
+                    return Ok(());
+                }
+
+                let args = json!({"assets": assets.to_string()});
+                self.payout(user, "withdraw", args, payout_fails).await?;
+            }
+            Op::Transfer { from, to, percent } => {
+                let sender = &self.users[from];
+                let shares = vault_balance_of(&self.vault, sender, sender).await?.0 * percent / 100;
+                if from == to || shares == 0 {
+                    return Ok(());
+                }
+
+                let result = sender
+                    .call(self.vault.id(), "ft_transfer")
+                    .args_json(json!({
+                        "receiver_id": self.users[to].id(),
+                        "amount": shares.to_string(),
+                    }))
+                    .deposit(NearToken::from_yoctonear(1))
+                    .transact()
+                    .await?;
+                check_outcome(result)?;
+            }
+            Op::Donate { user, assets } => {
+                let assets = assets.min(self.expected_balances[user]);
+                if assets == 0 {
+                    return Ok(());
+                }
+
+                let result = self.users[user]
+                    .call(self.usdt.id(), "mt_transfer")
+                    .args_json(json!({
+                        "receiver_id": self.vault.id(),
+                        "token_id": "token1",
+                        "amount": assets.to_string(),
+                    }))
+                    .deposit(NearToken::from_yoctonear(1))
+                    .transact()
+                    .await?;
+                if check_outcome(result)?.is_some() {
+                    self.expected_balances[user] -= assets;
+                }
+                self.sync().await?;
+            }
+            Op::Yield { assets } => {
+                mt_credit_yield(&self.usdt, self.vault.id(), "token1", assets).await?;
+                self.sync().await?;
+            }
+            Op::Slash { percent } => {
+                let held = mt_balance_of(&self.usdt, self.vault.as_account(), "token1").await?;
+                let slashed = held * percent / 100;
+                if slashed > 0 {
+                    mt_slash(&self.usdt, self.vault.id(), "token1", slashed).await?;
+                }
+                self.sync().await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Calls `method` of the vault for `user` and books the assets it paid out.
+    /// With `payout_fails` the asset transfer to the user fails, which must pay nothing.
+    async fn payout(
+        &mut self,
+        user: usize,
+        method: &str,
+        args: serde_json::Value,
+        payout_fails: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let account = &self.users[user];
+        if payout_fails {
+            let mode = json!({"mode": "transfers_to", "account_id": account.id()});
+            mt_set_failure_mode(&self.usdt, mode).await?;
+        }
+
+        let result = account
+            .call(self.vault.id(), method)
+            .args_json(args)
+            .deposit(NearToken::from_yoctonear(1))
+            .gas(Gas::from_tgas(300))
+            .transact()
+            .await?;
+        let paid = check_outcome(result)?
+            .map(|success| success.json::<U128>())
+            .transpose()?;
+
+        if payout_fails {
+            mt_set_failure_mode(&self.usdt, json!({"mode": "none"})).await?;
+        }
+
+        if let Some(paid) = paid {
+            if payout_fails && paid.0 != 0 {
+                return Err(
+                    format!("{} paid {} although its transfer failed", method, paid.0).into(),
+                );
+            }
+            self.expected_balances[user] += paid.0;
+        }
+
+        Ok(())
+    }
+
+    async fn sync(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let result = self.users[0]
+            .call(self.vault.id(), "sync")
+            .gas(Gas::from_tgas(100))
+            .transact()
+            .await?;
+        check_outcome(result)?;
+        Ok(())
+    }
+
+    async fn check_invariants(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let viewer = &self.users[0];
+        let supply = vault_total_supply(&self.vault, viewer).await?.0;
+
+        let mut shares = 0;
+        for (user, account) in self.users.iter().enumerate() {
+            shares += vault_balance_of(&self.vault, viewer, account).await?.0;
+
+            let balance = mt_balance_of(&self.usdt, account, "token1").await?;
+            if balance != self.expected_balances[user] {
+                return Err(format!(
+                    "User {} holds {} assets, the call outcomes add up to {}",
+                    user, balance, self.expected_balances[user]
+                )
+                .into());
+            }
+
+            let position = vault_get_account_position(&self.vault, viewer, account).await?;
+            if position["pending_withdrawals"]["requests"] != 0 {
+                return Err(format!("User {} has unresolved withdrawals", user).into());
+            }
+        }
+        if shares != supply {
+            return Err(format!(
+                "Balances add up to {} shares, the supply is {}",
+                shares, supply
+            )
+            .into());
+        }
+
+        let held = mt_balance_of(&self.usdt, self.vault.as_account(), "token1").await?;
+        let info = vault_get_vault_info(&self.vault, viewer).await?;
+        let total_assets: U128 = serde_json::from_value(info["total_assets"].clone())?;
+        let locked_profit: U128 = serde_json::from_value(info["locked_profit"].clone())?;
+        if total_assets.0 + locked_profit.0 > held {
+            return Err(format!(
+                "Total assets {} and locked profit {} exceed the {} assets held",
+                total_assets.0, locked_profit.0, held
+            )
+            .into());
+        }
+
+        let redeemable = vault_convert_to_assets(&self.vault, viewer, supply)
+            .await?
+            .0;
+        if redeemable > held {
+            return Err(format!(
+                "All shares redeem for {}, the vault holds {}",
+                redeemable, held
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns a successful call, or `None` if a contract rejected it on purpose.
+/// Unexpected failures anywhere in the receipt tree are errors.
+fn check_outcome(
+    result: ExecutionFinalResult,
+) -> Result<Option<ExecutionSuccess>, Box<dyn std::error::Error>> {
+    for failure in result.receipt_failures() {
+        let failure = format!("{:?}", failure);
+        if !EXPECTED_FAILURES
+            .iter()
+            .any(|expected| failure.contains(expected))
+        {
+            return Err(format!("Unexpected failure: {}", failure).into());
+        }
+    }
+
+    match result.into_result() {
+        Ok(success) => Ok(Some(success)),
+        Err(failure) => {
+            let failure = format!("{:?}", failure);
+            if EXPECTED_FAILURES
+                .iter()
+                .any(|expected| failure.contains(expected))
+            {
+                Ok(None)
+            } else {
+                Err(format!("Unexpected failure: {}", failure).into())
+            }
+        }
+    }
+}
+
+/// Test that random operations by several accounts keep the vault's on-chain
+/// invariants, printing the seed to replay the run if they do not
+#[tokio::test]
+async fn test_fuzz_vault_operations() -> Result<(), Box<dyn std::error::Error>> {
+    let seed = env_or("VAULT_FUZZ_SEED", rand::random());
+    let batches = env_or("VAULT_FUZZ_BATCHES", 4);
+    let batch_size = env_or("VAULT_FUZZ_BATCH_SIZE", 6);
+    println!("VAULT_FUZZ_SEED={}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+    let vault = deploy_and_init_vault(&owner, &usdt, "token1", "USDT Vault", "vUSDT").await?;
+
+    // Synced yield goes straight into the share price, so every sync moves it
+    vault_set_surplus_policy(&vault, vault.as_account(), "donate").await?;
+    vault_set_profit_unlock_period(&vault, vault.as_account(), 0).await?;
+
+    let mut users = Vec::with_capacity(USERS);
+    for _ in 0..USERS {
+        let user = worker.dev_create_account().await?;
+        vault_storage_deposit(&vault, &user).await?;
+        mt_mint(&usdt, &user, "token1", INITIAL_BALANCE).await?;
+        users.push(user);
+    }
+
+    let mut fuzzer = Fuzzer {
+        vault,
+        usdt,
+        users,
+        expected_balances: vec![INITIAL_BALANCE; USERS],
+    };
+
+    let mut history = Vec::new();
+    for batch in 0..batches {
+        for _ in 0..batch_size {
+            let op = random_op(&mut rng);
+            history.push(op);
+            if let Err(error) = fuzzer.apply(op).await {
+                panic!(
+                    "{:?} failed: {}\nOperations: {:#?}\nReplay with VAULT_FUZZ_SEED={}",
+                    op, error, history, seed
+                );
+            }
+        }
+
+        if let Err(error) = fuzzer.check_invariants().await {
+            panic!(
+                "Invariant broken after batch {}: {}\nOperations: {:#?}\nReplay with VAULT_FUZZ_SEED={}",
+                batch, error, history, seed
+            );
+        }
+    }
+
+    Ok(())
+}
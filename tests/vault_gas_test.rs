@@ -0,0 +1,282 @@
+//! Gas benchmarks of the vault entry points against the compiled WASM.
+//!
+//! Every scenario runs on a small state, a fresh vault, and on a large one with many
+//! holders and long share and price histories. The gas burnt is compared to
+//! `tests/gas_baseline.json`, and the test fails if any figure grew by more than the
+//! baseline's `threshold_percent`, or if a receipt was measured without a baseline
+//! figure or the other way around. After an intended change, run with
+//! `VAULT_GAS_UPDATE_BASELINE=1` to record the new figures and commit the file.
+
+use std::collections::BTreeMap;
+
+use near_sdk::NearToken;
+use near_workspaces::{result::ExecutionFinalResult, types::Gas, Account, Contract, Worker};
+use serde_json::{json, Value};
+
+use crate::helper::{
+    mock_mt::{deploy_and_init_mock_mt, mt_mint, mt_set_failure_mode},
+    vault::{
+        deploy_and_init_vault, mt_transfer_call_deposit, vault_set_price_history_length,
+        vault_storage_deposit,
+    },
+};
+
+mod helper;
+
+const BASELINE_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/gas_baseline.json");
+
+/// Holders besides the benchmarked account in the large state
+const LARGE_STATE_HOLDERS: usize = 20;
+/// Deposits the benchmarked account makes beforehand in the large state, each
+/// adding a share checkpoint and a price checkpoint
+const LARGE_STATE_HISTORY: usize = 30;
+
+/// Gas burnt by each receipt the vault executed in `result`, in execution order.
+/// Refund receipts burn no gas and are left out.
+fn vault_gas(result: &ExecutionFinalResult, vault: &Contract) -> Vec<u64> {
+    result
+        .receipt_outcomes()
+        .iter()
+        .filter(|outcome| &outcome.executor_id == vault.id())
+        .map(|outcome| outcome.gas_burnt.as_gas())
+        .filter(|&gas| gas > 0)
+        .collect()
+}
+
+/// Records the gas of the vault receipts in `result` under `names`, in order
+fn record(
+    measured: &mut BTreeMap<String, u64>,
+    state: &str,
+    names: &[&str],
+    result: &ExecutionFinalResult,
+    vault: &Contract,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let gas = vault_gas(result, vault);
+    if gas.len() != names.len() {
+        return Err(format!(
+            "Expected vault receipts {:?}, got {} of them",
+            names,
+            gas.len()
+        )
+        .into());
+    }
+
+    for (name, gas) in names.iter().zip(gas) {
+        measured.insert(format!("{}/{}", state, name), gas);
+    }
+    Ok(())
+}
+
+async fn call_vault(
+    account: &Account,
+    vault: &Contract,
+    method: &str,
+    args: Value,
+) -> Result<ExecutionFinalResult, Box<dyn std::error::Error>> {
+    let result = account
+        .call(vault.id(), method)
+        .args_json(args)
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(Gas::from_tgas(300))
+        .transact()
+        .await?;
+    if result.is_failure() {
+        return Err(format!("{} failed: {:?}", method, result.into_result().unwrap_err()).into());
+    }
+    Ok(result)
+}
+
+/// Runs every benchmarked entry point once on a fresh vault, after building up a
+/// large state first if `large`
+async fn measure_state(
+    worker: &Worker<near_workspaces::network::Sandbox>,
+    large: bool,
+    measured: &mut BTreeMap<String, u64>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let state = if large { "large" } else { "small" };
+
+    let owner = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+    let bob = worker.dev_create_account().await?;
+
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+    let vault = deploy_and_init_vault(&owner, &usdt, "token1", "USDT Vault", "vUSDT").await?;
+
+    for account in [&alice, &bob] {
+        vault_storage_deposit(&vault, account).await?;
+        mt_mint(&usdt, account, "token1", 1_000_000_000).await?;
+    }
+
+    if large {
+        // The vault account initialised itself, so it is the owner
+        vault_set_price_history_length(&vault, vault.as_account(), 1_000).await?;
+
+        for _ in 0..LARGE_STATE_HOLDERS {
+            let holder = worker.dev_create_account().await?;
+            vault_storage_deposit(&vault, &holder).await?;
+            mt_mint(&usdt, &holder, "token1", 1_000_000).await?;
+            mt_transfer_call_deposit(
+                &usdt, &vault, &holder, "token1", 1_000_000, None, None, None, None,
+            )
+            .await?;
+        }
+        for _ in 0..LARGE_STATE_HISTORY {
+            mt_transfer_call_deposit(
+                &usdt, &vault, &alice, "token1", 1_000, None, None, None, None,
+            )
+            .await?;
+        }
+    }
+
+    // Deposits are chained through the asset contract, so the whole chain is recorded too
+    let result = alice
+        .call(usdt.id(), "mt_transfer_call")
+        .args_json(json!({
+            "receiver_id": vault.id(),
+            "token_id": "token1",
+            "amount": "1000000",
+            "msg": "",
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(Gas::from_tgas(300))
+        .transact()
+        .await?;
+    if result.is_failure() {
+        return Err(format!("Deposit failed: {:?}", result.into_result().unwrap_err()).into());
+    }
+    record(measured, state, &["mt_on_transfer"], &result, &vault)?;
+    measured.insert(
+        format!("{}/mt_transfer_call_total", state),
+        result.total_gas_burnt.as_gas(),
+    );
+
+    let args = json!({"receiver_id": bob.id(), "amount": "1000"});
+    let result = call_vault(&alice, &vault, "ft_transfer", args).await?;
+    record(measured, state, &["ft_transfer"], &result, &vault)?;
+
+    let result = call_vault(&alice, &vault, "redeem", json!({"shares": "1000"})).await?;
+    record(
+        measured,
+        state,
+        &["redeem", "redeem.resolve_withdraw"],
+        &result,
+        &vault,
+    )?;
+
+    let result = call_vault(&alice, &vault, "withdraw", json!({"assets": "1000"})).await?;
+    record(
+        measured,
+        state,
+        &["withdraw", "withdraw.resolve_withdraw"],
+        &result,
+        &vault,
+    )?;
+
+    // Rolling a withdrawal back re-mints the shares, the costliest way to resolve
+    mt_set_failure_mode(&usdt, json!({"mode": "all_transfers"})).await?;
+    let result = call_vault(&alice, &vault, "redeem", json!({"shares": "1000"})).await?;
+    record(
+        measured,
+        state,
+        &["redeem_failed", "redeem_failed.resolve_withdraw"],
+        &result,
+        &vault,
+    )?;
+
+    Ok(())
+}
+
+/// Compares `measured` against the baseline and returns the regressions beyond its
+/// threshold, along with the receipts missing from either side
+fn compare(baseline: &Value, measured: &BTreeMap<String, u64>) -> Vec<String> {
+    let threshold = baseline["threshold_percent"].as_u64().unwrap_or(5);
+    let mut regressions = Vec::new();
+
+    println!(
+        "{:<44} {:>18} {:>18} {:>9}",
+        "receipt", "baseline", "measured", "change"
+    );
+    for (name, &gas) in measured {
+        let Some(expected) = baseline["gas"][name].as_u64() else {
+            println!("{:<44} {:>18} {:>18} {:>9}", name, "-", gas, "new");
+            regressions.push(format!("{} burnt {} gas but has no baseline", name, gas));
+            continue;
+        };
+
+        let change = (gas as f64 - expected as f64) / expected as f64 * 100.0;
+        println!("{:<44} {:>18} {:>18} {:>8.2}%", name, expected, gas, change);
+        if gas as u128 * 100 > expected as u128 * (100 + threshold as u128) {
+            regressions.push(format!(
+                "{} burnt {} gas, {:.2}% more than the baseline {}",
+                name, gas, change, expected
+            ));
+        }
+    }
+
+    for name in baseline["gas"]
+        .as_object()
+        .into_iter()
+        .flat_map(|gas| gas.keys())
+    {
+        if !measured.contains_key(name) {
+            println!("{:<44} is in the baseline but was not measured", name);
+            regressions.push(format!("{} is in the baseline but was not measured", name));
+        }
+    }
+
+    regressions
+}
+
+/// Test that no vault entry point burns more gas than its baseline allows, on a
+/// small and a large state
+#[tokio::test]
+async fn test_gas_regressions() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+
+    let mut measured = BTreeMap::new();
+    measure_state(&worker, false, &mut measured).await?;
+    measure_state(&worker, true, &mut measured).await?;
+
+    let baseline: Value = serde_json::from_str(&std::fs::read_to_string(BASELINE_PATH)?)?;
+
+    if std::env::var("VAULT_GAS_UPDATE_BASELINE").is_ok() {
+        let updated = json!({
+            "threshold_percent": baseline["threshold_percent"],
+            "gas": measured,
+        });
+        std::fs::write(
+            BASELINE_PATH,
+            serde_json::to_string_pretty(&updated)? + "\n",
+        )?;
+        println!("Recorded the gas baseline in {}", BASELINE_PATH);
+        return Ok(());
+    }
+
+    let regressions = compare(&baseline, &measured);
+    assert!(
+        regressions.is_empty(),
+        "Gas differs from the baseline, rerun with VAULT_GAS_UPDATE_BASELINE=1 if intended:\n{}",
+        regressions.join("\n")
+    );
+
+    Ok(())
+}
+
+/// Test that receipts missing from the baseline or from the measurements fail the
+/// comparison, so an empty or outdated baseline cannot pass
+#[test]
+fn test_compare_baseline_mismatch() {
+    let measured = BTreeMap::from([("deposit".to_string(), 1_000)]);
+
+    let baseline = json!({"threshold_percent": 5, "gas": {}});
+    assert_eq!(compare(&baseline, &measured).len(), 1);
+
+    let baseline = json!({"threshold_percent": 5, "gas": {"deposit": 1_000, "withdraw": 1_000}});
+    assert_eq!(compare(&baseline, &measured).len(), 1);
+
+    let baseline = json!({"threshold_percent": 5, "gas": {"deposit": 960}});
+    assert!(compare(&baseline, &measured).is_empty());
+
+    let baseline = json!({"threshold_percent": 5, "gas": {"deposit": 940}});
+    assert_eq!(compare(&baseline, &measured).len(), 1);
+}
@@ -0,0 +1,126 @@
+use near_sdk::json_types::U128;
+
+use crate::helper::{
+    errors::assert_vault_error,
+    mock_mt::{deploy_and_init_mock_mt, mt_mint, mt_transfer},
+    vault::{
+        deploy_and_init_vault, mt_transfer_call_deposit, vault_price_history_length,
+        vault_price_per_share_at, vault_set_price_history_length, vault_set_profit_unlock_period,
+        vault_set_surplus_policy, vault_storage_deposit, vault_sync, vault_twap_price_per_share,
+    },
+};
+
+mod helper;
+
+// One whole share has 24 decimals in the test vault
+const ONE_SHARE: u128 = 1_000_000_000_000_000_000_000_000;
+
+/// Test that every deposit and sync records a checkpoint readable by timestamp
+#[tokio::test]
+async fn test_price_per_share_at_follows_history() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+    let vault = deploy_and_init_vault(&owner, &usdt, "token1", "USDT Vault", "vUSDT").await?;
+
+    vault_storage_deposit(&vault, &alice).await?;
+    mt_mint(&usdt, &alice, "token1", 10000).await?;
+
+    vault_set_surplus_policy(&vault, vault.as_account(), "donate").await?;
+    vault_set_profit_unlock_period(&vault, vault.as_account(), 0).await?;
+
+    let before_deposit = worker.view_block().await?.timestamp();
+    assert_eq!(
+        vault_price_per_share_at(&vault, &alice, before_deposit).await?,
+        None,
+        "Nothing is recorded before the first deposit"
+    );
+
+    mt_transfer_call_deposit(
+        &usdt, &vault, &alice, "token1", 1000, None, None, None, None,
+    )
+    .await?;
+    let after_deposit = worker.view_block().await?.timestamp();
+
+    // 1000 shares backed by 1000 assets: ONE_SHARE * 1001 / 1001
+    let deposit_price = ONE_SHARE;
+    assert_eq!(
+        vault_price_per_share_at(&vault, &alice, after_deposit).await?,
+        Some(U128(deposit_price))
+    );
+
+    mt_transfer(&usdt, &alice, vault.id(), "token1", 1000).await?;
+    vault_sync(&vault, &alice).await?;
+    let after_sync = worker.view_block().await?.timestamp();
+
+    // Donated yield doubles the price, while the earlier checkpoint is unchanged
+    assert_eq!(
+        vault_price_per_share_at(&vault, &alice, after_sync).await?,
+        Some(U128(ONE_SHARE * 2001 / 1001))
+    );
+    assert_eq!(
+        vault_price_per_share_at(&vault, &alice, after_deposit).await?,
+        Some(U128(deposit_price))
+    );
+
+    Ok(())
+}
+
+/// Test that the TWAP averages over the window and rejects windows the history does not cover
+#[tokio::test]
+async fn test_twap_price_per_share() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+    let vault = deploy_and_init_vault(&owner, &usdt, "token1", "USDT Vault", "vUSDT").await?;
+
+    vault_storage_deposit(&vault, &alice).await?;
+    mt_mint(&usdt, &alice, "token1", 10000).await?;
+
+    mt_transfer_call_deposit(
+        &usdt, &vault, &alice, "token1", 1000, None, None, None, None,
+    )
+    .await?;
+    let after_deposit = worker.view_block().await?.timestamp();
+
+    worker.fast_forward(5).await?;
+
+    // A window inside the history sees only the deposit checkpoint
+    let now = worker.view_block().await?.timestamp();
+    let twap = vault_twap_price_per_share(&vault, &alice, now - after_deposit).await?;
+    assert_eq!(twap.0, ONE_SHARE);
+
+    // A window reaching back before the first checkpoint cannot be priced
+    let result = vault_twap_price_per_share(&vault, &alice, now).await;
+    assert_vault_error(result, "PRICE_HISTORY_TOO_SHORT");
+
+    Ok(())
+}
+
+/// Test that only the owner can bound the history, and only within the allowed range
+#[tokio::test]
+async fn test_set_price_history_length() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+    let vault = deploy_and_init_vault(&owner, &usdt, "token1", "USDT Vault", "vUSDT").await?;
+
+    assert_eq!(vault_price_history_length(&vault, &alice).await?, 720);
+
+    let result = vault_set_price_history_length(&vault, &alice, 10).await;
+    assert_vault_error(result, "NOT_OWNER");
+
+    let result = vault_set_price_history_length(&vault, vault.as_account(), 0).await;
+    assert_vault_error(result, "INVALID_PRICE_HISTORY_LENGTH");
+
+    vault_set_price_history_length(&vault, vault.as_account(), 10).await?;
+    assert_eq!(vault_price_history_length(&vault, &alice).await?, 10);
+
+    Ok(())
+}
@@ -0,0 +1,131 @@
+use vault_client::{vault_events::VaultEvent, DepositOptions, VaultClient};
+
+use crate::helper::{
+    errors::assert_vault_error,
+    mock_mt::{deploy_and_init_mock_mt, mt_balance_of, mt_mint},
+    vault::{
+        deploy_and_init_vault, mt_transfer_call_deposit_referred, vault_balance_of,
+        vault_referral_stats, vault_set_referral_fee_bps, vault_storage_deposit,
+        vault_total_supply,
+    },
+};
+
+mod helper;
+
+/// Test that referred deposits are tracked and earn the referrer its fee shares
+#[tokio::test]
+async fn test_referral_fee_and_stats() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+    let partner = worker.dev_create_account().await?;
+
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+    let vault = deploy_and_init_vault(&owner, &usdt, "token1", "USDT Vault", "vUSDT").await?;
+
+    vault_storage_deposit(&vault, &alice).await?;
+    vault_storage_deposit(&vault, &partner).await?;
+    mt_mint(&usdt, &alice, "token1", 100000).await?;
+
+    // Without a fee the deposit is only attributed
+    mt_transfer_call_deposit_referred(&usdt, &vault, &alice, "token1", 10000, &partner).await?;
+    assert_eq!(vault_balance_of(&vault, &alice, &alice).await?.0, 10000);
+    assert_eq!(vault_balance_of(&vault, &alice, &partner).await?.0, 0);
+
+    // A 1% fee pays the partner 1% of the deposited shares, out of the depositor's
+    vault_set_referral_fee_bps(&vault, vault.as_account(), 100).await?;
+    mt_transfer_call_deposit_referred(&usdt, &vault, &alice, "token1", 20000, &partner).await?;
+    assert_eq!(vault_balance_of(&vault, &alice, &alice).await?.0, 29800);
+    assert_eq!(vault_balance_of(&vault, &alice, &partner).await?.0, 200);
+    assert_eq!(
+        vault_total_supply(&vault, &alice).await?.0,
+        30000,
+        "The fee must not dilute other holders"
+    );
+
+    let stats = vault_referral_stats(&vault, &alice, &partner).await?;
+    assert_eq!(stats["deposits"], 2);
+    assert_eq!(stats["assets"], "30000");
+    assert_eq!(stats["shares"], "30000");
+    assert_eq!(stats["fee_shares"], "200");
+
+    let stats = vault_referral_stats(&vault, &alice, &alice).await?;
+    assert_eq!(stats["deposits"], 0);
+
+    // The referrer is carried in the deposit event
+    let client = VaultClient::connect(alice.clone(), vault.id().clone()).await?;
+    let deposit = client
+        .deposit(
+            1000,
+            DepositOptions {
+                referrer: Some(partner.id().clone()),
+                ..Default::default()
+            },
+        )
+        .await?;
+    assert!(matches!(
+        deposit.events.as_slice(),
+        [VaultEvent::VaultDeposit(deposits)]
+            if deposits[0].referrer.as_deref() == Some(partner.id().as_str())
+    ));
+
+    Ok(())
+}
+
+/// Test that deposits referred by unregistered accounts or the depositor are refunded
+#[tokio::test]
+async fn test_invalid_referrers() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+    let stranger = worker.dev_create_account().await?;
+
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+    let vault = deploy_and_init_vault(&owner, &usdt, "token1", "USDT Vault", "vUSDT").await?;
+
+    vault_storage_deposit(&vault, &alice).await?;
+    mt_mint(&usdt, &alice, "token1", 10000).await?;
+
+    // Both deposits are refunded in full rather than failing
+    for referrer in [&stranger, &alice] {
+        let used =
+            mt_transfer_call_deposit_referred(&usdt, &vault, &alice, "token1", 1000, referrer)
+                .await?;
+        assert_eq!(used.0, 0);
+    }
+
+    assert_eq!(vault_balance_of(&vault, &alice, &alice).await?.0, 0);
+    assert_eq!(mt_balance_of(&usdt, &alice, "token1").await?, 10000);
+    assert_eq!(
+        vault_referral_stats(&vault, &alice, &stranger).await?["deposits"],
+        0
+    );
+
+    Ok(())
+}
+
+/// Test that only the owner sets the referral fee, and at most 10%
+#[tokio::test]
+async fn test_referral_fee_config() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+    let vault = deploy_and_init_vault(&owner, &usdt, "token1", "USDT Vault", "vUSDT").await?;
+
+    assert_vault_error(
+        vault_set_referral_fee_bps(&vault, &alice, 100).await,
+        "NOT_OWNER",
+    );
+    assert_vault_error(
+        vault_set_referral_fee_bps(&vault, vault.as_account(), 1001).await,
+        "FEE_TOO_HIGH",
+    );
+
+    vault_set_referral_fee_bps(&vault, vault.as_account(), 1000).await?;
+    let fee_bps: u16 = alice.view(vault.id(), "referral_fee_bps").await?.json()?;
+    assert_eq!(fee_bps, 1000);
+
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use crate::helper::{
+    errors::assert_vault_error,
+    mock_mt::{deploy_and_init_mock_mt, mt_balance_of, mt_mint, mt_transfer},
+    vault::{
+        deploy_and_init_vault, mt_transfer_call_deposit, vault_rescue_mt, vault_storage_deposit,
+        vault_sweepable_surplus, vault_sync, vault_total_assets,
+    },
+};
+
+mod helper;
+
+/// Test that the owner can rescue a foreign token ID sent to the vault
+#[tokio::test]
+async fn test_rescue_foreign_mt_token() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+    let bob = worker.dev_create_account().await?;
+
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+    let vault = deploy_and_init_vault(&owner, &usdt, "token1", "USDT Vault", "vUSDT").await?;
+
+    mt_mint(&usdt, &alice, "token2", 1000).await?;
+    mt_transfer(&usdt, &alice, vault.id(), "token2", 1000).await?;
+
+    // The vault account initialised itself, so it is the owner
+    let rescued = vault_rescue_mt(
+        &vault,
+        vault.as_account(),
+        usdt.id(),
+        "token2",
+        1000,
+        bob.id(),
+    )
+    .await?;
+    assert_eq!(rescued.0, 1000);
+
+    let bob_balance = mt_balance_of(&usdt, &bob, "token2").await?;
+    assert_eq!(
+        bob_balance, 1000,
+        "Rescued tokens should reach the receiver"
+    );
+
+    Ok(())
+}
+
+/// Test that only the surplus of the vault asset can be rescued
+#[tokio::test]
+async fn test_rescue_vault_asset_limited_to_surplus() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+    let bob = worker.dev_create_account().await?;
+
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+    let vault = deploy_and_init_vault(&owner, &usdt, "token1", "USDT Vault", "vUSDT").await?;
+
+    vault_storage_deposit(&vault, &alice).await?;
+    mt_mint(&usdt, &alice, "token1", 10000).await?;
+
+    mt_transfer_call_deposit(
+        &usdt, &vault, &alice, "token1", 1000, None, None, None, None,
+    )
+    .await?;
+
+    // Deposited assets back shares and cannot be rescued
+    let result =
+        vault_rescue_mt(&vault, vault.as_account(), usdt.id(), "token1", 1, bob.id()).await;
+    assert_vault_error(result, "EXCEEDS_SWEEPABLE_SURPLUS");
+
+    mt_transfer(&usdt, &alice, vault.id(), "token1", 300).await?;
+    vault_sync(&vault, &alice).await?;
+
+    let rescued = vault_rescue_mt(
+        &vault,
+        vault.as_account(),
+        usdt.id(),
+        "token1",
+        300,
+        bob.id(),
+    )
+    .await?;
+    assert_eq!(rescued.0, 300);
+    assert_eq!(mt_balance_of(&usdt, &bob, "token1").await?, 300);
+    assert_eq!(vault_sweepable_surplus(&vault, &alice).await?.0, 0);
+    assert_eq!(vault_total_assets(&vault, &alice).await?.0, 1000);
+
+    Ok(())
+}
+
+/// Test that only the owner can rescue tokens
+#[tokio::test]
+async fn test_rescue_requires_owner() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+    let vault = deploy_and_init_vault(&owner, &usdt, "token1", "USDT Vault", "vUSDT").await?;
+
+    mt_mint(&usdt, &alice, "token2", 1000).await?;
+    mt_transfer(&usdt, &alice, vault.id(), "token2", 1000).await?;
+
+    let result = vault_rescue_mt(&vault, &alice, usdt.id(), "token2", 1000, alice.id()).await;
+    assert_vault_error(result, "NOT_OWNER");
+
+    Ok(())
+}
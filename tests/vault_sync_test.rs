@@ -0,0 +1,120 @@
+use crate::helper::{
+    mock_mt::{deploy_and_init_mock_mt, mt_mint, mt_transfer},
+    vault::{
+        deploy_and_init_vault, mt_transfer_call_deposit, vault_convert_to_assets,
+        vault_locked_profit, vault_set_profit_unlock_period, vault_set_surplus_policy,
+        vault_storage_deposit, vault_sweepable_surplus, vault_sync, vault_total_assets,
+    },
+};
+
+mod helper;
+
+/// Test that a plain mt_transfer to the vault is left sweepable under the default policy
+#[tokio::test]
+async fn test_sync_sweep_policy_keeps_total_assets() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+    let vault = deploy_and_init_vault(&owner, &usdt, "token1", "USDT Vault", "vUSDT").await?;
+
+    vault_storage_deposit(&vault, &alice).await?;
+    mt_mint(&usdt, &alice, "token1", 10000).await?;
+
+    mt_transfer_call_deposit(
+        &usdt, &vault, &alice, "token1", 1000, None, None, None, None,
+    )
+    .await?;
+
+    // Assets sent without mt_transfer_call are invisible to the vault until synced
+    mt_transfer(&usdt, &alice, vault.id(), "token1", 500).await?;
+    assert_eq!(vault_total_assets(&vault, &alice).await?.0, 1000);
+
+    let total_assets = vault_sync(&vault, &alice).await?;
+    assert_eq!(
+        total_assets.0, 1000,
+        "Sweep policy must not move total_assets"
+    );
+
+    let surplus = vault_sweepable_surplus(&vault, &alice).await?;
+    assert_eq!(surplus.0, 500, "Surplus should be set aside for sweeping");
+
+    Ok(())
+}
+
+/// Test that a surplus is added to total_assets under the donate policy
+#[tokio::test]
+async fn test_sync_donate_policy_adds_yield() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+    let vault = deploy_and_init_vault(&owner, &usdt, "token1", "USDT Vault", "vUSDT").await?;
+
+    vault_storage_deposit(&vault, &alice).await?;
+    mt_mint(&usdt, &alice, "token1", 10000).await?;
+
+    // The vault account initialised itself, so it is the owner
+    vault_set_surplus_policy(&vault, vault.as_account(), "donate").await?;
+    vault_set_profit_unlock_period(&vault, vault.as_account(), 0).await?;
+
+    mt_transfer_call_deposit(
+        &usdt, &vault, &alice, "token1", 1000, None, None, None, None,
+    )
+    .await?;
+
+    mt_transfer(&usdt, &alice, vault.id(), "token1", 1000).await?;
+    let total_assets = vault_sync(&vault, &alice).await?;
+    assert_eq!(total_assets.0, 2000, "Surplus should be donated as yield");
+    assert_eq!(vault_total_assets(&vault, &alice).await?.0, 2000);
+    assert_eq!(vault_sweepable_surplus(&vault, &alice).await?.0, 0);
+
+    // Existing holders now own the donated yield: 500 shares * 2001 / 1001, rounded down
+    let assets = vault_convert_to_assets(&vault, &alice, 500).await?;
+    assert_eq!(assets.0, 999);
+
+    Ok(())
+}
+
+/// Test that donated yield is locked and excluded from total_assets right after sync
+#[tokio::test]
+async fn test_sync_donate_policy_locks_profit() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+    let vault = deploy_and_init_vault(&owner, &usdt, "token1", "USDT Vault", "vUSDT").await?;
+
+    vault_storage_deposit(&vault, &alice).await?;
+    mt_mint(&usdt, &alice, "token1", 10000).await?;
+
+    // Default unlock period is one day, far longer than this test runs
+    vault_set_surplus_policy(&vault, vault.as_account(), "donate").await?;
+
+    mt_transfer_call_deposit(
+        &usdt, &vault, &alice, "token1", 1000, None, None, None, None,
+    )
+    .await?;
+
+    mt_transfer(&usdt, &alice, vault.id(), "token1", 1000).await?;
+    vault_sync(&vault, &alice).await?;
+
+    let locked_profit = vault_locked_profit(&vault, &alice).await?.0;
+    assert!(
+        locked_profit > 990 && locked_profit <= 1000,
+        "Donated yield should still be locked (got {})",
+        locked_profit
+    );
+
+    let total_assets = vault_total_assets(&vault, &alice).await?.0;
+    assert!(
+        (1000..1010).contains(&total_assets),
+        "Locked profit should not count towards total_assets (got {})",
+        total_assets
+    );
+
+    Ok(())
+}
@@ -0,0 +1,152 @@
+use crate::helper::{
+    errors::assert_vault_error,
+    mock_mt::{deploy_and_init_mock_mt, mt_mint},
+    vault::{
+        deploy_and_init_vault, mt_transfer_call_deposit, vault_balance_of_at, vault_delegate,
+        vault_ft_transfer, vault_get_past_votes, vault_get_votes, vault_redeem,
+        vault_storage_deposit, vault_total_supply_at,
+    },
+};
+
+mod helper;
+
+/// Test that mints, transfers and burns are checkpointed per block
+#[tokio::test]
+async fn test_balance_checkpoints() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+    let bob = worker.dev_create_account().await?;
+
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+    let vault = deploy_and_init_vault(&owner, &usdt, "token1", "USDT Vault", "vUSDT").await?;
+
+    vault_storage_deposit(&vault, &alice).await?;
+    vault_storage_deposit(&vault, &bob).await?;
+    mt_mint(&usdt, &alice, "token1", 10000).await?;
+
+    let before_deposit = worker.view_block().await?.height();
+
+    mt_transfer_call_deposit(
+        &usdt, &vault, &alice, "token1", 1000, None, None, None, None,
+    )
+    .await?;
+    let after_deposit = worker.view_block().await?.height();
+
+    vault_ft_transfer(&vault, &alice, &bob, 400).await?;
+    let after_transfer = worker.view_block().await?.height();
+
+    vault_redeem(&vault, &bob, 100, None, None).await?;
+    let after_redeem = worker.view_block().await?.height();
+
+    worker.fast_forward(1).await?;
+
+    assert_eq!(
+        vault_balance_of_at(&vault, &alice, &alice, before_deposit)
+            .await?
+            .0,
+        0
+    );
+    assert_eq!(
+        vault_balance_of_at(&vault, &alice, &alice, after_deposit)
+            .await?
+            .0,
+        1000
+    );
+    assert_eq!(
+        vault_balance_of_at(&vault, &alice, &alice, after_transfer)
+            .await?
+            .0,
+        600
+    );
+    assert_eq!(
+        vault_balance_of_at(&vault, &alice, &bob, after_deposit)
+            .await?
+            .0,
+        0
+    );
+    assert_eq!(
+        vault_balance_of_at(&vault, &alice, &bob, after_transfer)
+            .await?
+            .0,
+        400
+    );
+    assert_eq!(
+        vault_balance_of_at(&vault, &alice, &bob, after_redeem)
+            .await?
+            .0,
+        300
+    );
+
+    assert_eq!(
+        vault_total_supply_at(&vault, &alice, before_deposit)
+            .await?
+            .0,
+        0
+    );
+    assert_eq!(
+        vault_total_supply_at(&vault, &alice, after_transfer)
+            .await?
+            .0,
+        1000
+    );
+    assert_eq!(
+        vault_total_supply_at(&vault, &alice, after_redeem).await?.0,
+        900
+    );
+
+    // The current block is still open, so it cannot be snapshotted
+    let now = worker.view_block().await?.height();
+    let result = vault_total_supply_at(&vault, &alice, now + 1).await;
+    assert_vault_error(result, "BLOCK_NOT_IN_PAST");
+
+    Ok(())
+}
+
+/// Test that shares count towards their holder until delegated, and follow the delegation
+#[tokio::test]
+async fn test_vote_delegation() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+    let bob = worker.dev_create_account().await?;
+
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+    let vault = deploy_and_init_vault(&owner, &usdt, "token1", "USDT Vault", "vUSDT").await?;
+
+    vault_storage_deposit(&vault, &alice).await?;
+    vault_storage_deposit(&vault, &bob).await?;
+    mt_mint(&usdt, &alice, "token1", 10000).await?;
+
+    mt_transfer_call_deposit(
+        &usdt, &vault, &alice, "token1", 1000, None, None, None, None,
+    )
+    .await?;
+    vault_ft_transfer(&vault, &alice, &bob, 400).await?;
+
+    assert_eq!(vault_get_votes(&vault, &alice, &alice).await?.0, 600);
+    assert_eq!(vault_get_votes(&vault, &alice, &bob).await?.0, 400);
+
+    vault_delegate(&vault, &alice, Some(&bob)).await?;
+    let after_delegate = worker.view_block().await?.height();
+    assert_eq!(vault_get_votes(&vault, &alice, &alice).await?.0, 0);
+    assert_eq!(vault_get_votes(&vault, &alice, &bob).await?.0, 1000);
+
+    // Shares received after delegating count towards the delegatee as well
+    vault_ft_transfer(&vault, &bob, &alice, 100).await?;
+    assert_eq!(vault_get_votes(&vault, &alice, &bob).await?.0, 1000);
+
+    vault_delegate(&vault, &alice, None).await?;
+    assert_eq!(vault_get_votes(&vault, &alice, &alice).await?.0, 700);
+    assert_eq!(vault_get_votes(&vault, &alice, &bob).await?.0, 300);
+
+    worker.fast_forward(1).await?;
+    assert_eq!(
+        vault_get_past_votes(&vault, &alice, &bob, after_delegate)
+            .await?
+            .0,
+        1000
+    );
+
+    Ok(())
+}
@@ -0,0 +1,190 @@
+use crate::helper::{
+    mock_mt::{
+        deploy_and_init_mock_mt, mt_balance_of, mt_credit_yield, mt_mint, mt_set_yield_rate,
+        mt_slash,
+    },
+    vault::{
+        deploy_and_init_vault, mt_transfer_call_deposit, vault_balance_of, vault_convert_to_assets,
+        vault_locked_profit, vault_redeem, vault_set_profit_unlock_period,
+        vault_set_surplus_policy, vault_storage_deposit, vault_sync, vault_total_assets,
+    },
+};
+
+mod helper;
+
+/// Test that yield credited to the vault raises the share price for existing holders
+/// and that a later depositor does not dilute them
+#[tokio::test]
+async fn test_credited_yield_raises_share_price() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+    let bob = worker.dev_create_account().await?;
+    let carol = worker.dev_create_account().await?;
+
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+    let vault = deploy_and_init_vault(&owner, &usdt, "token1", "USDT Vault", "vUSDT").await?;
+    vault_set_surplus_policy(&vault, vault.as_account(), "donate").await?;
+    vault_set_profit_unlock_period(&vault, vault.as_account(), 0).await?;
+
+    for account in [&alice, &bob, &carol] {
+        vault_storage_deposit(&vault, account).await?;
+        mt_mint(&usdt, account, "token1", 10000).await?;
+    }
+    for account in [&alice, &bob] {
+        mt_transfer_call_deposit(
+            &usdt, &vault, account, "token1", 1000, None, None, None, None,
+        )
+        .await?;
+    }
+
+    mt_credit_yield(&usdt, vault.id(), "token1", 1000).await?;
+    assert_eq!(vault_sync(&vault, &alice).await?.0, 3000);
+
+    // 1000 shares * 3001 / 2001, rounded down
+    let value_before = vault_convert_to_assets(&vault, &alice, 1000).await?.0;
+    assert_eq!(value_before, 1499);
+
+    // 1500 * 2001 / 3001 shares, rounded down in favour of the holders
+    mt_transfer_call_deposit(
+        &usdt, &vault, &carol, "token1", 1500, None, None, None, None,
+    )
+    .await?;
+    assert_eq!(vault_balance_of(&vault, &carol, &carol).await?.0, 1000);
+
+    let value_after = vault_convert_to_assets(&vault, &alice, 1000).await?.0;
+    assert!(
+        value_after >= value_before,
+        "Depositing at the new price must not dilute existing holders ({} < {})",
+        value_after,
+        value_before
+    );
+
+    // 1000 shares * 4501 / 3001, rounded down
+    assert_eq!(
+        vault_redeem(&vault, &alice, 1000, None, None).await?.0,
+        1499
+    );
+    assert_eq!(mt_balance_of(&usdt, &alice, "token1").await?, 10499);
+
+    Ok(())
+}
+
+/// Test that a slash of the vault's balance is realised by sync and shared by every holder
+#[tokio::test]
+async fn test_slash_is_shared_by_holders() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+    let bob = worker.dev_create_account().await?;
+
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+    let vault = deploy_and_init_vault(&owner, &usdt, "token1", "USDT Vault", "vUSDT").await?;
+
+    for account in [&alice, &bob] {
+        vault_storage_deposit(&vault, account).await?;
+        mt_mint(&usdt, account, "token1", 10000).await?;
+        mt_transfer_call_deposit(
+            &usdt, &vault, account, "token1", 1000, None, None, None, None,
+        )
+        .await?;
+    }
+
+    mt_slash(&usdt, vault.id(), "token1", 500).await?;
+    assert_eq!(vault_sync(&vault, &alice).await?.0, 1500);
+
+    // 1000 shares * 1501 / 2001
+    assert_eq!(vault_convert_to_assets(&vault, &alice, 1000).await?.0, 750);
+    assert_eq!(vault_redeem(&vault, &bob, 1000, None, None).await?.0, 750);
+
+    // Bob leaving at the slashed price leaves Alice's share of the loss unchanged
+    assert_eq!(vault_total_assets(&vault, &alice).await?.0, 750);
+    assert_eq!(vault_redeem(&vault, &alice, 500, None, None).await?.0, 375);
+
+    Ok(())
+}
+
+/// Test that yield streamed to the vault over time is picked up by sync
+#[tokio::test]
+async fn test_streamed_yield_is_synced() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+    let vault = deploy_and_init_vault(&owner, &usdt, "token1", "USDT Vault", "vUSDT").await?;
+    vault_set_surplus_policy(&vault, vault.as_account(), "donate").await?;
+    vault_set_profit_unlock_period(&vault, vault.as_account(), 0).await?;
+
+    vault_storage_deposit(&vault, &alice).await?;
+    mt_mint(&usdt, &alice, "token1", 10000).await?;
+    mt_transfer_call_deposit(
+        &usdt, &vault, &alice, "token1", 1000, None, None, None, None,
+    )
+    .await?;
+
+    mt_set_yield_rate(&usdt, vault.id(), "token1", 10).await?;
+    worker.fast_forward(20).await?;
+    mt_set_yield_rate(&usdt, vault.id(), "token1", 0).await?;
+
+    let streamed = mt_balance_of(&usdt, vault.as_account(), "token1").await? - 1000;
+    assert!(streamed > 0, "Yield should have streamed to the vault");
+    assert_eq!(streamed % 10, 0, "Only whole seconds of yield are paid");
+
+    assert_eq!(vault_sync(&vault, &alice).await?.0, 1000 + streamed);
+    assert_eq!(
+        vault_sync(&vault, &alice).await?.0,
+        1000 + streamed,
+        "A stopped stream must not add yield"
+    );
+
+    // Alice owns every share, so she owns all of the yield less rounding
+    let value = vault_convert_to_assets(&vault, &alice, 1000).await?.0;
+    assert!(value > 1000 && value <= 1000 + streamed);
+
+    Ok(())
+}
+
+/// Test that depositing right before yield is synced and redeeming right after
+/// captures none of it while the profit is still locked
+#[tokio::test]
+async fn test_yield_cannot_be_sandwiched() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+    let bob = worker.dev_create_account().await?;
+
+    let usdt = deploy_and_init_mock_mt(&owner).await?;
+    let vault = deploy_and_init_vault(&owner, &usdt, "token1", "USDT Vault", "vUSDT").await?;
+    // Default unlock period is one day, far longer than this test runs
+    vault_set_surplus_policy(&vault, vault.as_account(), "donate").await?;
+
+    for account in [&alice, &bob] {
+        vault_storage_deposit(&vault, account).await?;
+        mt_mint(&usdt, account, "token1", 10000).await?;
+        mt_transfer_call_deposit(
+            &usdt, &vault, account, "token1", 1000, None, None, None, None,
+        )
+        .await?;
+    }
+
+    mt_credit_yield(&usdt, vault.id(), "token1", 1000).await?;
+    vault_sync(&vault, &alice).await?;
+
+    let shares = vault_balance_of(&vault, &bob, &bob).await?.0;
+    let redeemed = vault_redeem(&vault, &bob, shares, None, None).await?.0;
+    assert!(
+        redeemed <= 1000,
+        "Redeemed {} right after the sync, more than was deposited",
+        redeemed
+    );
+
+    let locked_profit = vault_locked_profit(&vault, &alice).await?.0;
+    assert!(
+        locked_profit > 990,
+        "The yield should stay locked for the remaining holders (got {})",
+        locked_profit
+    );
+
+    Ok(())
+}
@@ -0,0 +1,61 @@
+/// Parses a decimal amount such as `1.5` into units of a token with `decimals` decimals
+pub fn parse_amount(amount: &str, decimals: u8) -> Result<u128, String> {
+    let invalid = || format!("Invalid amount {}", amount);
+
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    if whole.is_empty() && fraction.is_empty() {
+        return Err(invalid());
+    }
+    if !whole
+        .chars()
+        .chain(fraction.chars())
+        .all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+    if fraction.len() > decimals as usize {
+        return Err(format!(
+            "Amount {} has more than {} decimals",
+            amount, decimals
+        ));
+    }
+
+    let scale = 10u128
+        .checked_pow(decimals as u32)
+        .ok_or_else(|| format!("Unsupported decimals {}", decimals))?;
+    let whole: u128 = if whole.is_empty() {
+        0
+    } else {
+        whole.parse().map_err(|_| invalid())?
+    };
+    let fraction: u128 = if fraction.is_empty() {
+        0
+    } else {
+        let padded = format!("{:0<width$}", fraction, width = decimals as usize);
+        padded.parse().map_err(|_| invalid())?
+    };
+
+    whole
+        .checked_mul(scale)
+        .and_then(|units| units.checked_add(fraction))
+        .ok_or_else(|| format!("Amount {} is too large", amount))
+}
+
+/// Formats `units` of a token with `decimals` decimals, without trailing zeros
+pub fn format_amount(units: u128, decimals: u8) -> String {
+    let digits = units.to_string();
+    let decimals = decimals as usize;
+    if decimals == 0 {
+        return digits;
+    }
+
+    let padded = format!("{:0>width$}", digits, width = decimals + 1);
+    let (whole, fraction) = padded.split_at(padded.len() - decimals);
+    let fraction = fraction.trim_end_matches('0');
+
+    if fraction.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{}.{}", whole, fraction)
+    }
+}
@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+
+/// Command line split into positional arguments and `--name value` options
+pub struct Args {
+    positional: Vec<String>,
+    options: HashMap<String, String>,
+}
+
+impl Args {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut positional = vec![];
+        let mut options = HashMap::new();
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let Some(name) = arg.strip_prefix("--") else {
+                positional.push(arg);
+                continue;
+            };
+
+            let (name, value) = match name.split_once('=') {
+                Some((name, value)) => (name.to_string(), value.to_string()),
+                None => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| format!("Missing value for --{}", name))?;
+                    (name.to_string(), value)
+                }
+            };
+            options.insert(name, value);
+        }
+
+        Ok(Self {
+            positional,
+            options,
+        })
+    }
+
+    pub fn command(&self) -> Option<&str> {
+        self.positional.first().map(String::as_str)
+    }
+
+    /// Positional argument after the command, 0 being the first
+    pub fn argument(&self, index: usize, name: &str) -> Result<&str, String> {
+        self.positional
+            .get(index + 1)
+            .map(String::as_str)
+            .ok_or_else(|| format!("Missing <{}>", name))
+    }
+
+    pub fn optional_argument(&self, index: usize) -> Option<&str> {
+        self.positional.get(index + 1).map(String::as_str)
+    }
+
+    /// Positional arguments from `index` on, at least one
+    pub fn arguments_from(&self, index: usize, name: &str) -> Result<&[String], String> {
+        match self.positional.get(index + 1..) {
+            Some(arguments) if !arguments.is_empty() => Ok(arguments),
+            _ => Err(format!("Missing <{}>...", name)),
+        }
+    }
+
+    /// Value of `--name`, falling back to the `env` environment variable
+    pub fn option(&self, name: &str, env: Option<&str>) -> Option<String> {
+        self.options
+            .get(name)
+            .cloned()
+            .or_else(|| env.and_then(|env| std::env::var(env).ok()))
+    }
+
+    pub fn required_option(&self, name: &str, env: Option<&str>) -> Result<String, String> {
+        self.option(name, env).ok_or_else(|| match env {
+            Some(env) => format!("Missing --{} (or {})", name, env),
+            None => format!("Missing --{}", name),
+        })
+    }
+}
@@ -0,0 +1,520 @@
+//! Operator commands for tokenized MT vaults, sent through JSON-RPC.
+//!
+//! Amounts are read and printed in whole tokens. Shares are scaled by the decimals
+//! from the vault's `ft_metadata`, assets by `--asset-decimals` or, without it, by
+//! the decimals in the asset contract's NEP-245 metadata.
+
+pub mod amount;
+mod args;
+
+use std::error::Error;
+
+use near_contract_standards::fungible_token::metadata::FungibleTokenMetadata;
+use near_sdk::{json_types::U128, AccountId, Gas, NearToken};
+use serde_json::json;
+use vault_client::rpc::{RpcTransport, SecretKey};
+use vault_client::vault_events::VaultEvent;
+use vault_client::{DepositOptions, Executed, SurplusPolicy, VaultClient, VaultTransport};
+
+use crate::amount::{format_amount, parse_amount};
+use crate::args::Args;
+
+const GAS_FOR_INIT: Gas = Gas::from_tgas(100);
+
+/// Commands that only call views, so they need no secret key
+const READ_ONLY_COMMANDS: [&str; 3] = ["info", "position", "referrals"];
+
+/// Decimals amounts are scaled by, which differ between the asset and the shares
+#[derive(Clone, Copy)]
+struct Decimals {
+    assets: u8,
+    shares: u8,
+}
+
+pub const USAGE: &str = "\
+Usage: vault-cli [--rpc-url URL] [--account-id ID] [--secret-key KEY] [--vault ID]
+                 [--asset-decimals N] <command>
+
+Connection (options or environment variables):
+  --rpc-url     VAULT_RPC_URL      JSON-RPC endpoint
+  --account-id  VAULT_ACCOUNT_ID   Account signing transactions
+  --secret-key  VAULT_SECRET_KEY   Full access key of that account, e.g. ed25519:...,
+                                   not needed for info, position and referrals
+  --vault       VAULT_ID           Vault contract, not needed for deploy
+  --asset-decimals VAULT_ASSET_DECIMALS
+                                   Decimals of the vault asset, read from its
+                                   metadata if omitted
+
+Commands:
+  deploy <wasm> --asset ID --token-id ID --name NAME --symbol SYMBOL --decimals N
+                               Deploy the vault to the signer account and initialise it
+  info                         Vault-wide figures and settings
+  position [account]           Position of an account, the signer by default
+  register [account]           Register an account for vault shares, or top up its storage
+  deposit <amount> [--receiver ID] [--min-shares N] [--max-shares N] [--memo TEXT]
+          [--referrer ID]
+  redeem <shares> [--receiver ID] [--memo TEXT]
+  withdraw <amount> [--receiver ID] [--memo TEXT]
+  sync                         Reconcile total assets with the vault's asset balance
+  referrals [account]          Deposits attributed to a referrer, the signer by default
+
+Owner commands:
+  set-owner <account>
+  set-surplus-policy <donate|sweep>
+  set-profit-unlock-period <seconds>
+  set-flash-loans <on|off> <fee-bps>
+  set-price-history-length <checkpoints>
+  set-referral-fee <fee-bps>
+  set-allowlist <on|off>
+  allowlist-add <account>...
+  allowlist-remove <account>...
+  set-guardian [account]       Omit the account to remove the guardian
+  freeze <account> [--memo TEXT]
+  unfreeze <account> [--memo TEXT]
+
+Guardian commands:
+  seize <account> <recovery-account> [--memo TEXT]
+";
+
+/// Runs the command line `args`, without the program name, printing its result.
+pub async fn run(args: impl IntoIterator<Item = String>) -> Result<(), Box<dyn Error>> {
+    let args = Args::parse(args)?;
+    let Some(command) = args.command() else {
+        print!("{}", USAGE);
+        return Ok(());
+    };
+
+    let rpc_url = args.required_option("rpc-url", Some("VAULT_RPC_URL"))?;
+    let account_id = args
+        .required_option("account-id", Some("VAULT_ACCOUNT_ID"))?
+        .parse()?;
+    // Views go without a key, only commands that send a transaction sign
+    let transport = if READ_ONLY_COMMANDS.contains(&command) {
+        RpcTransport::read_only(&rpc_url, account_id)
+    } else {
+        RpcTransport::new(
+            &rpc_url,
+            account_id,
+            args.required_option("secret-key", Some("VAULT_SECRET_KEY"))?
+                .parse::<SecretKey>()?,
+        )
+    };
+
+    if command == "deploy" {
+        return deploy(&args, &transport).await;
+    }
+
+    let vault_id: AccountId = args.required_option("vault", Some("VAULT_ID"))?.parse()?;
+    let client = VaultClient::connect(transport, vault_id).await?;
+    let decimals = Decimals {
+        assets: match args.option("asset-decimals", Some("VAULT_ASSET_DECIMALS")) {
+            Some(decimals) => decimals.parse()?,
+            None => client
+                .asset_decimals()
+                .await?
+                .ok_or("The asset contract does not report its decimals, pass --asset-decimals")?,
+        },
+        shares: client.ft_metadata().await?.decimals,
+    };
+    let signer_id = client.transport().signer_id();
+    let receiver_id = args
+        .option("receiver", None)
+        .map(|id| id.parse())
+        .transpose()?;
+    let memo = args.option("memo", None);
+
+    match command {
+        "info" => {
+            let info = client.vault_info().await?;
+            println!("Vault:                {}", client.vault_id());
+            println!(
+                "Asset:                {} / {}",
+                info.asset, info.asset_token_id
+            );
+            println!("Owner:                {}", info.owner);
+            println!("Version:              {}", info.version);
+            println!("Share decimals:       {}", info.decimals);
+            println!("Asset decimals:       {}", decimals.assets);
+            println!(
+                "Total assets:         {}",
+                format_amount(info.total_assets.0, decimals.assets)
+            );
+            println!(
+                "Total supply:         {}",
+                format_amount(info.total_supply.0, decimals.shares)
+            );
+            println!(
+                "Price per share:      {}",
+                format_amount(info.price_per_share.0, decimals.assets)
+            );
+            println!(
+                "Locked profit:        {}",
+                format_amount(info.locked_profit.0, decimals.assets)
+            );
+            println!(
+                "Sweepable surplus:    {}",
+                format_amount(info.sweepable_surplus.0, decimals.assets)
+            );
+            println!("Surplus policy:       {:?}", info.surplus_policy);
+            println!(
+                "Profit unlock period: {}s",
+                info.profit_unlock_period.0 / 1_000_000_000
+            );
+            println!(
+                "Flash loans:          {} ({} bps fee{})",
+                if info.flash_loans_enabled {
+                    "on"
+                } else {
+                    "off"
+                },
+                info.flash_loan_fee_bps,
+                if info.flash_loan_open {
+                    ", loan open"
+                } else {
+                    ""
+                }
+            );
+            println!("Referral fee:         {} bps", info.referral_fee_bps);
+            println!(
+                "Allowlist:            {}",
+                if info.allowlist_enabled { "on" } else { "off" }
+            );
+            match &info.guardian {
+                Some(guardian) => println!("Guardian:             {}", guardian),
+                None => println!("Guardian:             none"),
+            }
+        }
+        "position" => {
+            let account_id = match args.optional_argument(0) {
+                Some(account_id) => account_id.parse()?,
+                None => signer_id,
+            };
+            let position = client.account_position(&account_id).await?;
+            println!("Account:       {}", position.account_id);
+            println!("Registered:    {}", position.storage_balance.is_some());
+            println!(
+                "Shares:        {}",
+                format_amount(position.shares.0, decimals.shares)
+            );
+            println!(
+                "Assets:        {}",
+                format_amount(position.assets.0, decimals.assets)
+            );
+            println!(
+                "Max withdraw:  {}",
+                format_amount(position.max_withdraw.0, decimals.assets)
+            );
+            println!(
+                "Max redeem:    {}",
+                format_amount(position.max_redeem.0, decimals.shares)
+            );
+            println!(
+                "Pending:       {} withdrawal(s), {} shares, {} assets",
+                position.pending_withdrawals.requests,
+                format_amount(position.pending_withdrawals.shares.0, decimals.shares),
+                format_amount(position.pending_withdrawals.assets.0, decimals.assets)
+            );
+        }
+        "register" => {
+            let account_id = args
+                .optional_argument(0)
+                .map(|account_id| account_id.parse())
+                .transpose()?;
+            client.storage_deposit(account_id).await?;
+            println!("Registered");
+        }
+        "deposit" => {
+            let amount = parse_amount(args.argument(0, "amount")?, decimals.assets)?;
+            let shares = |name| {
+                args.option(name, None)
+                    .map(|shares| parse_amount(&shares, decimals.shares).map(U128))
+                    .transpose()
+            };
+            let options = DepositOptions {
+                receiver_id,
+                min_shares: shares("min-shares")?,
+                max_shares: shares("max-shares")?,
+                memo,
+                referrer: args
+                    .option("referrer", None)
+                    .map(|id| id.parse())
+                    .transpose()?,
+            };
+
+            let executed = client.deposit(amount, options).await?;
+            println!(
+                "Minted {} shares",
+                format_amount(executed.value, decimals.shares)
+            );
+            print_events(&executed, decimals);
+        }
+        "redeem" => {
+            let shares = parse_amount(args.argument(0, "shares")?, decimals.shares)?;
+            let executed = client.redeem(shares, receiver_id, memo).await?;
+            println!(
+                "Received {} assets",
+                format_amount(executed.value, decimals.assets)
+            );
+            print_events(&executed, decimals);
+        }
+        "withdraw" => {
+            let assets = parse_amount(args.argument(0, "amount")?, decimals.assets)?;
+            let executed = client.withdraw(assets, receiver_id, memo).await?;
+            println!(
+                "Received {} assets",
+                format_amount(executed.value, decimals.assets)
+            );
+            print_events(&executed, decimals);
+        }
+        "sync" => {
+            let executed = client.sync().await?;
+            println!(
+                "Total assets {}",
+                format_amount(executed.value, decimals.assets)
+            );
+            print_events(&executed, decimals);
+        }
+        "referrals" => {
+            let referrer_id = match args.optional_argument(0) {
+                Some(referrer_id) => referrer_id.parse()?,
+                None => signer_id,
+            };
+            let stats = client.referral_stats(&referrer_id).await?;
+            println!("Referrer:      {}", referrer_id);
+            println!("Deposits:      {}", stats.deposits);
+            println!(
+                "Assets:        {}",
+                format_amount(stats.assets.0, decimals.assets)
+            );
+            println!(
+                "Shares:        {}",
+                format_amount(stats.shares.0, decimals.shares)
+            );
+            println!(
+                "Fee shares:    {}",
+                format_amount(stats.fee_shares.0, decimals.shares)
+            );
+        }
+        "set-owner" => {
+            let new_owner = args.argument(0, "account")?.parse()?;
+            print_events(&client.set_owner(&new_owner).await?, decimals);
+        }
+        "set-surplus-policy" => {
+            let surplus_policy = match args.argument(0, "donate|sweep")? {
+                "donate" => SurplusPolicy::Donate,
+                "sweep" => SurplusPolicy::Sweep,
+                other => return Err(format!("Unknown surplus policy {}", other).into()),
+            };
+            print_events(&client.set_surplus_policy(surplus_policy).await?, decimals);
+        }
+        "set-profit-unlock-period" => {
+            let seconds: u64 = args.argument(0, "seconds")?.parse()?;
+            let period_ns = seconds
+                .checked_mul(1_000_000_000)
+                .ok_or("Profit unlock period too long")?;
+            print_events(&client.set_profit_unlock_period(period_ns).await?, decimals);
+        }
+        "set-flash-loans" => {
+            let enabled = parse_switch(args.argument(0, "on|off")?)?;
+            let fee_bps = args.argument(1, "fee-bps")?.parse()?;
+            print_events(
+                &client.set_flash_loan_config(enabled, fee_bps).await?,
+                decimals,
+            );
+        }
+        "set-price-history-length" => {
+            let length = args.argument(0, "checkpoints")?.parse()?;
+            print_events(&client.set_price_history_length(length).await?, decimals);
+        }
+        "set-allowlist" => {
+            let enabled = parse_switch(args.argument(0, "on|off")?)?;
+            print_events(&client.set_allowlist_enabled(enabled).await?, decimals);
+        }
+        "allowlist-add" | "allowlist-remove" => {
+            let account_ids = args
+                .arguments_from(0, "account")?
+                .iter()
+                .map(|account_id| account_id.parse())
+                .collect::<Result<Vec<AccountId>, _>>()?;
+            let executed = if command == "allowlist-add" {
+                client.add_to_allowlist(&account_ids).await?
+            } else {
+                client.remove_from_allowlist(&account_ids).await?
+            };
+            print_events(&executed, decimals);
+        }
+        "set-guardian" => {
+            let guardian = args
+                .optional_argument(0)
+                .map(|guardian| guardian.parse())
+                .transpose()?;
+            print_events(&client.set_guardian(guardian.as_ref()).await?, decimals);
+        }
+        "freeze" | "unfreeze" => {
+            let account_id = args.argument(0, "account")?.parse()?;
+            let executed = if command == "freeze" {
+                client.freeze(&account_id, memo).await?
+            } else {
+                client.unfreeze(&account_id, memo).await?
+            };
+            print_events(&executed, decimals);
+        }
+        "seize" => {
+            let account_id = args.argument(0, "account")?.parse()?;
+            let recovery_id = args.argument(1, "recovery-account")?.parse()?;
+            let executed = client.seize(&account_id, &recovery_id, memo).await?;
+            println!(
+                "Seized {} shares",
+                format_amount(executed.value, decimals.shares)
+            );
+            print_events(&executed, decimals);
+        }
+        "set-referral-fee" => {
+            let fee_bps = args.argument(0, "fee-bps")?.parse()?;
+            print_events(&client.set_referral_fee_bps(fee_bps).await?, decimals);
+        }
+        other => return Err(format!("Unknown command {}\n\n{}", other, USAGE).into()),
+    }
+
+    Ok(())
+}
+
+async fn deploy(args: &Args, transport: &RpcTransport) -> Result<(), Box<dyn Error>> {
+    let code = tokio::fs::read(args.argument(0, "wasm")?).await?;
+    let metadata = FungibleTokenMetadata {
+        spec: "ft-1.0.0".to_string(),
+        name: args.required_option("name", None)?,
+        symbol: args.required_option("symbol", None)?,
+        icon: None,
+        reference: None,
+        reference_hash: None,
+        decimals: args.required_option("decimals", None)?.parse()?,
+    };
+
+    let vault_id = transport.signer_id();
+    transport.deploy_contract(code).await?;
+    transport
+        .call(
+            &vault_id,
+            "new",
+            serde_json::to_vec(&json!({
+                "asset": args.required_option("asset", None)?,
+                "asset_token_id": args.required_option("token-id", None)?,
+                "metadata": metadata,
+            }))?,
+            NearToken::from_yoctonear(0),
+            GAS_FOR_INIT,
+        )
+        .await?;
+
+    println!("Deployed vault to {}", vault_id);
+    Ok(())
+}
+
+fn parse_switch(value: &str) -> Result<bool, String> {
+    match value {
+        "on" => Ok(true),
+        "off" => Ok(false),
+        other => Err(format!("Expected on or off, got {}", other)),
+    }
+}
+
+/// Prints one line per vault event logged by a transaction
+fn print_events<T>(executed: &Executed<T>, decimals: Decimals) {
+    for event in &executed.events {
+        match event {
+            VaultEvent::VaultDeposit(deposits) => {
+                for deposit in deposits {
+                    println!(
+                        "  deposit: {} assets for {} shares to {}{}",
+                        format_amount(deposit.assets, decimals.assets),
+                        format_amount(deposit.shares, decimals.shares),
+                        deposit.owner_id,
+                        deposit
+                            .referrer
+                            .as_ref()
+                            .map(|referrer| format!(", referred by {}", referrer))
+                            .unwrap_or_default()
+                    );
+                }
+            }
+            VaultEvent::VaultDepositRefund(refunds) => {
+                for refund in refunds {
+                    println!(
+                        "  deposit refunded: {} assets to {} ({}: {})",
+                        format_amount(refund.amount, decimals.assets),
+                        refund.sender_id,
+                        refund.code,
+                        refund.reason
+                    );
+                }
+            }
+            VaultEvent::VaultWithdraw(withdrawals) => {
+                for withdrawal in withdrawals {
+                    println!(
+                        "  withdraw: {} shares for {} assets to {}",
+                        format_amount(withdrawal.shares, decimals.shares),
+                        format_amount(withdrawal.assets, decimals.assets),
+                        withdrawal.receiver_id
+                    );
+                }
+            }
+            VaultEvent::VaultWithdrawFailed(failures) => {
+                for failure in failures {
+                    println!(
+                        "  withdraw failed: {}, {} shares restored to {}",
+                        failure.reason,
+                        format_amount(failure.shares, decimals.shares),
+                        failure.owner_id
+                    );
+                }
+            }
+            VaultEvent::VaultConfigChange(changes) => {
+                for change in changes {
+                    println!(
+                        "  {}: {} -> {}",
+                        change.parameter, change.old_value, change.new_value
+                    );
+                }
+            }
+            VaultEvent::VaultOwnerChange(changes) => {
+                for change in changes {
+                    println!(
+                        "  owner: {} -> {}",
+                        change.previous_owner_id, change.new_owner_id
+                    );
+                }
+            }
+            VaultEvent::VaultAllowlistUpdate(updates) => {
+                for update in updates {
+                    println!(
+                        "  allowlist: {} {}",
+                        if update.allowed { "added" } else { "removed" },
+                        update.account_id
+                    );
+                }
+            }
+            VaultEvent::VaultFreeze(updates) => {
+                for update in updates {
+                    println!(
+                        "  {} {} by {}",
+                        if update.frozen { "froze" } else { "unfroze" },
+                        update.account_id,
+                        update.actor_id
+                    );
+                }
+            }
+            VaultEvent::VaultSeize(seizures) => {
+                for seizure in seizures {
+                    println!(
+                        "  seized {} shares of {} to {}",
+                        format_amount(seizure.shares, decimals.shares),
+                        seizure.account_id,
+                        seizure.recovery_id
+                    );
+                }
+            }
+            other => println!("  {:?}", other),
+        }
+    }
+}
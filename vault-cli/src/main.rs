@@ -0,0 +1,7 @@
+#[tokio::main]
+async fn main() {
+    if let Err(error) = vault_cli::run(std::env::args().skip(1)).await {
+        eprintln!("error: {}", error);
+        std::process::exit(1);
+    }
+}
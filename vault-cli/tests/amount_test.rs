@@ -0,0 +1,29 @@
+use vault_cli::amount::{format_amount, parse_amount};
+
+#[test]
+fn test_parse_amount() {
+    assert_eq!(parse_amount("1", 6), Ok(1_000_000));
+    assert_eq!(parse_amount("1.5", 6), Ok(1_500_000));
+    assert_eq!(parse_amount(".25", 6), Ok(250_000));
+    assert_eq!(parse_amount("0.000001", 6), Ok(1));
+    assert_eq!(parse_amount("42", 0), Ok(42));
+
+    assert!(parse_amount("0.0000001", 6).is_err());
+    assert!(parse_amount("", 6).is_err());
+    assert!(parse_amount(".", 6).is_err());
+    assert!(parse_amount("-1", 6).is_err());
+    assert!(parse_amount("1e6", 6).is_err());
+}
+
+#[test]
+fn test_format_amount() {
+    assert_eq!(format_amount(1_500_000, 6), "1.5");
+    assert_eq!(format_amount(1_000_000, 6), "1");
+    assert_eq!(format_amount(1, 6), "0.000001");
+    assert_eq!(format_amount(0, 6), "0");
+    assert_eq!(format_amount(42, 0), "42");
+
+    for amount in [0, 1, 999_999, 1_000_000, 123_456_789] {
+        assert_eq!(parse_amount(&format_amount(amount, 6), 6), Ok(amount));
+    }
+}
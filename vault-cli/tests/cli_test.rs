@@ -0,0 +1,168 @@
+use near_sdk::json_types::U128;
+use near_workspaces::{network::Sandbox, Account, Worker};
+use serde_json::json;
+
+/// Runs the CLI as `account` against the sandbox and returns its stdout
+async fn vault_cli(
+    worker: &Worker<Sandbox>,
+    account: &Account,
+    vault_id: Option<&str>,
+    args: &[&str],
+) -> Result<String, Box<dyn std::error::Error>> {
+    let secret_key = account.secret_key().to_string();
+    let mut full_args = vec!["--secret-key", &secret_key];
+    full_args.extend_from_slice(args);
+
+    vault_cli_without_key(worker, account, vault_id, &full_args).await
+}
+
+/// Runs the CLI for `account` without its secret key, as for views
+async fn vault_cli_without_key(
+    worker: &Worker<Sandbox>,
+    account: &Account,
+    vault_id: Option<&str>,
+    args: &[&str],
+) -> Result<String, Box<dyn std::error::Error>> {
+    let mut command = tokio::process::Command::new(env!("CARGO_BIN_EXE_vault-cli"));
+    command
+        .arg("--rpc-url")
+        .arg(worker.rpc_addr())
+        .arg("--account-id")
+        .arg(account.id().as_str());
+    if let Some(vault_id) = vault_id {
+        command.arg("--vault").arg(vault_id);
+    }
+
+    let output = command.args(args).output().await?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).into_owned().into());
+    }
+
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+/// Test deploying a vault, depositing and handing over ownership from the command line
+#[tokio::test]
+async fn test_cli_deploy_deposit_and_admin() -> Result<(), Box<dyn std::error::Error>> {
+    let worker = near_workspaces::sandbox().await?;
+    let owner = worker.dev_create_account().await?;
+    let vault = worker.dev_create_account().await?;
+    let alice = worker.dev_create_account().await?;
+
+    let mt_code = near_workspaces::compile_project("../mock_contracts/mock_mt").await?;
+    let usdt = owner.deploy(&mt_code).await?.into_result()?;
+    usdt.call("new").transact().await?.into_result()?;
+    alice
+        .call(usdt.id(), "mint")
+        .args_json(json!({
+            "account_id": alice.id(),
+            "token_id": "token1",
+            "amount": "10000000",
+        }))
+        .transact()
+        .await?
+        .into_result()?;
+
+    let vault_code = near_workspaces::compile_project("../").await?;
+    let wasm_path = std::env::temp_dir().join(format!("vault-cli-test-{}.wasm", vault.id()));
+    tokio::fs::write(&wasm_path, &vault_code).await?;
+
+    let output = vault_cli(
+        &worker,
+        &vault,
+        None,
+        &[
+            "deploy",
+            wasm_path.to_str().unwrap(),
+            "--asset",
+            usdt.id().as_str(),
+            "--token-id",
+            "token1",
+            "--name",
+            "USDT Vault",
+            "--symbol",
+            "vUSDT",
+            "--decimals",
+            "6",
+        ],
+    )
+    .await?;
+    assert!(output.contains(vault.id().as_str()));
+    tokio::fs::remove_file(&wasm_path).await?;
+
+    let vault_id = Some(vault.id().as_str());
+    vault_cli(&worker, &alice, vault_id, &["register"]).await?;
+
+    // The mock asset reports no metadata, so its decimals must be passed
+    let result = vault_cli(&worker, &alice, vault_id, &["deposit", "1.5"]).await;
+    assert!(result.unwrap_err().to_string().contains("--asset-decimals"));
+
+    // An asset with 3 decimals mints 1000 share units per whole token into an empty vault
+    let output = vault_cli(
+        &worker,
+        &alice,
+        vault_id,
+        &["deposit", "1.5", "--asset-decimals", "3"],
+    )
+    .await?;
+    assert!(output.contains("Minted 0.0015 shares"), "{}", output);
+
+    let shares: U128 = alice
+        .view(vault.id(), "ft_balance_of")
+        .args_json(json!({ "account_id": alice.id() }))
+        .await?
+        .json()?;
+    assert_eq!(shares.0, 1_500);
+
+    // Views need no secret key, transactions do
+    let output = vault_cli_without_key(
+        &worker,
+        &alice,
+        vault_id,
+        &["info", "--asset-decimals", "3"],
+    )
+    .await?;
+    assert!(output.contains("Total assets:         1.5"), "{}", output);
+    assert!(
+        output.contains("Total supply:         0.0015"),
+        "{}",
+        output
+    );
+
+    let output = vault_cli_without_key(
+        &worker,
+        &alice,
+        vault_id,
+        &["position", "--asset-decimals", "3"],
+    )
+    .await?;
+    assert!(output.contains("Shares:        0.0015"), "{}", output);
+    assert!(output.contains("Assets:        1.5"), "{}", output);
+    let error = vault_cli_without_key(&worker, &alice, vault_id, &["sync"])
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("--secret-key"), "{}", error);
+
+    // Only the owner may hand over the vault
+    assert!(vault_cli(
+        &worker,
+        &alice,
+        vault_id,
+        &["set-owner", owner.id().as_str(), "--asset-decimals", "3"]
+    )
+    .await
+    .is_err());
+    let output = vault_cli(
+        &worker,
+        &vault,
+        vault_id,
+        &["set-owner", owner.id().as_str(), "--asset-decimals", "3"],
+    )
+    .await?;
+    assert!(output.contains(owner.id().as_str()), "{}", output);
+
+    let vault_owner: String = alice.view(vault.id(), "owner").await?.json()?;
+    assert_eq!(vault_owner, owner.id().as_str());
+
+    Ok(())
+}
@@ -0,0 +1,509 @@
+//! Typed client for a deployed tokenized MT vault.
+//!
+//! [`VaultClient`] wraps the vault's JSON interface. It talks to the chain
+//! through a [`VaultTransport`], implemented for near-workspaces accounts
+//! (feature `workspaces`) and for plain JSON-RPC (feature `rpc`).
+
+mod transport;
+mod types;
+
+#[cfg(feature = "rpc")]
+pub mod rpc;
+#[cfg(feature = "workspaces")]
+mod workspaces;
+
+use near_contract_standards::fungible_token::metadata::FungibleTokenMetadata;
+use near_contract_standards::storage_management::{StorageBalance, StorageBalanceBounds};
+use near_sdk::{
+    json_types::{U128, U64},
+    AccountId, Gas, NearToken,
+};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use vault_events::VaultEvent;
+
+pub use crate::transport::{vault_error_code, CallOutcome, ClientError, Result, VaultTransport};
+pub use crate::types::{
+    AccountPosition, DepositOptions, Executed, PendingWithdrawals, ReferralStats, SurplusPolicy,
+    VaultInfo,
+};
+pub use vault_events;
+
+const GAS_FOR_DEPOSIT: Gas = Gas::from_tgas(300);
+const GAS_FOR_WITHDRAWAL: Gas = Gas::from_tgas(300);
+const GAS_FOR_CALL: Gas = Gas::from_tgas(100);
+/// Deposited with a registration on top of the minimum, to pay for the share
+/// history of roughly a hundred deposits or transfers
+const SHARE_HISTORY_STORAGE: NearToken = NearToken::from_millinear(100);
+
+pub struct VaultClient<T> {
+    transport: T,
+    vault_id: AccountId,
+    asset: AccountId,
+    asset_token_id: String,
+}
+
+impl<T: VaultTransport> VaultClient<T> {
+    /// Connects to the vault at `vault_id`, looking up its underlying asset once.
+    pub async fn connect(transport: T, vault_id: AccountId) -> Result<Self> {
+        let asset = view(&transport, &vault_id, "asset", json!({})).await?;
+        let asset_token_id = view(&transport, &vault_id, "asset_token_id", json!({})).await?;
+
+        Ok(Self {
+            transport,
+            vault_id,
+            asset,
+            asset_token_id,
+        })
+    }
+
+    pub fn vault_id(&self) -> &AccountId {
+        &self.vault_id
+    }
+
+    pub fn asset(&self) -> &AccountId {
+        &self.asset
+    }
+
+    pub fn asset_token_id(&self) -> &str {
+        &self.asset_token_id
+    }
+
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    // ===== Transactions =====
+
+    /// Registers `account_id`, or the signer, for vault shares, or tops up its storage
+    /// deposit if it is registered already. The vault charges the share history an
+    /// account adds, e.g. by depositing or transferring, to that deposit.
+    pub async fn storage_deposit(&self, account_id: Option<AccountId>) -> Result<StorageBalance> {
+        let bounds: StorageBalanceBounds = view(
+            &self.transport,
+            &self.vault_id,
+            "storage_balance_bounds",
+            json!({}),
+        )
+        .await?;
+
+        self.call(
+            &self.vault_id,
+            "storage_deposit",
+            json!({"account_id": account_id, "registration_only": false}),
+            bounds.min.saturating_add(SHARE_HISTORY_STORAGE),
+            GAS_FOR_CALL,
+        )
+        .await?
+        .json()
+    }
+
+    /// Deposits `amount` of the asset with `mt_transfer_call` and returns the shares minted.
+    ///
+    /// The shares are 0 when the vault rejected the deposit, e.g. because
+    /// `min_shares` could not be met.
+    pub async fn deposit(&self, amount: u128, options: DepositOptions) -> Result<Executed<u128>> {
+        let outcome = self
+            .call(
+                &self.asset,
+                "mt_transfer_call",
+                json!({
+                    "receiver_id": self.vault_id,
+                    "token_id": self.asset_token_id,
+                    "amount": U128(amount),
+                    "msg": serde_json::to_string(&options)?,
+                }),
+                NearToken::from_yoctonear(1),
+                GAS_FOR_DEPOSIT,
+            )
+            .await?;
+
+        let events = outcome.events();
+        let shares = events
+            .iter()
+            .flat_map(|event| match event {
+                VaultEvent::VaultDeposit(deposits) => deposits.as_slice(),
+                _ => &[],
+            })
+            .map(|deposit| deposit.shares)
+            .sum();
+
+        Ok(Executed {
+            value: shares,
+            events,
+        })
+    }
+
+    /// Burns `shares` and sends the assets they are worth to `receiver_id`, or the signer.
+    ///
+    /// Returns the assets sent, 0 if the transfer failed and the shares were restored.
+    pub async fn redeem(
+        &self,
+        shares: u128,
+        receiver_id: Option<AccountId>,
+        memo: Option<String>,
+    ) -> Result<Executed<u128>> {
+        self.withdrawal(
+            "redeem",
+            json!({"shares": U128(shares), "receiver_id": receiver_id, "memo": memo}),
+        )
+        .await
+    }
+
+    /// Sends `assets` to `receiver_id`, or the signer, burning the shares they are worth.
+    ///
+    /// Returns the assets sent, 0 if the transfer failed and the shares were restored.
+    pub async fn withdraw(
+        &self,
+        assets: u128,
+        receiver_id: Option<AccountId>,
+        memo: Option<String>,
+    ) -> Result<Executed<u128>> {
+        self.withdrawal(
+            "withdraw",
+            json!({"assets": U128(assets), "receiver_id": receiver_id, "memo": memo}),
+        )
+        .await
+    }
+
+    /// Reconciles `total_assets` with the vault's asset balance and returns the new total.
+    pub async fn sync(&self) -> Result<Executed<u128>> {
+        let outcome = self
+            .call(
+                &self.vault_id,
+                "sync",
+                json!({}),
+                NearToken::from_yoctonear(0),
+                GAS_FOR_CALL,
+            )
+            .await?;
+
+        Ok(Executed {
+            value: outcome.json::<U128>()?.0,
+            events: outcome.events(),
+        })
+    }
+
+    // ===== Owner actions =====
+
+    pub async fn set_owner(&self, new_owner: &AccountId) -> Result<Executed<()>> {
+        self.owner_call("set_owner", json!({"new_owner": new_owner}))
+            .await
+    }
+
+    pub async fn set_surplus_policy(&self, surplus_policy: SurplusPolicy) -> Result<Executed<()>> {
+        self.owner_call(
+            "set_surplus_policy",
+            json!({"surplus_policy": surplus_policy}),
+        )
+        .await
+    }
+
+    /// Sets the period, in nanoseconds, over which donated yield unlocks.
+    pub async fn set_profit_unlock_period(&self, period_ns: u64) -> Result<Executed<()>> {
+        self.owner_call(
+            "set_profit_unlock_period",
+            json!({"profit_unlock_period": U64(period_ns)}),
+        )
+        .await
+    }
+
+    pub async fn set_flash_loan_config(&self, enabled: bool, fee_bps: u16) -> Result<Executed<()>> {
+        self.owner_call(
+            "set_flash_loan_config",
+            json!({"enabled": enabled, "fee_bps": fee_bps}),
+        )
+        .await
+    }
+
+    pub async fn set_price_history_length(&self, length: u32) -> Result<Executed<()>> {
+        self.owner_call("set_price_history_length", json!({"length": length}))
+            .await
+    }
+
+    pub async fn set_referral_fee_bps(&self, fee_bps: u16) -> Result<Executed<()>> {
+        self.owner_call("set_referral_fee_bps", json!({"fee_bps": fee_bps}))
+            .await
+    }
+
+    pub async fn set_allowlist_enabled(&self, enabled: bool) -> Result<Executed<()>> {
+        self.owner_call("set_allowlist_enabled", json!({"enabled": enabled}))
+            .await
+    }
+
+    pub async fn add_to_allowlist(&self, account_ids: &[AccountId]) -> Result<Executed<()>> {
+        self.owner_call("add_to_allowlist", json!({"account_ids": account_ids}))
+            .await
+    }
+
+    pub async fn remove_from_allowlist(&self, account_ids: &[AccountId]) -> Result<Executed<()>> {
+        self.owner_call("remove_from_allowlist", json!({"account_ids": account_ids}))
+            .await
+    }
+
+    pub async fn set_guardian(&self, guardian: Option<&AccountId>) -> Result<Executed<()>> {
+        self.owner_call("set_guardian", json!({"guardian": guardian}))
+            .await
+    }
+
+    /// Freezes `account_id`. The signer must be the owner or the guardian.
+    pub async fn freeze(
+        &self,
+        account_id: &AccountId,
+        memo: Option<String>,
+    ) -> Result<Executed<()>> {
+        self.owner_call("freeze", json!({"account_id": account_id, "memo": memo}))
+            .await
+    }
+
+    pub async fn unfreeze(
+        &self,
+        account_id: &AccountId,
+        memo: Option<String>,
+    ) -> Result<Executed<()>> {
+        self.owner_call("unfreeze", json!({"account_id": account_id, "memo": memo}))
+            .await
+    }
+
+    /// Moves the shares of the frozen `account_id` to `recovery_id` and returns them.
+    /// The signer must be the guardian.
+    pub async fn seize(
+        &self,
+        account_id: &AccountId,
+        recovery_id: &AccountId,
+        memo: Option<String>,
+    ) -> Result<Executed<u128>> {
+        let outcome = self
+            .call(
+                &self.vault_id,
+                "seize",
+                json!({"account_id": account_id, "recovery_id": recovery_id, "memo": memo}),
+                NearToken::from_yoctonear(1),
+                GAS_FOR_CALL,
+            )
+            .await?;
+
+        Ok(Executed {
+            value: outcome.json::<U128>()?.0,
+            events: outcome.events(),
+        })
+    }
+
+    // ===== Views =====
+
+    pub async fn total_assets(&self) -> Result<u128> {
+        self.view_u128("total_assets", json!({})).await
+    }
+
+    pub async fn total_supply(&self) -> Result<u128> {
+        self.view_u128("ft_total_supply", json!({})).await
+    }
+
+    pub async fn balance_of(&self, account_id: &AccountId) -> Result<u128> {
+        self.view_u128("ft_balance_of", json!({"account_id": account_id}))
+            .await
+    }
+
+    pub async fn convert_to_shares(&self, assets: u128) -> Result<u128> {
+        self.view_u128("convert_to_shares", json!({"assets": U128(assets)}))
+            .await
+    }
+
+    pub async fn convert_to_assets(&self, shares: u128) -> Result<u128> {
+        self.view_u128("convert_to_assets", json!({"shares": U128(shares)}))
+            .await
+    }
+
+    pub async fn preview_deposit(&self, assets: u128) -> Result<u128> {
+        self.view_u128("preview_deposit", json!({"assets": U128(assets)}))
+            .await
+    }
+
+    pub async fn preview_mint(&self, shares: u128) -> Result<u128> {
+        self.view_u128("preview_mint", json!({"shares": U128(shares)}))
+            .await
+    }
+
+    pub async fn preview_redeem(&self, shares: u128) -> Result<u128> {
+        self.view_u128("preview_redeem", json!({"shares": U128(shares)}))
+            .await
+    }
+
+    pub async fn preview_withdraw(&self, assets: u128) -> Result<u128> {
+        self.view_u128("preview_withdraw", json!({"assets": U128(assets)}))
+            .await
+    }
+
+    pub async fn max_deposit(&self, receiver_id: &AccountId) -> Result<u128> {
+        self.view_u128("max_deposit", json!({"receiver_id": receiver_id}))
+            .await
+    }
+
+    pub async fn max_mint(&self, receiver_id: &AccountId) -> Result<u128> {
+        self.view_u128("max_mint", json!({"receiver_id": receiver_id}))
+            .await
+    }
+
+    pub async fn max_redeem(&self, owner_id: &AccountId) -> Result<u128> {
+        self.view_u128("max_redeem", json!({"owner_id": owner_id}))
+            .await
+    }
+
+    pub async fn max_withdraw(&self, owner_id: &AccountId) -> Result<u128> {
+        self.view_u128("max_withdraw", json!({"owner_id": owner_id}))
+            .await
+    }
+
+    pub async fn ft_metadata(&self) -> Result<FungibleTokenMetadata> {
+        view(&self.transport, &self.vault_id, "ft_metadata", json!({})).await
+    }
+
+    /// Decimals of the vault asset from the NEP-245 `mt_metadata_base_by_token_id` of
+    /// the asset contract, `None` if the token has no metadata or it leaves them out
+    pub async fn asset_decimals(&self) -> Result<Option<u8>> {
+        let metadata: Vec<Option<Value>> = view(
+            &self.transport,
+            &self.asset,
+            "mt_metadata_base_by_token_id",
+            json!({"token_ids": [self.asset_token_id]}),
+        )
+        .await?;
+
+        // The standard encodes decimals as a string, some contracts as a number
+        let decimals = metadata.into_iter().next().flatten().and_then(|metadata| {
+            match &metadata["decimals"] {
+                Value::String(decimals) => decimals.parse().ok(),
+                Value::Number(decimals) => decimals.as_u64().and_then(|d| d.try_into().ok()),
+                _ => None,
+            }
+        });
+
+        Ok(decimals)
+    }
+
+    pub async fn vault_info(&self) -> Result<VaultInfo> {
+        view(&self.transport, &self.vault_id, "get_vault_info", json!({})).await
+    }
+
+    pub async fn account_position(&self, account_id: &AccountId) -> Result<AccountPosition> {
+        view(
+            &self.transport,
+            &self.vault_id,
+            "get_account_position",
+            json!({"account_id": account_id}),
+        )
+        .await
+    }
+
+    pub async fn referral_stats(&self, referrer_id: &AccountId) -> Result<ReferralStats> {
+        view(
+            &self.transport,
+            &self.vault_id,
+            "referral_stats",
+            json!({"referrer_id": referrer_id}),
+        )
+        .await
+    }
+
+    pub async fn allowlist_enabled(&self) -> Result<bool> {
+        view(
+            &self.transport,
+            &self.vault_id,
+            "allowlist_enabled",
+            json!({}),
+        )
+        .await
+    }
+
+    pub async fn is_allowlisted(&self, account_id: &AccountId) -> Result<bool> {
+        view(
+            &self.transport,
+            &self.vault_id,
+            "is_allowlisted",
+            json!({"account_id": account_id}),
+        )
+        .await
+    }
+
+    pub async fn is_frozen(&self, account_id: &AccountId) -> Result<bool> {
+        view(
+            &self.transport,
+            &self.vault_id,
+            "is_frozen",
+            json!({"account_id": account_id}),
+        )
+        .await
+    }
+
+    async fn withdrawal(&self, method: &str, args: Value) -> Result<Executed<u128>> {
+        let outcome = self
+            .call(
+                &self.vault_id,
+                method,
+                args,
+                NearToken::from_yoctonear(1),
+                GAS_FOR_WITHDRAWAL,
+            )
+            .await?;
+
+        Ok(Executed {
+            value: outcome.json::<U128>()?.0,
+            events: outcome.events(),
+        })
+    }
+
+    async fn owner_call(&self, method: &str, args: Value) -> Result<Executed<()>> {
+        let outcome = self
+            .call(
+                &self.vault_id,
+                method,
+                args,
+                NearToken::from_yoctonear(1),
+                GAS_FOR_CALL,
+            )
+            .await?;
+
+        Ok(Executed {
+            value: (),
+            events: outcome.events(),
+        })
+    }
+
+    async fn view_u128(&self, method: &str, args: Value) -> Result<u128> {
+        Ok(view::<U128>(&self.transport, &self.vault_id, method, args)
+            .await?
+            .0)
+    }
+
+    async fn call(
+        &self,
+        contract_id: &AccountId,
+        method: &str,
+        args: Value,
+        deposit: NearToken,
+        gas: Gas,
+    ) -> Result<CallOutcome> {
+        self.transport
+            .call(
+                contract_id,
+                method,
+                serde_json::to_vec(&args)?,
+                deposit,
+                gas,
+            )
+            .await
+    }
+}
+
+async fn view<R: DeserializeOwned>(
+    transport: &impl VaultTransport,
+    contract_id: &AccountId,
+    method: &str,
+    args: Value,
+) -> Result<R> {
+    let result = transport
+        .view(contract_id, method, serde_json::to_vec(&args)?)
+        .await?;
+
+    Ok(serde_json::from_slice(&result)?)
+}
@@ -0,0 +1,172 @@
+use async_trait::async_trait;
+use near_crypto::{InMemorySigner, Signer};
+use near_jsonrpc_client::{methods, JsonRpcClient};
+use near_jsonrpc_primitives::types::query::{QueryResponseKind, RpcQueryError};
+use near_primitives::{
+    action::{Action, DeployContractAction, FunctionCallAction},
+    transaction::{SignedTransaction, Transaction, TransactionV0},
+    types::{BlockReference, Finality, FunctionArgs},
+    views::{FinalExecutionStatus, QueryRequest},
+};
+use near_sdk::{AccountId, Gas, NearToken};
+
+use crate::transport::{CallOutcome, ClientError, Result, VaultTransport};
+
+pub use near_crypto::SecretKey;
+
+/// Signs transactions locally with a full access key and sends them to a JSON-RPC endpoint
+pub struct RpcTransport {
+    client: JsonRpcClient,
+    /// `None` when the transport only reads
+    signer: Option<Signer>,
+    signer_id: AccountId,
+}
+
+impl RpcTransport {
+    pub fn new(rpc_url: &str, signer_id: AccountId, secret_key: SecretKey) -> Self {
+        Self {
+            client: JsonRpcClient::connect(rpc_url),
+            signer: Some(InMemorySigner::from_secret_key(
+                signer_id.clone(),
+                secret_key,
+            )),
+            signer_id,
+        }
+    }
+
+    /// Transport for views on behalf of `account_id`, without a key to send transactions
+    pub fn read_only(rpc_url: &str, account_id: AccountId) -> Self {
+        Self {
+            client: JsonRpcClient::connect(rpc_url),
+            signer: None,
+            signer_id: account_id,
+        }
+    }
+
+    async fn query(&self, request: QueryRequest) -> Result<QueryResponseKind> {
+        let response = self
+            .client
+            .call(methods::query::RpcQueryRequest {
+                block_reference: BlockReference::Finality(Finality::Final),
+                request,
+            })
+            .await
+            .map_err(|error| match error.handler_error() {
+                // Only a view that ran and panicked is an execution failure
+                Some(RpcQueryError::ContractExecutionError { .. }) => {
+                    ClientError::Execution(error.to_string())
+                }
+                _ => ClientError::Transport(error.to_string()),
+            })?;
+
+        Ok(response.kind)
+    }
+}
+
+#[async_trait]
+impl VaultTransport for RpcTransport {
+    fn signer_id(&self) -> AccountId {
+        self.signer_id.clone()
+    }
+
+    async fn view(&self, contract_id: &AccountId, method: &str, args: Vec<u8>) -> Result<Vec<u8>> {
+        let request = QueryRequest::CallFunction {
+            account_id: contract_id.clone(),
+            method_name: method.to_string(),
+            args: FunctionArgs::from(args),
+        };
+
+        match self.query(request).await? {
+            QueryResponseKind::CallResult(result) => Ok(result.result),
+            other => Err(ClientError::Transport(format!(
+                "unexpected query response {:?}",
+                other
+            ))),
+        }
+    }
+
+    async fn call(
+        &self,
+        contract_id: &AccountId,
+        method: &str,
+        args: Vec<u8>,
+        deposit: NearToken,
+        gas: Gas,
+    ) -> Result<CallOutcome> {
+        let action = Action::FunctionCall(Box::new(FunctionCallAction {
+            method_name: method.to_string(),
+            args,
+            gas: gas.as_gas(),
+            deposit: deposit.as_yoctonear(),
+        }));
+
+        self.send(contract_id, vec![action]).await
+    }
+}
+
+impl RpcTransport {
+    /// Deploys `code` to the signer's own account.
+    pub async fn deploy_contract(&self, code: Vec<u8>) -> Result<CallOutcome> {
+        let action = Action::DeployContract(DeployContractAction { code });
+
+        self.send(&self.signer_id, vec![action]).await
+    }
+
+    async fn send(&self, receiver_id: &AccountId, actions: Vec<Action>) -> Result<CallOutcome> {
+        let signer = self.signer.as_ref().ok_or_else(|| {
+            ClientError::Transport(format!("no secret key to sign as {}", self.signer_id))
+        })?;
+        let public_key = signer.public_key();
+        let access_key = self
+            .client
+            .call(methods::query::RpcQueryRequest {
+                block_reference: BlockReference::Finality(Finality::Final),
+                request: QueryRequest::ViewAccessKey {
+                    account_id: self.signer_id.clone(),
+                    public_key: public_key.clone(),
+                },
+            })
+            .await
+            .map_err(|error| ClientError::Transport(error.to_string()))?;
+        let nonce = match access_key.kind {
+            QueryResponseKind::AccessKey(access_key) => access_key.nonce,
+            other => {
+                return Err(ClientError::Transport(format!(
+                    "unexpected access key response {:?}",
+                    other
+                )))
+            }
+        };
+
+        let transaction = Transaction::V0(TransactionV0 {
+            signer_id: self.signer_id.clone(),
+            public_key,
+            nonce: nonce + 1,
+            receiver_id: receiver_id.clone(),
+            block_hash: access_key.block_hash,
+            actions,
+        });
+        let (hash, _) = transaction.get_hash_and_size();
+        let signed_transaction = SignedTransaction::new(signer.sign(hash.as_ref()), transaction);
+
+        let outcome = self
+            .client
+            .call(methods::broadcast_tx_commit::RpcBroadcastTxCommitRequest { signed_transaction })
+            .await
+            .map_err(|error| ClientError::Transport(error.to_string()))?;
+
+        let logs = std::iter::once(&outcome.transaction_outcome)
+            .chain(&outcome.receipts_outcome)
+            .flat_map(|outcome| outcome.outcome.logs.iter().cloned())
+            .collect();
+
+        match outcome.status {
+            FinalExecutionStatus::SuccessValue(value) => Ok(CallOutcome { value, logs }),
+            FinalExecutionStatus::Failure(error) => Err(ClientError::Execution(error.to_string())),
+            status => Err(ClientError::Transport(format!(
+                "transaction did not finish: {:?}",
+                status
+            ))),
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use std::fmt;
+
+use async_trait::async_trait;
+use near_sdk::{AccountId, Gas, NearToken};
+use serde::de::DeserializeOwned;
+use vault_events::{parse_event_log, VaultEvent};
+
+#[derive(Debug)]
+pub enum ClientError {
+    /// The network could not be reached or rejected the request
+    Transport(String),
+    /// The transaction or view executed but failed, e.g. a contract panic
+    Execution(String),
+    /// The contract returned something other than the expected JSON
+    InvalidResponse(serde_json::Error),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transport(error) => write!(f, "transport error: {}", error),
+            Self::Execution(error) => write!(f, "execution failed: {}", error),
+            Self::InvalidResponse(error) => write!(f, "invalid response: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl ClientError {
+    /// Stable code of the vault error the call failed with, e.g. `EXCEEDS_MAX_REDEEM`
+    pub fn vault_error_code(&self) -> Option<&str> {
+        match self {
+            Self::Execution(error) => vault_error_code(error),
+            _ => None,
+        }
+    }
+}
+
+/// Extracts the code from a failure message containing `VAULT_ERROR[<CODE>]`
+pub fn vault_error_code(message: &str) -> Option<&str> {
+    let start = message.find(VAULT_ERROR_PREFIX)? + VAULT_ERROR_PREFIX.len();
+    let length = message[start..].find(']')?;
+    Some(&message[start..start + length])
+}
+
+const VAULT_ERROR_PREFIX: &str = "VAULT_ERROR[";
+
+impl From<serde_json::Error> for ClientError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::InvalidResponse(error)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, ClientError>;
+
+/// Result of a successful transaction
+#[derive(Debug, Clone, Default)]
+pub struct CallOutcome {
+    /// Return value of the transaction, i.e. of its last receipt
+    pub value: Vec<u8>,
+    /// Logs of every receipt of the transaction, in execution order
+    pub logs: Vec<String>,
+}
+
+impl CallOutcome {
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T> {
+        Ok(serde_json::from_slice(&self.value)?)
+    }
+
+    /// Vault events among the logs. Logs of other standards are skipped.
+    pub fn events(&self) -> Vec<VaultEvent> {
+        self.logs
+            .iter()
+            .filter_map(|log| parse_event_log(log).ok())
+            .collect()
+    }
+}
+
+/// Way of reaching the chain on behalf of one signer
+#[async_trait]
+pub trait VaultTransport: Send + Sync {
+    /// Account that signs the transactions sent through this transport
+    fn signer_id(&self) -> AccountId;
+
+    /// Calls a view method with JSON `args` and returns its raw result
+    async fn view(&self, contract_id: &AccountId, method: &str, args: Vec<u8>) -> Result<Vec<u8>>;
+
+    /// Sends a function call transaction and waits for all of its receipts
+    async fn call(
+        &self,
+        contract_id: &AccountId,
+        method: &str,
+        args: Vec<u8>,
+        deposit: NearToken,
+        gas: Gas,
+    ) -> Result<CallOutcome>;
+}
@@ -0,0 +1,96 @@
+use near_contract_standards::storage_management::StorageBalance;
+use near_sdk::{
+    json_types::{U128, U64},
+    serde::{Deserialize, Serialize},
+    AccountId,
+};
+
+/// Options of a deposit, sent to the vault as the `mt_transfer_call` message
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DepositOptions {
+    /// Account credited with the shares, the depositor by default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub receiver_id: Option<AccountId>,
+    /// Reject the whole deposit if it would mint fewer shares
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_shares: Option<U128>,
+    /// Mint at most this many shares and refund the rest of the assets
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_shares: Option<U128>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+    /// Partner the deposit is attributed to, must be registered with the vault
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub referrer: Option<AccountId>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum SurplusPolicy {
+    Donate,
+    Sweep,
+}
+
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ReferralStats {
+    pub deposits: u64,
+    pub assets: U128,
+    pub shares: U128,
+    pub fee_shares: U128,
+}
+
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PendingWithdrawals {
+    pub requests: u32,
+    pub shares: U128,
+    pub assets: U128,
+}
+
+/// Result of `get_account_position`
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccountPosition {
+    pub account_id: AccountId,
+    pub shares: U128,
+    pub assets: U128,
+    pub max_withdraw: U128,
+    pub max_redeem: U128,
+    pub max_deposit: U128,
+    pub pending_withdrawals: PendingWithdrawals,
+    pub storage_balance: Option<StorageBalance>,
+}
+
+/// Result of `get_vault_info`
+#[derive(Deserialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct VaultInfo {
+    pub asset: AccountId,
+    pub asset_token_id: String,
+    pub total_assets: U128,
+    pub total_supply: U128,
+    pub price_per_share: U128,
+    pub decimals: u8,
+    pub owner: AccountId,
+    pub surplus_policy: SurplusPolicy,
+    pub profit_unlock_period: U64,
+    pub locked_profit: U128,
+    pub sweepable_surplus: U128,
+    pub flash_loans_enabled: bool,
+    pub flash_loan_fee_bps: u16,
+    pub flash_loan_open: bool,
+    pub referral_fee_bps: u16,
+    pub allowlist_enabled: bool,
+    pub guardian: Option<AccountId>,
+    pub version: String,
+}
+
+/// Value returned by a transaction, with the vault events it logged
+#[derive(Debug, Clone)]
+pub struct Executed<T> {
+    pub value: T,
+    pub events: Vec<vault_events::VaultEvent>,
+}
@@ -0,0 +1,49 @@
+use async_trait::async_trait;
+use near_sdk::{AccountId, Gas, NearToken};
+use near_workspaces::Account;
+
+use crate::transport::{CallOutcome, ClientError, Result, VaultTransport};
+
+/// Signs with a near-workspaces account, on the sandbox or any network it connects to
+#[async_trait]
+impl VaultTransport for Account {
+    fn signer_id(&self) -> AccountId {
+        self.id().clone()
+    }
+
+    async fn view(&self, contract_id: &AccountId, method: &str, args: Vec<u8>) -> Result<Vec<u8>> {
+        let result = Account::view(self, contract_id, method)
+            .args(args)
+            .await
+            .map_err(|error| ClientError::Execution(error.to_string()))?;
+
+        Ok(result.result)
+    }
+
+    async fn call(
+        &self,
+        contract_id: &AccountId,
+        method: &str,
+        args: Vec<u8>,
+        deposit: NearToken,
+        gas: Gas,
+    ) -> Result<CallOutcome> {
+        let result = Account::call(self, contract_id, method)
+            .args(args)
+            .deposit(deposit)
+            .gas(gas)
+            .transact()
+            .await
+            .map_err(|error| ClientError::Transport(error.to_string()))?;
+
+        let logs = result.logs().into_iter().map(String::from).collect();
+        let success = result
+            .into_result()
+            .map_err(|error| ClientError::Execution(error.to_string()))?;
+
+        Ok(CallOutcome {
+            value: success.raw_bytes().unwrap_or_default(),
+            logs,
+        })
+    }
+}
@@ -0,0 +1,339 @@
+//! Owned, serde-based versions of the events logged by the tokenized MT vault.
+//!
+//! Indexers feed raw receipt logs to [`parse_event_log`] and get a typed
+//! [`VaultEvent`] back. Amounts are decoded from their JSON strings into `u128`.
+//! Logs of other versions of the standard can be told apart with
+//! [`parse_event_header`] before decoding them.
+//! Builds without `std` when the default features are disabled.
+#![no_std]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+extern crate std;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Prefix NEAR puts in front of every structured event log
+pub const EVENT_LOG_PREFIX: &str = "EVENT_JSON:";
+/// Name of the event standard the vault logs under
+pub const VAULT_STANDARD: &str = "vault";
+/// Version of the vault event standard this crate decodes. Logs of any version
+/// with the same major are accepted.
+pub const VAULT_STANDARD_VERSION: &str = "1.0.0";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VaultDeposit {
+    pub sender_id: String,
+    pub owner_id: String,
+    pub token_id: String,
+    #[serde(with = "u128_string")]
+    pub assets: u128,
+    #[serde(with = "u128_string")]
+    pub shares: u128,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub referrer: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VaultDepositRefund {
+    pub sender_id: String,
+    pub owner_id: String,
+    pub token_id: String,
+    #[serde(with = "u128_string")]
+    pub amount: u128,
+    /// Stable error code of the reason the deposit was refused
+    pub code: String,
+    pub reason: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VaultWithdraw {
+    pub owner_id: String,
+    pub receiver_id: String,
+    pub token_id: String,
+    #[serde(with = "u128_string")]
+    pub shares: u128,
+    #[serde(with = "u128_string")]
+    pub assets: u128,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VaultWithdrawFailed {
+    pub owner_id: String,
+    pub receiver_id: String,
+    pub token_id: String,
+    #[serde(with = "u128_string")]
+    pub shares: u128,
+    #[serde(with = "u128_string")]
+    pub assets: u128,
+    pub reason: String,
+    /// Stable error code of the failure
+    pub code: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VaultSync {
+    pub token_id: String,
+    #[serde(with = "u128_string")]
+    pub balance: u128,
+    #[serde(with = "u128_string")]
+    pub previous_total_assets: u128,
+    #[serde(with = "u128_string")]
+    pub total_assets: u128,
+    #[serde(with = "u128_string")]
+    pub profit: u128,
+    #[serde(with = "u128_string")]
+    pub loss: u128,
+    #[serde(with = "u128_string")]
+    pub sweepable_surplus: u128,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VaultRescue {
+    pub token: String,
+    /// `None` when an NEP-141 token was rescued
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_id: Option<String>,
+    #[serde(with = "u128_string")]
+    pub amount: u128,
+    pub receiver_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VaultFlashLoan {
+    pub borrower_id: String,
+    pub token_id: String,
+    #[serde(with = "u128_string")]
+    pub amount: u128,
+    #[serde(with = "u128_string")]
+    pub fee: u128,
+    #[serde(with = "u128_string")]
+    pub repaid: u128,
+    /// Part of the amount plus fee that was not repaid and was taken from the bond
+    #[serde(with = "u128_string")]
+    pub bond_seized: u128,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VaultFlashLoanBond {
+    pub account_id: String,
+    pub token_id: String,
+    #[serde(with = "u128_string")]
+    pub posted: u128,
+    #[serde(with = "u128_string")]
+    pub withdrawn: u128,
+    /// Bond of the account after the change
+    #[serde(with = "u128_string")]
+    pub bond: u128,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VaultFeeAccrued {
+    pub token_id: String,
+    pub source: String,
+    #[serde(with = "u128_string")]
+    pub amount: u128,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VaultOwnerChange {
+    pub previous_owner_id: String,
+    pub new_owner_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VaultConfigChange {
+    /// Name of the setting, matching its view method on the vault
+    pub parameter: String,
+    pub old_value: serde_json::Value,
+    pub new_value: serde_json::Value,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VaultDelegate {
+    pub delegator_id: String,
+    pub from_delegatee_id: String,
+    pub to_delegatee_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VaultAllowlistUpdate {
+    pub account_id: String,
+    /// `true` if the account was added, `false` if it was removed
+    pub allowed: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VaultFreeze {
+    pub account_id: String,
+    /// `true` if the account was frozen, `false` if it was unfrozen
+    pub frozen: bool,
+    /// Owner or guardian who changed the status
+    pub actor_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VaultSeize {
+    pub account_id: String,
+    pub recovery_id: String,
+    pub guardian_id: String,
+    #[serde(with = "u128_string")]
+    pub shares: u128,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
+/// One vault event log. Every variant carries all entries of a batched log.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "event", content = "data")]
+#[serde(rename_all = "snake_case")]
+#[allow(clippy::enum_variant_names)]
+pub enum VaultEvent {
+    VaultDeposit(Vec<VaultDeposit>),
+    VaultDepositRefund(Vec<VaultDepositRefund>),
+    VaultWithdraw(Vec<VaultWithdraw>),
+    VaultWithdrawFailed(Vec<VaultWithdrawFailed>),
+    VaultSync(Vec<VaultSync>),
+    VaultRescue(Vec<VaultRescue>),
+    VaultFlashLoan(Vec<VaultFlashLoan>),
+    VaultFlashLoanBond(Vec<VaultFlashLoanBond>),
+    VaultFeeAccrued(Vec<VaultFeeAccrued>),
+    VaultOwnerChange(Vec<VaultOwnerChange>),
+    VaultConfigChange(Vec<VaultConfigChange>),
+    VaultDelegate(Vec<VaultDelegate>),
+    VaultAllowlistUpdate(Vec<VaultAllowlistUpdate>),
+    VaultFreeze(Vec<VaultFreeze>),
+    VaultSeize(Vec<VaultSeize>),
+}
+
+impl VaultEvent {
+    /// Formats the event the way the vault logs it, including the standard and version
+    pub fn to_event_log(&self) -> String {
+        let envelope = EventEnvelope {
+            standard: String::from(VAULT_STANDARD),
+            version: String::from(VAULT_STANDARD_VERSION),
+            event: self.clone(),
+        };
+        let json = serde_json::to_string(&envelope).expect("Events always serialize");
+
+        alloc::format!("{}{}", EVENT_LOG_PREFIX, json)
+    }
+}
+
+#[derive(Debug)]
+pub enum ParseError {
+    /// The log does not start with [`EVENT_LOG_PREFIX`]
+    NotAnEvent,
+    /// The event belongs to another standard, e.g. `nep141` share transfers
+    OtherStandard(String),
+    /// The major version differs from [`VAULT_STANDARD_VERSION`]'s
+    UnsupportedVersion(String),
+    /// The JSON is malformed or does not match any known vault event
+    InvalidJson(serde_json::Error),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotAnEvent => write!(f, "log is not an {} event", EVENT_LOG_PREFIX),
+            Self::OtherStandard(standard) => write!(f, "event of standard {}", standard),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported vault event version {}", version)
+            }
+            Self::InvalidJson(error) => write!(f, "invalid vault event: {}", error),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+impl From<serde_json::Error> for ParseError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::InvalidJson(error)
+    }
+}
+
+/// Decodes a receipt log into a vault event.
+///
+/// Logs of other standards are rejected with [`ParseError::OtherStandard`], so
+/// indexers can skip them. The major version must be the one of
+/// [`VAULT_STANDARD_VERSION`]; older logs can be routed elsewhere by checking
+/// [`parse_event_header`] first.
+pub fn parse_event_log(log: &str) -> Result<VaultEvent, ParseError> {
+    // Check the header first so that unknown versions are not reported as bad JSON
+    let header = parse_event_header(log)?;
+    if major(&header.version) != major(VAULT_STANDARD_VERSION) {
+        return Err(ParseError::UnsupportedVersion(header.version));
+    }
+
+    let json = &log[EVENT_LOG_PREFIX.len()..];
+    let envelope: EventEnvelope = serde_json::from_str(json)?;
+    Ok(envelope.event)
+}
+
+/// Reads the standard and version of a vault event log without decoding the event,
+/// for consumers that handle several versions of the standard
+pub fn parse_event_header(log: &str) -> Result<EventHeader, ParseError> {
+    let json = log
+        .strip_prefix(EVENT_LOG_PREFIX)
+        .ok_or(ParseError::NotAnEvent)?;
+
+    let header: EventHeader = serde_json::from_str(json)?;
+    if header.standard != VAULT_STANDARD {
+        return Err(ParseError::OtherStandard(header.standard));
+    }
+
+    Ok(header)
+}
+
+/// Standard and version every event log starts with
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct EventHeader {
+    pub standard: String,
+    pub version: String,
+}
+
+fn major(version: &str) -> &str {
+    version.split('.').next().unwrap_or(version)
+}
+
+#[derive(Serialize, Deserialize)]
+struct EventEnvelope {
+    standard: String,
+    version: String,
+    #[serde(flatten)]
+    event: VaultEvent,
+}
+
+/// `u128` amounts are logged as decimal strings, like `U128` in near-sdk
+mod u128_string {
+    use alloc::string::String;
+
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &u128, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u128, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
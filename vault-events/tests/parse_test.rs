@@ -0,0 +1,117 @@
+use vault_events::{
+    parse_event_header, parse_event_log, ParseError, VaultDeposit, VaultDepositRefund, VaultEvent,
+    VaultRescue,
+};
+
+/// Test that a deposit log, as the vault writes it, decodes into typed amounts
+#[test]
+fn test_parse_deposit() {
+    let log = r#"EVENT_JSON:{"standard":"vault","version":"1.0.0","event":"vault_deposit","data":[{"sender_id":"alice.near","owner_id":"bob.near","token_id":"token1","assets":"1000","shares":"340282366920938463463374607431768211455"}]}"#;
+
+    let event = parse_event_log(log).unwrap();
+    assert_eq!(
+        event,
+        VaultEvent::VaultDeposit(vec![VaultDeposit {
+            sender_id: "alice.near".to_string(),
+            owner_id: "bob.near".to_string(),
+            token_id: "token1".to_string(),
+            assets: 1000,
+            shares: u128::MAX,
+            memo: None,
+            referrer: None,
+        }])
+    );
+}
+
+/// Test that a refunded deposit carries the error code of the refusal
+#[test]
+fn test_parse_deposit_refund() {
+    let log = r#"EVENT_JSON:{"standard":"vault","version":"1.0.0","event":"vault_deposit_refund","data":[{"sender_id":"alice.near","owner_id":"alice.near","token_id":"token1","amount":"1000","code":"SLIPPAGE_EXCEEDED","reason":"Deposit would mint 1000 shares, below the minimum of 2000"}]}"#;
+
+    let event = parse_event_log(log).unwrap();
+    assert_eq!(
+        event,
+        VaultEvent::VaultDepositRefund(vec![VaultDepositRefund {
+            sender_id: "alice.near".to_string(),
+            owner_id: "alice.near".to_string(),
+            token_id: "token1".to_string(),
+            amount: 1000,
+            code: "SLIPPAGE_EXCEEDED".to_string(),
+            reason: "Deposit would mint 1000 shares, below the minimum of 2000".to_string(),
+            memo: None,
+        }])
+    );
+    assert_eq!(event.to_event_log(), log);
+}
+
+/// Test that formatting an event and parsing it back gives the same event and log
+#[test]
+fn test_round_trip() {
+    let log = r#"EVENT_JSON:{"standard":"vault","version":"1.0.0","event":"vault_rescue","data":[{"token":"usdt.near","amount":"5","receiver_id":"owner.near"}]}"#;
+
+    let event = parse_event_log(log).unwrap();
+    assert_eq!(
+        event,
+        VaultEvent::VaultRescue(vec![VaultRescue {
+            token: "usdt.near".to_string(),
+            token_id: None,
+            amount: 5,
+            receiver_id: "owner.near".to_string(),
+        }])
+    );
+    assert_eq!(event.to_event_log(), log);
+    assert_eq!(parse_event_log(&event.to_event_log()).unwrap(), event);
+}
+
+/// Test that logs that are not vault events of this version are told apart
+#[test]
+fn test_reject_other_logs() {
+    assert!(matches!(
+        parse_event_log("Transfer 5 from alice.near"),
+        Err(ParseError::NotAnEvent)
+    ));
+
+    let log = r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_mint","data":[{"owner_id":"alice.near","amount":"5"}]}"#;
+    assert!(matches!(
+        parse_event_log(log),
+        Err(ParseError::OtherStandard(standard)) if standard == "nep141"
+    ));
+
+    let log =
+        r#"EVENT_JSON:{"standard":"vault","version":"2.0.0","event":"vault_deposit","data":[]}"#;
+    assert!(matches!(
+        parse_event_log(log),
+        Err(ParseError::UnsupportedVersion(version)) if version == "2.0.0"
+    ));
+
+    let log =
+        r#"EVENT_JSON:{"standard":"vault","version":"1.0.0","event":"vault_unknown","data":[]}"#;
+    assert!(matches!(
+        parse_event_log(log),
+        Err(ParseError::InvalidJson(_))
+    ));
+}
+
+/// Test that consumers can branch on the version, and that logs of another major
+/// are not decoded into the current shapes
+#[test]
+fn test_versions() {
+    let log = r#"EVENT_JSON:{"standard":"vault","version":"2.0.0","event":"vault_rescue","data":[{"token":"usdt.near","amount":"5","receiver_id":"owner.near"}]}"#;
+    assert_eq!(parse_event_header(log).unwrap().version, "2.0.0");
+    assert!(matches!(
+        parse_event_log(log),
+        Err(ParseError::UnsupportedVersion(version)) if version == "2.0.0"
+    ));
+
+    // Minor versions only add to the standard, so they still decode
+    let log = log.replace("2.0.0", "1.1.0");
+    assert_eq!(parse_event_header(&log).unwrap().version, "1.1.0");
+    assert!(parse_event_log(&log).is_ok());
+
+    // A failed withdrawal always carries its code
+    let log = r#"EVENT_JSON:{"standard":"vault","version":"1.0.0","event":"vault_withdraw_failed","data":[{"owner_id":"alice.near","receiver_id":"alice.near","token_id":"token1","shares":"5","assets":"5","reason":"Transfer failed"}]}"#;
+    assert!(matches!(
+        parse_event_log(log),
+        Err(ParseError::InvalidJson(_))
+    ));
+}